@@ -0,0 +1,190 @@
+//! Throughput of the receive chain's filters on a second of 96 kHz audio,
+//! sample by sample against a block at a time.
+
+use std::f32::consts::PI;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use waterfall_rs::configuration::{AudioSampleRate, Configuration, TunerSettings};
+use waterfall_rs::dsp::fir::FirBuilder;
+use waterfall_rs::dsp::resampler::Resampler;
+use waterfall_rs::dsp::rx::Rx;
+use waterfall_rs::statistics::Statistics;
+use waterfall_rs::units::Frequency;
+
+const SAMPLE_RATE: f32 = 96000.;
+const BLOCK: usize = 4096;
+
+fn input() -> Vec<f32> {
+    (0..SAMPLE_RATE as usize)
+        .map(|i| (2. * PI * 1000. * i as f32 / SAMPLE_RATE).sin())
+        .collect()
+}
+
+fn fir(c: &mut Criterion) {
+    let input = input();
+    let mut output = vec![0.; BLOCK];
+    let mut group = c.benchmark_group("FIR at 96 kHz");
+    group.throughput(Throughput::Elements(input.len() as u64));
+
+    for length in [31, 127, 255, 1023] {
+        let builder = FirBuilder::low_pass(
+            length,
+            Frequency::Hertz(SAMPLE_RATE),
+            Frequency::Hertz(3000.),
+        );
+
+        let mut fir = builder.build_asymmetric();
+        group.bench_with_input(
+            BenchmarkId::new("asymmetric update", length),
+            &input,
+            |b, input| b.iter(|| input.iter().map(|x| fir.update(*x)).sum::<f32>()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("asymmetric process", length),
+            &input,
+            |b, input| {
+                b.iter(|| {
+                    for block in input.chunks(BLOCK) {
+                        fir.process(block, &mut output[..block.len()]);
+                    }
+                    black_box(&output);
+                })
+            },
+        );
+
+        let mut fir = builder.build_symmetric();
+        group.bench_with_input(
+            BenchmarkId::new("symmetric update", length),
+            &input,
+            |b, input| b.iter(|| input.iter().map(|x| fir.update(*x)).sum::<f32>()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("symmetric process", length),
+            &input,
+            |b, input| {
+                b.iter(|| {
+                    for block in input.chunks(BLOCK) {
+                        fir.process(block, &mut output[..block.len()]);
+                    }
+                    black_box(&output);
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+// The same eight lanes as the filters' own dot product.
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    let mut lanes = [0f32; 8];
+    for (a, b) in a.chunks_exact(8).zip(b.chunks_exact(8)) {
+        for lane in 0..8 {
+            lanes[lane] += a[lane] * b[lane];
+        }
+    }
+    let tail = a.len() - a.len() % 8;
+    let tail: f32 = a[tail..].iter().zip(&b[tail..]).map(|(a, b)| a * b).sum();
+    lanes.iter().sum::<f32>() + tail
+}
+
+/// `SymmetricFir::process` runs short kernels over blocks unfolded, although
+/// folding the kernel halves the multiplies. This times both ways over the
+/// same block.
+fn folding(c: &mut Criterion) {
+    let input = input();
+    let mut output = vec![0.; BLOCK];
+    let mut folded = Vec::new();
+    let mut group = c.benchmark_group("Symmetric FIR block at 96 kHz");
+    group.throughput(Throughput::Elements(BLOCK as u64));
+
+    for length in [31, 63] {
+        let coeff = FirBuilder::low_pass(
+            length,
+            Frequency::Hertz(SAMPLE_RATE),
+            Frequency::Hertz(3000.),
+        )
+        .coefficients();
+        let buffer = &input[..BLOCK + length - 1];
+
+        group.bench_with_input(BenchmarkId::new("unfolded", length), &coeff, |b, coeff| {
+            b.iter(|| {
+                for (n, output) in output.iter_mut().enumerate() {
+                    *output = dot(coeff, &buffer[n..n + length]);
+                }
+                black_box(&output);
+            })
+        });
+
+        let half = length / 2;
+        folded.resize(half + 1, 0.);
+        group.bench_with_input(BenchmarkId::new("folded", length), &coeff, |b, coeff| {
+            b.iter(|| {
+                for (n, output) in output.iter_mut().enumerate() {
+                    let window = &buffer[n..n + length];
+                    let pairs = window[..half].iter().zip(window[half + 1..].iter().rev());
+                    for (folded, (a, b)) in folded.iter_mut().zip(pairs) {
+                        *folded = a + b;
+                    }
+                    folded[half] = window[half];
+                    *output = dot(&coeff[..=half], &folded);
+                }
+                black_box(&output);
+            })
+        });
+    }
+    group.finish();
+}
+
+fn resampler(c: &mut Criterion) {
+    let input = input();
+    let mut group = c.benchmark_group("Resampler at 96 kHz");
+    group.throughput(Throughput::Elements(input.len() as u64));
+
+    let mut resampler = Resampler::new(Frequency::Hertz(SAMPLE_RATE), Frequency::Hertz(4000.));
+    group.bench_function("update", |b| {
+        b.iter(|| {
+            input
+                .iter()
+                .filter_map(|x| resampler.update(*x))
+                .sum::<f32>()
+        })
+    });
+    group.bench_function("process", |b| {
+        b.iter(|| {
+            input
+                .chunks(BLOCK)
+                .map(|block| resampler.process(block).len())
+                .sum::<usize>()
+        })
+    });
+    group.finish();
+}
+
+fn rx(c: &mut Criterion) {
+    let input = input();
+    let config = Configuration {
+        audio_sample_rate: AudioSampleRate::F96000,
+        tuner: TunerSettings {
+            carrier: 1000.,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut rx = Rx::new(&config);
+    let mut stats = Statistics::default();
+
+    let mut group = c.benchmark_group("Rx at 96 kHz");
+    group.throughput(Throughput::Elements(input.len() as u64));
+    group.bench_function("run", |b| {
+        b.iter(|| {
+            for block in input.chunks(BLOCK) {
+                rx.run(block.to_vec(), &config, &mut stats);
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, fir, folding, resampler, rx);
+criterion_main!(benches);
@@ -5,14 +5,21 @@ use wasm_timer::Instant;
 
 use crate::configuration::Configuration;
 use crate::dsp::Processor;
-use crate::input::{Audio, Example, InputSource, Source};
-use crate::message::Message;
+use crate::input::{Audio, Example, Generator, InputSource, IqFile, RtlTcp, Source};
+use crate::message::DecoderEvent;
 use crate::messages::MessageCollector;
-use crate::scope::Scope;
+use crate::monitor::Monitor;
+use crate::rig::RigControl;
+use crate::scope::{Scope, Trace};
+use crate::spots::Spotter;
 use crate::statistics::Statistics;
-use crate::types::SampleType;
-use crate::ui::{About, Messages, ScopeViewer, Settings, Toolbar, WaterfallPlot, Windows};
+use crate::transmitter::Transmitter;
+use crate::types::{SampleType, Samples};
+use crate::ui::{
+    About, FilterViewer, Messages, ScopeViewer, Settings, TextView, Toolbar, WaterfallPlot, Windows,
+};
 use crate::units::Time;
+use crate::waterfall_labels::WaterfallLabels;
 
 pub struct App {
     image_rx: mpsc::Receiver<ColorImage>,
@@ -23,7 +30,12 @@ pub struct App {
     processor: Processor,
 
     scope: Scope,
+    monitor: Monitor,
     messages: MessageCollector,
+    labels: WaterfallLabels,
+    spots: Spotter,
+    transmitter: Transmitter,
+    rig: RigControl,
 
     source: Box<dyn Source>,
     input_source: InputSource,
@@ -38,15 +50,19 @@ pub struct App {
 impl App {
     pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
         let (image_tx, image_rx) = mpsc::channel::<ColorImage>(5);
-        let (sample_tx, sample_rx) = mpsc::channel::<Vec<SampleType>>(1024);
-        let (plot_tx, plot_rx) = mpsc::channel::<Vec<SampleType>>(5);
-        let (message_tx, message_rx) = mpsc::channel::<Box<dyn Message>>(5);
+        let (sample_tx, sample_rx) = mpsc::channel::<Samples>(1024);
+        let (plot_tx, plot_rx) = mpsc::channel::<Vec<Trace>>(5);
+        let (monitor_tx, monitor_rx) = mpsc::channel::<Vec<SampleType>>(16);
+        let (message_tx, message_rx) = mpsc::channel::<DecoderEvent>(16);
 
         let config = Configuration::default();
 
-        let processor = Processor::new(sample_rx, image_tx, plot_tx, message_tx, &config);
+        let processor = Processor::new(
+            sample_rx, image_tx, plot_tx, monitor_tx, message_tx, &config,
+        );
 
         let scope = Scope::new(plot_rx);
+        let monitor = Monitor::new(monitor_rx);
         let messages = MessageCollector::new(message_rx);
 
         let input_source = config.input_source;
@@ -62,7 +78,12 @@ impl App {
             processor,
 
             scope,
+            monitor,
             messages,
+            labels: WaterfallLabels::default(),
+            spots: Spotter::default(),
+            transmitter: Transmitter::default(),
+            rig: RigControl::default(),
 
             source,
             input_source,
@@ -75,10 +96,13 @@ impl App {
         }
     }
 
-    fn create_source(config: &Configuration, tx: mpsc::Sender<Vec<SampleType>>) -> Box<dyn Source> {
+    fn create_source(config: &Configuration, tx: mpsc::Sender<Samples>) -> Box<dyn Source> {
         match config.input_source {
             InputSource::Audio => Box::new(Audio::new(tx, config)),
             InputSource::Example => Box::new(Example::new(tx, config)),
+            InputSource::Generator => Box::new(Generator::new(tx, config)),
+            InputSource::IqFile => Box::new(IqFile::new(tx, config)),
+            InputSource::RtlTcp => Box::new(RtlTcp::new(tx, config)),
         }
     }
 }
@@ -97,7 +121,12 @@ impl eframe::App for App {
         self.processor.run(&self.config, &mut self.stats);
 
         self.scope.run(&mut self.config);
+        self.monitor.run(&mut self.config);
         self.messages.run(&mut self.config);
+        self.labels.update(&self.messages, self.processor.rows());
+        self.spots.run(&self.messages, &self.config);
+        self.transmitter.run(&self.config);
+        self.rig.run(&mut self.config);
 
         // egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
         //     egui::menu::bar(ui, |ui| {
@@ -130,10 +159,19 @@ impl eframe::App for App {
         egui::Window::new("🗠 Oscilloscope")
             .open(&mut self.show.scope)
             .show(ctx, |ui| {
-                let mut scope = ScopeViewer::new(&mut self.config, self.scope.get_plot_data());
+                let mut scope = ScopeViewer::new(&mut self.config, &mut self.scope);
                 scope.ui(ui);
             });
 
+        egui::Window::new("📈 Filters")
+            .open(&mut self.show.filters)
+            .show(ctx, |ui| {
+                let filters = self.processor.filters();
+                let mut viewer =
+                    FilterViewer::new(&mut self.config, &filters, self.processor.spectrum());
+                viewer.ui(ui);
+            });
+
         egui::Window::new("❔ About")
             .open(&mut self.show.about)
             .show(ctx, |ui| {
@@ -145,10 +183,19 @@ impl eframe::App for App {
             .default_height(100.)
             .open(&mut self.show.messages)
             .show(ctx, |ui| {
-                let mut messages = Messages::new(&self.messages);
+                let mut messages =
+                    Messages::new(&mut self.messages, &mut self.transmitter, &mut self.config);
                 messages.ui(ui);
             });
 
+        egui::Window::new("🖹 Text")
+            .default_height(200.)
+            .open(&mut self.show.text)
+            .show(ctx, |ui| {
+                let mut text = TextView::new(&mut self.messages);
+                text.ui(ui);
+            });
+
         egui::TopBottomPanel::bottom("status").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 if let Some(avg) = self.stats.rx.avg() {
@@ -163,13 +210,25 @@ impl eframe::App for App {
                     let t: Time = avg.into();
                     ui.label(format!("Render: {}", t));
                 }
+                if let Some(status) = self.source.status() {
+                    ui.label(format!("Input: {}", status));
+                }
+                if let Some(status) = &self.monitor.status {
+                    ui.label(format!("Monitor: {}", status));
+                }
+                if let Some(status) = &self.rig.status {
+                    ui.label(format!("Rig: {}", status));
+                }
+                if let Some(status) = &self.spots.status {
+                    ui.label(format!("Spots: {}", status));
+                }
             });
         });
 
         egui::CentralPanel::default()
             .frame(Frame::none().fill(ctx.style().visuals.faint_bg_color))
             .show(ctx, |ui| {
-                let mut waterfall = WaterfallPlot::new(&self.image, &mut self.config);
+                let mut waterfall = WaterfallPlot::new(&self.image, &mut self.config, &self.labels);
                 waterfall.ui(ui);
             });
 
@@ -30,20 +30,9 @@ impl Display for AudioSampleRate {
 }
 
 impl AudioSampleRate {
-    pub fn samples_to_skip(&self) -> usize {
-        (self.as_frequency() / self.baseband_sample_rate()) as usize
-    }
-
+    /// The rate the decoder works at, whatever the audio comes in at.
     pub fn baseband_sample_rate(&self) -> Frequency {
-        match *self {
-            AudioSampleRate::F8000 => Frequency::Hertz(100.0),
-            AudioSampleRate::F12000 => Frequency::Hertz(12000.0),
-            AudioSampleRate::F16000 => Frequency::Hertz(100.0),
-            AudioSampleRate::F22050 => Frequency::Hertz(105.0),
-            AudioSampleRate::F44100 => Frequency::Hertz(100.0),
-            AudioSampleRate::F48000 => Frequency::Hertz(12000.0),
-            AudioSampleRate::F96000 => Frequency::Hertz(100.0),
-        }
+        Frequency::Hertz(4000.0)
     }
 
     pub fn as_frequency(&self) -> Frequency {
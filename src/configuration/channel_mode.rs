@@ -0,0 +1,24 @@
+use std::fmt::Display;
+
+/// How to turn a multi-channel input into the mono signal we process.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ChannelMode {
+    Left,
+    Right,
+    Sum,
+    // Left is I, right is Q. The whole -Fs/2..Fs/2 band is processed.
+    Iq,
+}
+
+impl Display for ChannelMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match *self {
+            ChannelMode::Left => "Left",
+            ChannelMode::Right => "Right",
+            ChannelMode::Sum => "Sum",
+            ChannelMode::Iq => "I/Q",
+        };
+
+        write!(f, "{}", s)
+    }
+}
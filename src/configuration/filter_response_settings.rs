@@ -0,0 +1,15 @@
+#[derive(Clone)]
+pub struct FilterResponseSettings {
+    // Name of the filter to show.
+    pub filter: String,
+    pub spectrum: bool,
+}
+
+impl Default for FilterResponseSettings {
+    fn default() -> Self {
+        Self {
+            filter: "Prefilter".to_owned(),
+            spectrum: true,
+        }
+    }
+}
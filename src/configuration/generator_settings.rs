@@ -0,0 +1,62 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Waveform {
+    Tone,
+    Multitone,
+    Sweep,
+    WhiteNoise,
+    PinkNoise,
+    Rtty,
+    Cw,
+    Psk,
+}
+
+impl Waveform {
+    /// Whether the waveform carries `GeneratorSettings::text`.
+    pub fn is_keyed(&self) -> bool {
+        matches!(self, Waveform::Rtty | Waveform::Cw | Waveform::Psk)
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct GeneratorSettings {
+    pub waveform: Waveform,
+
+    // Tone frequency, the start of a sweep, the lowest of a multitone, or the
+    // space tone for RTTY.
+    pub frequency: f32,
+
+    pub tones: usize,
+    pub spacing: f32,
+
+    pub sweep_stop: f32,
+    // Seconds
+    pub sweep_period: f32,
+
+    // Mark is this far above space.
+    pub shift: f32,
+    // Symbols per second. For CW this is the dot rate, so WPM = 1.2 * baud.
+    pub baud: f32,
+    pub text: String,
+
+    pub noise: bool,
+    // Measured in a 2500 Hz bandwidth.
+    pub snr_db: f32,
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> Self {
+        Self {
+            waveform: Waveform::Rtty,
+            frequency: 930.0,
+            tones: 4,
+            spacing: 200.0,
+            sweep_stop: 3000.0,
+            sweep_period: 10.0,
+            shift: 170.0,
+            baud: 45.45,
+            text: "THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG 0123456789\r\n".to_string(),
+            noise: false,
+            snr_db: 10.0,
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use crate::configuration::DecoderType;
+
+/// Decoded messages written over the waterfall, where they were received.
+#[derive(Clone, PartialEq)]
+pub struct LabelSettings {
+    pub enabled: bool,
+    // Only messages from these decoders are labelled.
+    pub modes: Vec<DecoderType>,
+}
+
+impl Default for LabelSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            modes: DecoderType::ALL.to_vec(),
+        }
+    }
+}
+
+impl LabelSettings {
+    /// Whether messages in `mode`, as a message gives it, are labelled.
+    pub fn labels(&self, mode: &str) -> bool {
+        self.enabled && self.modes.iter().any(|decoder| decoder.mode() == mode)
+    }
+}
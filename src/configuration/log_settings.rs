@@ -0,0 +1,20 @@
+#[derive(Clone, PartialEq)]
+pub struct LogSettings {
+    // Append every decoded message to `path`, as JSON lines. Off until
+    // asked for, as the path is relative to wherever the app was started.
+    pub enabled: bool,
+    pub path: String,
+
+    // Where exports are written. The extension follows the format.
+    pub export_path: String,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: "messages.jsonl".to_owned(),
+            export_path: "messages".to_owned(),
+        }
+    }
+}
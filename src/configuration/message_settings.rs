@@ -0,0 +1,44 @@
+/// Colors a message whose text matches `pattern`, a regular expression.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Highlight {
+    pub pattern: String,
+    pub color: [u8; 3],
+}
+
+/// What the message window shows, and how.
+#[derive(Clone, PartialEq)]
+pub struct MessageSettings {
+    // A regular expression the text must match. Empty matches everything.
+    pub search: String,
+    // Only messages in this mode.
+    pub mode: Option<String>,
+    // Only messages received with the tuner at this frequency, in Hz.
+    pub channel: Option<f64>,
+    // Only messages received between these frequencies, in Hz.
+    pub frequency_range: Option<(f64, f64)>,
+
+    // Our callsign is highlighted ahead of the other rules.
+    pub callsign_color: [u8; 3],
+    // The first rule a message matches colors it.
+    pub highlights: Vec<Highlight>,
+
+    // Keep the newest message in view as they arrive.
+    pub follow: bool,
+}
+
+impl Default for MessageSettings {
+    fn default() -> Self {
+        Self {
+            search: String::new(),
+            mode: None,
+            channel: None,
+            frequency_range: None,
+            callsign_color: [0xff, 0x80, 0x00],
+            highlights: vec![Highlight {
+                pattern: r"\bCQ\b".to_owned(),
+                color: [0x60, 0xff, 0x60],
+            }],
+            follow: true,
+        }
+    }
+}
@@ -1,17 +1,50 @@
 mod audio_sample_rate;
+mod channel_mode;
+mod filter_response_settings;
+mod generator_settings;
+mod label_settings;
+mod log_settings;
+mod message_settings;
+mod monitor_settings;
+mod noise_settings;
+mod rig_settings;
+mod rtl_tcp_settings;
 mod scope_settings;
+mod sideband;
+mod spot_settings;
+mod station_settings;
 mod tuner_settings;
 
 use crate::input::InputSource;
 use crate::units::Frequency;
 pub use audio_sample_rate::AudioSampleRate;
-pub use scope_settings::{AxisMode, ScopeMode, ScopeSettings, TriggerMode, TriggerSettings};
+pub use channel_mode::ChannelMode;
+pub use filter_response_settings::FilterResponseSettings;
+pub use generator_settings::{GeneratorSettings, Waveform};
+pub use label_settings::LabelSettings;
+pub use log_settings::LogSettings;
+pub use message_settings::{Highlight, MessageSettings};
+pub use monitor_settings::{MonitorSettings, MonitorSource};
+pub use noise_settings::NoiseSettings;
+pub use rig_settings::RigSettings;
+pub use rtl_tcp_settings::RtlTcpSettings;
+pub use scope_settings::{
+    AxisMode, CursorSettings, Probe, ScopeMode, ScopeSettings, ScopeView, TriggerMode,
+    TriggerSettings,
+};
+pub use sideband::Sideband;
+pub use spot_settings::SpotSettings;
+pub use station_settings::StationSettings;
 pub use tuner_settings::{DecoderType, TunerSettings};
 
 #[derive(Clone)]
 pub struct Configuration {
     pub input_source: InputSource,
     pub input_device: String,
+    pub input_channel: ChannelMode,
+    // Raw interleaved I/Q, played back at `audio_sample_rate`.
+    pub iq_file: String,
+    pub rtl_tcp: RtlTcpSettings,
     pub audio_sample_rate: AudioSampleRate,
     pub fft_depth: usize,
     pub min_db: f32,
@@ -21,6 +54,19 @@ pub struct Configuration {
     // when the audio_sample_rate is much higher than needed.
     pub trim_hz: usize,
 
+    // What zero hertz in a complex stream corresponds to. Used to label the
+    // display, and as the frequency to tune an SDR to.
+    pub center_frequency: f64,
+
+    // The radio's dial frequency and sideband for real audio input, so that
+    // audio frequencies can be shown as RF.
+    pub dial_frequency: f64,
+    pub sideband: Sideband,
+
+    pub rig: RigSettings,
+
+    pub station: StationSettings,
+
     // This actually can only be set at compile time
     // waterfall_depth: usize,
 
@@ -30,9 +76,25 @@ pub struct Configuration {
     // [0.0, 1.0]
     pub scroll: f32,
 
+    pub labels: LabelSettings,
+
     pub tuner: TunerSettings,
 
+    pub noise: NoiseSettings,
+
+    pub monitor: MonitorSettings,
+
+    pub log: LogSettings,
+
+    pub messages: MessageSettings,
+
+    pub spots: SpotSettings,
+
     pub scope: ScopeSettings,
+
+    pub filter_response: FilterResponseSettings,
+
+    pub generator: GeneratorSettings,
 }
 
 impl Default for Configuration {
@@ -40,26 +102,101 @@ impl Default for Configuration {
         Self {
             input_source: InputSource::Example,
             input_device: "Default".to_owned(),
+            input_channel: ChannelMode::Left,
+            iq_file: "capture.cf32".to_owned(),
+            rtl_tcp: RtlTcpSettings::default(),
             audio_sample_rate: AudioSampleRate::F44100,
             fft_depth: 2048,
             min_db: -20.0,
             max_db: 5.0,
             trim_hz: 8000,
+            center_frequency: 0.0,
+            dial_frequency: 0.0,
+            sideband: Sideband::Usb,
+            rig: RigSettings::default(),
+            station: StationSettings::default(),
             zoom: 1.0,
             scroll: 0.0,
+            labels: LabelSettings::default(),
             tuner: TunerSettings::default(),
+            noise: NoiseSettings::default(),
+            monitor: MonitorSettings::default(),
+            log: LogSettings::default(),
+            messages: MessageSettings::default(),
+            spots: SpotSettings::default(),
             scope: ScopeSettings::default(),
+            filter_response: FilterResponseSettings::default(),
+            generator: GeneratorSettings::default(),
         }
     }
 }
 
 impl Configuration {
+    /// Whether the input is I/Q rather than real audio.
+    pub fn is_complex(&self) -> bool {
+        match self.input_source {
+            InputSource::Audio => self.input_channel == ChannelMode::Iq,
+            InputSource::IqFile | InputSource::RtlTcp => true,
+            _ => false,
+        }
+    }
+
+    /// The lowest frequency in the waterfall. Complex inputs have negative
+    /// frequencies, so their spectrum is trimmed either side of zero.
+    pub fn min_hz(&self) -> f32 {
+        if self.is_complex() {
+            -(self.effective_trim_hz() as f32)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn span_hz(&self) -> f32 {
+        self.effective_trim_hz() as f32 - self.min_hz()
+    }
+
     pub fn displayed_bandwidth(&self) -> f32 {
-        self.effective_trim_hz() as f32 / self.zoom
+        self.span_hz() / self.zoom
     }
 
     pub fn start_hz(&self) -> f32 {
-        ((self.effective_trim_hz() as f32) - self.displayed_bandwidth()) * self.scroll
+        self.min_hz() + (self.span_hz() - self.displayed_bandwidth()) * self.scroll
+    }
+
+    /// The RF frequency, in Hz, to show the user for a baseband frequency.
+    pub fn display_frequency(&self, f: Frequency) -> f64 {
+        let f = f.value() as f64;
+        if self.is_complex() {
+            self.center_frequency + f
+        } else {
+            match self.sideband {
+                Sideband::Usb => self.dial_frequency + f,
+                Sideband::Lsb => self.dial_frequency - f,
+            }
+        }
+    }
+
+    /// Whether `display_frequency` is on the air, rather than the baseband
+    /// frequency with no dial or center frequency to add.
+    pub fn has_rf_frequency(&self) -> bool {
+        if self.is_complex() {
+            self.center_frequency != 0.0
+        } else {
+            self.dial_frequency != 0.0
+        }
+    }
+
+    /// The inverse of `display_frequency`.
+    pub fn baseband_frequency(&self, rf: f64) -> Frequency {
+        let f = if self.is_complex() {
+            rf - self.center_frequency
+        } else {
+            match self.sideband {
+                Sideband::Usb => rf - self.dial_frequency,
+                Sideband::Lsb => self.dial_frequency - rf,
+            }
+        };
+        Frequency::Hertz(f as f32)
     }
 
     pub fn bin_hz(&self) -> f32 {
@@ -76,7 +213,7 @@ impl Configuration {
     }
 
     pub fn effective_len(&self) -> usize {
-        (self.effective_trim_hz() as f32 / self.bin_hz()) as usize
+        (self.span_hz() / self.bin_hz()) as usize
     }
 
     pub fn zoomed_length(&self) -> usize {
@@ -91,15 +228,113 @@ impl Configuration {
         self.scroll_start() + self.zoomed_length()
     }
 
+    /// Tune to `f`, a baseband frequency clicked on the waterfall. With QSY
+    /// enabled, a click outside the radio's passband retunes the radio
+    /// instead, so that the signal lands under the tuner's carrier.
+    pub fn click_to_tune(&mut self, f: Frequency) {
+        let outside = f.value() < 0.0 || f.value() > self.rig.passband;
+        if self.rig.enabled && self.rig.qsy && !self.is_complex() && outside {
+            let rf = self.display_frequency(f);
+            let carrier = self.tuner.carrier as f64;
+            self.dial_frequency = match self.sideband {
+                Sideband::Usb => rf - carrier,
+                Sideband::Lsb => rf + carrier,
+            };
+        } else {
+            self.tuner.carrier = f.value();
+        }
+    }
+
     pub fn zoomed_interval_to_hz(&self, interval: f32) -> Frequency {
         let offset_bins = self.zoomed_length() as f32 * interval;
         let bin = self.scroll_start() as f32 + offset_bins;
-        Frequency::Hertz(bin * self.bin_hz())
+        Frequency::Hertz(self.min_hz() + bin * self.bin_hz())
     }
 
     pub fn freq_to_zoom_interval(&self, f: Frequency) -> f32 {
-        let bin = (f.value() / self.bin_hz()) as i32;
+        let bin = ((f.value() - self.min_hz()) / self.bin_hz()) as i32;
         let result = (bin - self.scroll_start() as i32) as f32 / self.zoomed_length() as f32;
         result.clamp(0.0, 1.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complex_frequency_mapping() {
+        let config = Configuration {
+            input_source: InputSource::IqFile,
+            trim_hz: 4000,
+            ..Default::default()
+        };
+
+        assert_eq!(config.start_hz(), -4000.0);
+        assert_eq!(config.displayed_bandwidth(), 8000.0);
+        assert_eq!(config.zoomed_interval_to_hz(0.0), Frequency::Hertz(-4000.0));
+
+        let interval = config.freq_to_zoom_interval(Frequency::Hertz(-1000.0));
+        let f = config.zoomed_interval_to_hz(interval).value();
+        assert!((f + 1000.0).abs() <= config.bin_hz(), "{}", f);
+    }
+
+    #[test]
+    fn test_display_frequency() {
+        let mut config = Configuration {
+            dial_frequency: 14_080_000.0,
+            ..Default::default()
+        };
+        let audio = Frequency::Hertz(1500.0);
+
+        assert_eq!(config.display_frequency(audio), 14_081_500.0);
+
+        config.sideband = Sideband::Lsb;
+        assert_eq!(config.display_frequency(audio), 14_078_500.0);
+        assert_eq!(
+            config.baseband_frequency(config.display_frequency(audio)),
+            audio
+        );
+
+        config.input_source = InputSource::IqFile;
+        config.center_frequency = 7_000_000.0;
+        assert_eq!(
+            config.display_frequency(Frequency::Hertz(-1500.0)),
+            6_998_500.0
+        );
+
+        // Beyond what an f32 holds to the hertz.
+        config.center_frequency = 433_920_001.0;
+        assert_eq!(
+            config.display_frequency(Frequency::Hertz(1500.0)),
+            433_921_501.0
+        );
+    }
+
+    #[test]
+    fn test_click_to_tune() {
+        let mut config = Configuration {
+            dial_frequency: 14_080_000.0,
+            sideband: Sideband::Lsb,
+            ..Default::default()
+        };
+        config.rig.enabled = true;
+
+        // Without QSY only the tuner moves.
+        config.click_to_tune(Frequency::Hertz(5000.0));
+        assert_eq!(config.tuner.carrier, 5000.0);
+        assert_eq!(config.dial_frequency, 14_080_000.0);
+
+        // Inside the passband only the tuner moves.
+        config.rig.qsy = true;
+        config.click_to_tune(Frequency::Hertz(1000.0));
+        assert_eq!(config.tuner.carrier, 1000.0);
+        assert_eq!(config.dial_frequency, 14_080_000.0);
+
+        // Outside, the radio moves and the signal ends up under the carrier.
+        let rf = config.display_frequency(Frequency::Hertz(5000.0));
+        config.click_to_tune(Frequency::Hertz(5000.0));
+        assert_eq!(config.tuner.carrier, 1000.0);
+        assert_eq!(config.display_frequency(config.tuner.carrier()), rf);
+    }
+}
@@ -0,0 +1,35 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MonitorSource {
+    // Everything coming in.
+    Raw,
+    // The tuner's passband.
+    Channel,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct MonitorSettings {
+    pub enabled: bool,
+    pub source: MonitorSource,
+
+    // Move the tuner's carrier to `pitch`, so that every channel sounds the
+    // same wherever it is on the waterfall.
+    pub shift: bool,
+    pub pitch: f32,
+
+    // [0.0, 1.0]
+    pub volume: f32,
+    pub mute: bool,
+}
+
+impl Default for MonitorSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            source: MonitorSource::Channel,
+            shift: false,
+            pitch: 800.0,
+            volume: 0.5,
+            mute: false,
+        }
+    }
+}
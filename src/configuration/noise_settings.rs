@@ -0,0 +1,23 @@
+#[derive(Clone, PartialEq)]
+pub struct NoiseSettings {
+    // Keep what is predictable from the recent past, which noise isn't.
+    pub noise_reduction: bool,
+
+    // Remove steady carriers.
+    pub notch: bool,
+
+    pub blanker: bool,
+    // Blank samples this many times louder than the average level.
+    pub blanker_threshold: f32,
+}
+
+impl Default for NoiseSettings {
+    fn default() -> Self {
+        Self {
+            noise_reduction: false,
+            notch: false,
+            blanker: false,
+            blanker_threshold: 8.0,
+        }
+    }
+}
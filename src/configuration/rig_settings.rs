@@ -0,0 +1,24 @@
+#[derive(Clone, PartialEq)]
+pub struct RigSettings {
+    pub enabled: bool,
+    // Where rigctld is listening.
+    pub address: String,
+
+    // Retune the radio, rather than the tuner, when the waterfall is clicked
+    // outside the radio's passband.
+    pub qsy: bool,
+
+    // Width of the radio's filter in Hz. Read from the radio.
+    pub passband: f32,
+}
+
+impl Default for RigSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: "127.0.0.1:4532".to_string(),
+            qsy: false,
+            passband: 2400.0,
+        }
+    }
+}
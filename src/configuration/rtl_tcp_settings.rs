@@ -0,0 +1,22 @@
+#[derive(Clone, PartialEq)]
+pub struct RtlTcpSettings {
+    pub address: String,
+
+    // Tuner gain in dB, ignored when AGC is on.
+    pub gain: f32,
+    pub agc: bool,
+
+    // Crystal error correction in parts per million.
+    pub ppm: i32,
+}
+
+impl Default for RtlTcpSettings {
+    fn default() -> Self {
+        Self {
+            address: "127.0.0.1:1234".to_string(),
+            gain: 30.0,
+            agc: true,
+            ppm: 0,
+        }
+    }
+}
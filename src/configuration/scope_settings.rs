@@ -5,6 +5,16 @@ pub enum ScopeMode {
     Single,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScopeView {
+    // Traces against time.
+    Time,
+    // The discriminator overlaid a symbol at a time.
+    Eye,
+    // PSK symbols, I against Q.
+    Constellation,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum TriggerMode {
     Auto,
@@ -14,13 +24,21 @@ pub enum TriggerMode {
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum AxisMode {
+    // Follows the data.
     Fit,
+    // A fixed scale per division.
+    Divisions,
+    // Between set limits.
+    Manual,
 }
 
 #[derive(Clone)]
 pub struct TriggerSettings {
     pub mode: TriggerMode,
     pub level: f32,
+    // How far across the screen the trigger sits, 0 to 1. What comes
+    // before it is pre-trigger history.
+    pub position: f32,
 }
 
 impl Default for TriggerSettings {
@@ -28,6 +46,80 @@ impl Default for TriggerSettings {
         Self {
             mode: TriggerMode::Auto,
             level: 0.0,
+            position: 0.1,
+        }
+    }
+}
+
+/// A pair of time and a pair of level cursors, for measuring between.
+#[derive(Clone)]
+pub struct CursorSettings {
+    pub enabled: bool,
+    // In ms from the trigger.
+    pub times: [f32; 2],
+    pub levels: [f32; 2],
+}
+
+impl Default for CursorSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            times: [0.0, 10.0],
+            levels: [-0.5, 0.5],
+        }
+    }
+}
+
+/// Where in the receive chain the scope can look.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Probe {
+    // After the noise stages, at the input rate.
+    Input,
+    // The tuner's passband.
+    Channel,
+    // From here on at the decoder's rate.
+    Baseband,
+    Mark,
+    Space,
+    MarkEnvelope,
+    SpaceEnvelope,
+    // Mark envelope less space envelope.
+    Discriminator,
+    // 1 where a mark bit was sampled, -1 for space.
+    BitSamples,
+    // Recovered PSK31 symbols, one sample each.
+    PskI,
+    PskQ,
+}
+
+impl Probe {
+    pub const ALL: [Probe; 11] = [
+        Probe::Input,
+        Probe::Channel,
+        Probe::Baseband,
+        Probe::Mark,
+        Probe::Space,
+        Probe::MarkEnvelope,
+        Probe::SpaceEnvelope,
+        Probe::Discriminator,
+        Probe::BitSamples,
+        Probe::PskI,
+        Probe::PskQ,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Probe::Input => "Input",
+            Probe::Channel => "Channel",
+            Probe::Baseband => "Baseband",
+            Probe::Mark => "Mark filter",
+            Probe::Space => "Space filter",
+            Probe::MarkEnvelope => "Mark envelope",
+            Probe::SpaceEnvelope => "Space envelope",
+            Probe::Discriminator => "Discriminator",
+            Probe::BitSamples => "Bit samples",
+            Probe::PskI => "PSK symbols I",
+            Probe::PskQ => "PSK symbols Q",
         }
     }
 }
@@ -35,18 +127,83 @@ impl Default for TriggerSettings {
 #[derive(Clone)]
 pub struct ScopeSettings {
     pub mode: ScopeMode,
+    pub view: ScopeView,
     pub trigger: TriggerSettings,
     pub x_mode: AxisMode,
     pub y_mode: AxisMode,
+    // ms per division.
+    pub timebase: f32,
+    // Units per division.
+    pub scale: f32,
+    // In ms from the trigger.
+    pub x_range: (f32, f32),
+    pub y_range: (f32, f32),
+    // Shown together. The first is the one triggered on.
+    pub probes: Vec<Probe>,
+    pub cursors: CursorSettings,
+}
+
+impl ScopeSettings {
+    pub const X_DIVISIONS: usize = 10;
+    pub const Y_DIVISIONS: usize = 8;
+
+    /// The stretch of time on screen, in ms from the trigger, unless it
+    /// follows the data.
+    pub fn x_bounds(&self) -> Option<(f32, f32)> {
+        match self.x_mode {
+            AxisMode::Fit => None,
+            AxisMode::Divisions => {
+                let width = self.timebase * Self::X_DIVISIONS as f32;
+                let start = -self.trigger.position * width;
+                Some((start, start + width))
+            }
+            AxisMode::Manual => Some(self.x_range),
+        }
+    }
+
+    pub fn y_bounds(&self) -> Option<(f32, f32)> {
+        match self.y_mode {
+            AxisMode::Fit => None,
+            AxisMode::Divisions => {
+                let half = self.scale * Self::Y_DIVISIONS as f32 / 2.0;
+                Some((-half, half))
+            }
+            AxisMode::Manual => Some(self.y_range),
+        }
+    }
+
+    /// What the receive chain needs to pass on for the current view.
+    pub fn active_probes(&self) -> &[Probe] {
+        match self.view {
+            ScopeView::Time => &self.probes,
+            ScopeView::Eye => &[Probe::Discriminator, Probe::BitSamples],
+            ScopeView::Constellation => &[Probe::PskI, Probe::PskQ],
+        }
+    }
+
+    /// How much history to keep ahead of the trigger, in seconds.
+    pub fn pre_trigger(&self) -> f32 {
+        match self.x_bounds() {
+            Some((start, _)) => (-start / 1000.0).max(0.0),
+            None => self.trigger.position * self.timebase * Self::X_DIVISIONS as f32 / 1000.0,
+        }
+    }
 }
 
 impl Default for ScopeSettings {
     fn default() -> Self {
         Self {
             mode: ScopeMode::Run,
+            view: ScopeView::Time,
             trigger: TriggerSettings::default(),
             x_mode: AxisMode::Fit,
             y_mode: AxisMode::Fit,
+            timebase: 10.0,
+            scale: 0.25,
+            x_range: (-10.0, 90.0),
+            y_range: (-1.0, 1.0),
+            probes: vec![Probe::Channel],
+            cursors: CursorSettings::default(),
         }
     }
 }
@@ -0,0 +1,19 @@
+use std::fmt::Display;
+
+/// Which way audio frequencies run relative to the dial.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Sideband {
+    Usb,
+    Lsb,
+}
+
+impl Display for Sideband {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match *self {
+            Sideband::Usb => "USB",
+            Sideband::Lsb => "LSB",
+        };
+
+        write!(f, "{}", s)
+    }
+}
@@ -0,0 +1,21 @@
+/// Where spots of the stations we decode are sent.
+#[derive(Clone, PartialEq)]
+pub struct SpotSettings {
+    pub enabled: bool,
+    // DX cluster lines are appended here. Empty for none.
+    pub cluster_path: String,
+    // PSK Reporter datagrams go to `host`, such as a local collector.
+    pub psk_reporter: bool,
+    pub host: String,
+}
+
+impl Default for SpotSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cluster_path: "spots.txt".to_owned(),
+            psk_reporter: false,
+            host: "127.0.0.1:4739".to_owned(),
+        }
+    }
+}
@@ -0,0 +1,7 @@
+/// Who and where we are, for highlighting and reporting.
+#[derive(Clone, Default, PartialEq)]
+pub struct StationSettings {
+    pub callsign: String,
+    // Maidenhead grid locator, such as FN31pr.
+    pub locator: String,
+}
@@ -5,6 +5,17 @@ pub enum DecoderType {
     Rtty,
 }
 
+impl DecoderType {
+    pub const ALL: [DecoderType; 1] = [DecoderType::Rtty];
+
+    /// The mode its messages give.
+    pub fn mode(&self) -> &'static str {
+        match self {
+            DecoderType::Rtty => "RTTY",
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct TunerSettings {
     pub lower: f32,
@@ -3,12 +3,12 @@ use std::mem;
 
 use crate::types::SampleType;
 
-pub struct Aggregator {
+pub struct Aggregator<T = SampleType> {
     buffer_len: usize,
-    data: VecDeque<SampleType>,
+    data: VecDeque<T>,
 }
 
-impl Aggregator {
+impl<T> Aggregator<T> {
     pub fn new(buffer_len: usize) -> Self {
         Self {
             buffer_len,
@@ -16,17 +16,17 @@ impl Aggregator {
         }
     }
 
-    pub fn aggregate(&mut self, new_data: Vec<SampleType>) {
+    pub fn aggregate(&mut self, new_data: Vec<T>) {
         self.data.extend(new_data);
     }
 
-    pub fn return_slice(&mut self, data: Vec<SampleType>) {
-        let mut data_v: VecDeque<SampleType> = data.into();
+    pub fn return_slice(&mut self, data: Vec<T>) {
+        let mut data_v: VecDeque<T> = data.into();
         data_v.append(&mut self.data);
         mem::swap(&mut data_v, &mut self.data);
     }
 
-    pub fn get_slice(&mut self) -> Option<Vec<SampleType>> {
+    pub fn get_slice(&mut self) -> Option<Vec<T>> {
         if self.data.len() < self.buffer_len {
             return None;
         }
@@ -35,7 +35,7 @@ impl Aggregator {
         // self.data.rotate_left(self.buffer_len);
         // self.data.resize(self.data.len() - self.buffer_len, 0.0);
 
-        let subset: Vec<SampleType> = self.data.drain(..self.buffer_len).collect();
+        let subset: Vec<T> = self.data.drain(..self.buffer_len).collect();
 
         Some(subset)
     }
@@ -1,3 +1,5 @@
+mod psk;
 mod rtty;
 
-pub use rtty::Rtty;
+pub use psk::PskDemodulator;
+pub use rtty::{encode as ita2_encode, Rtty};
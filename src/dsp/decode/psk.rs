@@ -0,0 +1,184 @@
+use std::f32::consts::PI;
+use std::iter::zip;
+
+use crate::dsp::fir::{AsymmetricFir, FirBuilder};
+use crate::dsp::resampler::Resampler;
+use crate::types::{ComplexType, SampleType};
+use crate::units::Frequency;
+
+// Symbols are picked out at this rate, a whole number of samples each.
+const SAMPLE_RATE: Frequency = Frequency::Hertz(1000.);
+
+const MATCHED_FILTER_LENGTH: usize = 65;
+
+// Carrier loop noise bandwidth, and its damping.
+const LOOP_BANDWIDTH: f32 = 3.0;
+const DAMPING: f32 = 0.707;
+
+// How quickly symbol timing and level follow, per symbol.
+const TIMING_ALPHA: f32 = 0.05;
+const LEVEL_ALPHA: f32 = 0.05;
+
+/// Recovers BPSK symbols around the tuner's carrier: mixes to baseband,
+/// tracks the carrier phase with a Costas loop and samples where the symbol
+/// energy peaks. There is no varicode decoding; the symbols are for looking
+/// at.
+pub struct PskDemodulator {
+    input_sample_rate: f32,
+    mixer_phase: f32,
+    i_resampler: Resampler,
+    q_resampler: Resampler,
+    i_filter: AsymmetricFir,
+    q_filter: AsymmetricFir,
+
+    loop_phase: f32,
+    // Radians per sample.
+    loop_frequency: f32,
+    alpha: f32,
+    beta: f32,
+
+    // Average energy at each sample of a symbol.
+    energy: Vec<f32>,
+    clock: usize,
+    sampling_point: usize,
+
+    level: f32,
+}
+
+impl PskDemodulator {
+    pub const BAUD: Frequency = Frequency::Hertz(31.25);
+
+    pub fn new(input_sample_rate: Frequency) -> Self {
+        let samples_per_symbol = (SAMPLE_RATE.value() / Self::BAUD.value()).round() as usize;
+        let matched = FirBuilder::low_pass(MATCHED_FILTER_LENGTH, SAMPLE_RATE, Self::BAUD);
+
+        // Second order loop gains, for a phase detector with unit gain.
+        let theta = LOOP_BANDWIDTH / SAMPLE_RATE.value() / (DAMPING + 0.25 / DAMPING);
+        let d = 1. + 2. * DAMPING * theta + theta * theta;
+
+        Self {
+            input_sample_rate: input_sample_rate.value(),
+            mixer_phase: 0.,
+            i_resampler: Resampler::new(input_sample_rate, SAMPLE_RATE),
+            q_resampler: Resampler::new(input_sample_rate, SAMPLE_RATE),
+            i_filter: matched.build_asymmetric(),
+            q_filter: matched.build_asymmetric(),
+
+            loop_phase: 0.,
+            loop_frequency: 0.,
+            alpha: 4. * DAMPING * theta / d,
+            beta: 4. * theta * theta / d,
+
+            energy: vec![0.; samples_per_symbol],
+            clock: 0,
+            sampling_point: 0,
+
+            level: 0.,
+        }
+    }
+
+    /// Takes real samples at the input rate and returns a point for each
+    /// symbol, scaled so that they sit at about ±1.
+    pub fn process(&mut self, samples: &[SampleType], carrier: Frequency) -> Vec<ComplexType> {
+        let step = 2. * PI * carrier.value() / self.input_sample_rate;
+        let (i, q): (Vec<f32>, Vec<f32>) = samples
+            .iter()
+            .map(|x| {
+                let (sin, cos) = self.mixer_phase.sin_cos();
+                self.mixer_phase = (self.mixer_phase + step) % (2. * PI);
+                (x * cos, -x * sin)
+            })
+            .unzip();
+        let i = self.i_resampler.process(&i);
+        let q = self.q_resampler.process(&q);
+
+        let mut symbols = Vec::new();
+        for (i, q) in zip(i, q) {
+            let rotated = ComplexType::new(i, q) * ComplexType::from_polar(1., -self.loop_phase);
+            let y = ComplexType::new(
+                self.i_filter.update(rotated.re),
+                self.q_filter.update(rotated.im),
+            );
+
+            // About sin(2 * phase error) / 2, so either polarity locks.
+            let error = y.re * y.im / (y.norm_sqr() + f32::EPSILON);
+            self.loop_frequency += self.beta * error;
+            self.loop_phase =
+                (self.loop_phase + self.loop_frequency + self.alpha * error) % (2. * PI);
+
+            let energy = &mut self.energy[self.clock];
+            *energy += TIMING_ALPHA * (y.norm_sqr() - *energy);
+            if self.clock == self.sampling_point {
+                if self.level == 0. {
+                    // Starts from the first, rather than creeping up from
+                    // nothing.
+                    self.level = y.norm();
+                } else {
+                    self.level += LEVEL_ALPHA * (y.norm() - self.level);
+                }
+                if self.level > 0. {
+                    symbols.push(y / self.level);
+                }
+            }
+
+            self.clock += 1;
+            if self.clock == self.energy.len() {
+                self.clock = 0;
+                // Kept to one change a symbol, so a point can't be skipped or
+                // taken twice in the middle of one.
+                let energy = &self.energy;
+                self.sampling_point = (0..energy.len())
+                    .max_by(|a, b| energy[*a].partial_cmp(&energy[*b]).unwrap())
+                    .unwrap();
+            }
+        }
+        symbols
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FS: f32 = 8000.;
+
+    // BPSK with raised cosine reversals, as PSK31 sends.
+    fn bpsk(bits: &[bool], carrier: f32, phase: f32) -> Vec<f32> {
+        let samples_per_symbol = (FS / PskDemodulator::BAUD.value()) as usize;
+        let mut polarity = 1.;
+        let mut samples = Vec::new();
+        for bit in bits {
+            for n in 0..samples_per_symbol {
+                let progress = n as f32 / samples_per_symbol as f32;
+                let amplitude = if *bit {
+                    polarity
+                } else {
+                    polarity * (PI * progress).cos()
+                };
+                let t = samples.len() as f32 / FS;
+                samples.push(amplitude * (2. * PI * carrier * t + phase).sin());
+            }
+            if !bit {
+                polarity = -polarity;
+            }
+        }
+        samples
+    }
+
+    #[test]
+    fn test_symbols() {
+        // Pseudo-random, with a slightly off carrier to pull in.
+        let bits: Vec<bool> = (0..400u32).map(|n| (n * 7919 + n / 3) % 5 < 2).collect();
+        let samples = bpsk(&bits, 1000.5, 1.0);
+
+        let mut demodulator = PskDemodulator::new(Frequency::Hertz(FS));
+        let symbols = demodulator.process(&samples, Frequency::Hertz(1000.));
+
+        assert!((symbols.len() as i32 - bits.len() as i32).abs() <= 2);
+        // Once locked, every point is close to the real axis, at ±1.
+        for symbol in &symbols[symbols.len() / 2..] {
+            assert!((symbol.re.abs() - 1.).abs() < 0.25, "{}", symbol);
+            assert!(symbol.im.abs() < 0.25, "{}", symbol);
+        }
+    }
+}
@@ -15,6 +15,8 @@ pub struct MessageStateMachine {
     timeout: u32,
     counter: u32,
     message: String,
+    // What the last update decoded, to be shown as it arrives.
+    character: Option<char>,
 }
 
 impl MessageStateMachine {
@@ -28,12 +30,26 @@ impl MessageStateMachine {
             message: Default::default(),
             timeout,
             counter: timeout - 1,
+            character: None,
         }
     }
 
+    /// The character the last update decoded, if any. Line feeds come
+    /// through as line breaks, while carriage returns, shifts and nulls are
+    /// left out.
+    pub fn character(&self) -> Option<char> {
+        self.character
+    }
+
     pub fn update(&mut self, symbol: Option<u8>) -> Option<RttyMessage> {
+        self.character = None;
         if let Some(symbol) = symbol {
             let symbol = decode(symbol, self.symbol_state);
+            self.character = match symbol {
+                Symbol::Control(ControlType::Space | ControlType::LineFeed) => symbol.char(),
+                Symbol::Control(_) => None,
+                _ => symbol.char(),
+            };
             if let Symbol::Control(ref ct) = symbol {
                 match ct {
                     ControlType::Letters => {
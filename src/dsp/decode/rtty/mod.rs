@@ -2,36 +2,84 @@ mod message_state_machine;
 mod state_machine;
 mod symbols;
 
-use crate::dsp::downsample::Downsample;
-use crate::dsp::fir::{AsymmetricFir, FirBuilder};
-use crate::message::Message;
+use std::iter::zip;
+
+use crate::configuration::Probe;
+use crate::dsp::fir::AsymmetricFir;
+use crate::dsp::iir::{BiquadCascade, IirBuilder, Prototype};
+use crate::dsp::resampler::Resampler;
+use crate::dsp::response::FilterTaps;
+use crate::message::RttyMessage;
+use crate::scope::Trace;
 use crate::units::Frequency;
 
 use message_state_machine::MessageStateMachine;
 use state_machine::StateMachine;
 
+pub use symbols::encode;
+
+const SPACE: Frequency = Frequency::Hertz(930.);
+const MARK: Frequency = Frequency::Hertz(1100.);
+
+// The bandwidth SNR is quoted in.
+const SNR_BANDWIDTH: Frequency = Frequency::Hertz(2500.);
+const MIN_SNR: f32 = -20.;
+const MAX_SNR: f32 = 40.;
+
+// One biquad smooths the rectified tones as well as 31 FIR taps did.
+const ENVELOPE_ORDER: usize = 2;
+
+/// A bit as the state machine sampled it, with the envelope in the tone it
+/// was decided for and in the other one.
+#[derive(Clone, Copy)]
+struct Decision {
+    bit: bool,
+    signal: f32,
+    noise: f32,
+}
+
 pub struct Rtty {
     input_sample_rate: Frequency,
 
-    downsample: Downsample,
+    resampler: Resampler,
 
     space_filter: AsymmetricFir,
-    space_envelope: AsymmetricFir,
+    space_envelope: BiquadCascade,
 
     mark_filter: AsymmetricFir,
-    mark_envelope: AsymmetricFir,
+    mark_envelope: BiquadCascade,
 
     state_machine: StateMachine,
     message_state_machine: MessageStateMachine,
+
+    // The last two bits sampled, oldest first.
+    decisions: [Option<Decision>; 2],
+    // Envelope power in the tone each bit was decided for, and in the other
+    // one, summed over the message so far.
+    signal_power: f32,
+    noise_power: f32,
+    // From the envelopes' SNR, in the tone filters' noise bandwidth, to
+    // that in `SNR_BANDWIDTH`, in dB.
+    snr_offset: f32,
+    // How much of a steady tone's power gets through the other one's filter.
+    leakage: f32,
 }
 
 impl Rtty {
+    /// Where the tuner's carrier sits for the fixed tones we listen for.
+    pub const CARRIER: Frequency = Frequency::Hertz(884.55);
+
+    /// What the filters and timing run at, whatever the input rate.
+    pub const SAMPLE_RATE: Frequency = Frequency::Hertz(4000.);
+
+    pub const BAUD: Frequency = Frequency::Hertz(45.45);
+
     pub fn new(input_sample_rate: Frequency) -> Self {
-        let downsample = Downsample::new(input_sample_rate.into(), Frequency::Hertz(4000.), 101);
+        let resampler = Resampler::new(input_sample_rate, Rtty::SAMPLE_RATE);
 
         // let space_filter = FirBuilder::band_pass(
         //     101,
-        //     downsample.output_sample_rate,
+        //     resampler.output_sample_rate,
         //     Frequency::Hertz(930. - 45.45),
         //     Frequency::Hertz(930. + 45.45),
         // ).build_asymmetric();
@@ -128,7 +176,7 @@ impl Rtty {
 
         // let mark_filter = FirBuilder::band_pass(
         //     101,
-        //     downsample.output_sample_rate,
+        //     resampler.output_sample_rate,
         //     Frequency::Hertz(1100. - 45.45),
         //     Frequency::Hertz(1100. + 45.45),
         // )
@@ -222,13 +270,29 @@ impl Rtty {
             -0.005568659595559122,
         ]);
 
-        let envelope =
-            FirBuilder::low_pass(31, downsample.output_sample_rate, Frequency::Hertz(100.));
+        // Bessel, for its flat group delay, which keeps the envelopes'
+        // edges where the bits change.
+        let envelope = IirBuilder::low_pass(
+            Prototype::Bessel,
+            ENVELOPE_ORDER,
+            resampler.output_sample_rate,
+            Frequency::Hertz(100.),
+        );
 
-        let space_envelope = envelope.build_asymmetric();
-        let mark_envelope = envelope.build_asymmetric();
+        let space_envelope = envelope.build();
+        let mark_envelope = envelope.build();
 
-        dbg!(&downsample.output_sample_rate);
+        let sample_rate = resampler.output_sample_rate;
+        let space_taps = space_filter.coefficients();
+        let mark_taps = mark_filter.coefficients();
+        let noise_bandwidth = (noise_bandwidth(&space_taps, sample_rate, SPACE)
+            + noise_bandwidth(&mark_taps, sample_rate, MARK))
+            / 2.;
+        let leakage = (power_gain(&mark_taps, sample_rate, SPACE)
+            / power_gain(&space_taps, sample_rate, SPACE)
+            + power_gain(&space_taps, sample_rate, MARK)
+                / power_gain(&mark_taps, sample_rate, MARK))
+            / 2.;
 
         Self {
             input_sample_rate,
@@ -239,16 +303,57 @@ impl Rtty {
             space_envelope,
             mark_envelope,
 
-            state_machine: StateMachine::new(
-                downsample.output_sample_rate,
-                Frequency::Hertz(45.45),
-            ),
-            message_state_machine: MessageStateMachine::new(downsample.output_sample_rate),
-            downsample,
+            state_machine: StateMachine::new(resampler.output_sample_rate, Rtty::BAUD),
+            message_state_machine: MessageStateMachine::new(resampler.output_sample_rate),
+            resampler,
+
+            decisions: [None; 2],
+            signal_power: 0.,
+            noise_power: 0.,
+            snr_offset: 10. * (noise_bandwidth / SNR_BANDWIDTH.value()).log10(),
+            leakage,
         }
     }
 
-    pub fn update(&mut self, sample: f32) -> (Option<f32>, Option<Box<dyn Message>>) {
+    /// Every filter the decoder runs, in order.
+    pub fn filters(&self) -> Vec<FilterTaps> {
+        let sample_rate = self.resampler.output_sample_rate;
+        vec![
+            self.resampler.filter(),
+            FilterTaps::new("Mark", sample_rate, self.mark_filter.coefficients()),
+            FilterTaps::new("Space", sample_rate, self.space_filter.coefficients()),
+            FilterTaps::iir("Mark envelope", sample_rate, &self.mark_envelope),
+            FilterTaps::iir("Space envelope", sample_rate, &self.space_envelope),
+        ]
+    }
+
+    /// The SNR of the message so far. Only one tone is keyed at a time, so
+    /// the other one's filter hears just noise, and what the keyed one has
+    /// over it is signal.
+    fn snr(&self) -> f32 {
+        let noise = (self.noise_power - self.leakage * self.signal_power).max(f32::MIN_POSITIVE);
+        let signal = (self.signal_power - noise).max(0.);
+        let snr = 10. * (signal / noise).log10() + self.snr_offset;
+        snr.clamp(MIN_SNR, MAX_SNR)
+    }
+
+    /// Decodes a block of samples at the input rate. What each of `probes`
+    /// saw along the way is added to `traces`, and each character is added
+    /// to `text` as it's decoded.
+    pub fn process(
+        &mut self,
+        samples: &[f32],
+        probes: &[Probe],
+        traces: &mut Vec<Trace>,
+        text: &mut String,
+    ) -> Vec<RttyMessage> {
+        let sample_rate = self.resampler.output_sample_rate;
+        let mut probe = |probe: Probe, samples: &[f32]| {
+            if probes.contains(&probe) {
+                traces.push(Trace::new(probe, sample_rate, samples.to_vec()));
+            }
+        };
+
         // Space is at 930 Hz
         // Mark is at 1100 Hz
         // Spacing is 170 Hz
@@ -256,30 +361,109 @@ impl Rtty {
         // Tuner is at 884.55 Hz
         // Upper filter limit is at 260.9 Hz
 
-        // Decimate to 4000 Hz
-        if let Some(sample) = self.downsample.update(sample) {
-            let mark_val = self.mark_filter.update(sample);
-            let space_val = self.space_filter.update(sample);
+        // Resample to 4000 Hz
+        let samples = self.resampler.process(samples);
+        probe(Probe::Baseband, &samples);
+
+        let mut mark = vec![0.; samples.len()];
+        let mut space = vec![0.; samples.len()];
+        self.mark_filter.process(&samples, &mut mark);
+        self.space_filter.process(&samples, &mut space);
+        probe(Probe::Mark, &mark);
+        probe(Probe::Space, &space);
+        mark.iter_mut().for_each(|x| *x = x.abs());
+        space.iter_mut().for_each(|x| *x = x.abs());
 
-            let mark_env = self.mark_envelope.update(mark_val.abs());
-            let space_env = self.space_envelope.update(space_val.abs());
+        let mark_env: Vec<f32> = mark.iter().map(|x| self.mark_envelope.update(*x)).collect();
+        let space_env: Vec<f32> = space
+            .iter()
+            .map(|x| self.space_envelope.update(*x))
+            .collect();
+        probe(Probe::MarkEnvelope, &mark_env);
+        probe(Probe::SpaceEnvelope, &space_env);
 
-            let (c, mark) = if mark_env > space_env {
-                (self.state_machine.update(true), Some(1.))
-            } else {
-                (self.state_machine.update(false), Some(0.))
-            };
+        let discriminator: Vec<f32> = zip(&mark_env, &space_env)
+            .map(|(mark, space)| mark - space)
+            .collect();
+        probe(Probe::Discriminator, &discriminator);
 
+        let mut messages = Vec::new();
+        let mut bits = Vec::with_capacity(discriminator.len());
+        for (difference, (mark, space)) in zip(discriminator, zip(mark_env, space_env)) {
+            let c = self.state_machine.update(difference > 0.);
+            let sampled = self.state_machine.sampled();
+            if let Some(bit) = sampled {
+                let (signal, noise) = if bit { (mark, space) } else { (space, mark) };
+                self.decide(Decision { bit, signal, noise });
+            }
+            bits.push(match sampled {
+                Some(true) => 1.,
+                Some(false) => -1.,
+                None => 0.,
+            });
             let message = self.message_state_machine.update(c);
-            let message: Option<Box<dyn Message>> = if let Some(message) = message {
-                Some(Box::new(message))
-            } else {
-                None
-            };
-
-            (mark, message)
-        } else {
-            (None, None)
+            text.extend(self.message_state_machine.character());
+            if let Some(message) = message {
+                messages.push(message.with_snr(self.snr()));
+                self.signal_power = 0.;
+                self.noise_power = 0.;
+            }
+        }
+        probe(Probe::BitSamples, &bits);
+
+        messages
+    }
+
+    // Around a change of tone, the filters and envelopes still hold some of
+    // the other one, which would pass for noise. So only bits with the same
+    // on either side count towards the SNR.
+    fn decide(&mut self, decision: Decision) {
+        if let [Some(before), Some(middle)] = self.decisions {
+            if before.bit == middle.bit && middle.bit == decision.bit {
+                self.signal_power += middle.signal * middle.signal;
+                self.noise_power += middle.noise * middle.noise;
+            }
         }
+        self.decisions = [self.decisions[1], Some(decision)];
+    }
+}
+
+/// The filter's gain in power at `frequency`.
+fn power_gain(taps: &[f32], sample_rate: Frequency, frequency: Frequency) -> f32 {
+    let step = 2. * std::f32::consts::PI * frequency.value() / sample_rate.value();
+    let (re, im) = taps
+        .iter()
+        .enumerate()
+        .fold((0., 0.), |(re, im), (n, tap)| {
+            let phase = step * n as f32;
+            (re + tap * phase.cos(), im - tap * phase.sin())
+        });
+    re * re + im * im
+}
+
+/// The width of the ideal filter, with the same gain at `frequency`, that
+/// would let through as much white noise.
+fn noise_bandwidth(taps: &[f32], sample_rate: Frequency, frequency: Frequency) -> f32 {
+    let power = taps.iter().map(|tap| tap * tap).sum::<f32>();
+    power * sample_rate.value() / 2. / power_gain(taps, sample_rate, frequency)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_noise_bandwidth() {
+        // A moving average passes DC at unity, and as much noise as a brick
+        // wall to a tenth of Nyquist.
+        let sample_rate = Frequency::Hertz(4000.);
+        let average = [0.1; 10];
+        let bandwidth = noise_bandwidth(&average, sample_rate, Frequency::Hertz(0.));
+        assert!((bandwidth - 200.).abs() < 1e-3, "{}", bandwidth);
+
+        // The tone filters are 60 odd Hz wide, 16 dB down on 2500 Hz.
+        let rtty = Rtty::new(Frequency::Hertz(8000.));
+        assert!((rtty.snr_offset + 16.).abs() < 0.5, "{}", rtty.snr_offset);
+        assert!(rtty.leakage < 1e-4, "{}", rtty.leakage);
     }
 }
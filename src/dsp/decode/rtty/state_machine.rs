@@ -17,6 +17,8 @@ pub struct StateMachine {
     full_wait: u32,
     state: State,
     data: u8,
+    // The bit the last update sampled, if it sampled one.
+    sampled: Option<bool>,
 }
 
 impl StateMachine {
@@ -28,10 +30,17 @@ impl StateMachine {
             full_wait,
             state: Default::default(),
             data: Default::default(),
+            sampled: None,
         }
     }
 
+    /// Whether the last update sampled a bit, and if so, which.
+    pub fn sampled(&self) -> Option<bool> {
+        self.sampled
+    }
+
     pub fn update(&mut self, mark: bool) -> Option<u8> {
+        self.sampled = None;
         match self.state {
             State::Reset => {
                 self.data = 0;
@@ -48,6 +57,7 @@ impl StateMachine {
                 if wait != 0 {
                     self.state = State::WaitForAssertStart(wait - 1);
                 } else {
+                    self.sampled = Some(mark);
                     if !mark {
                         self.state = State::Bits(self.full_wait, 5);
                     } else {
@@ -60,6 +70,7 @@ impl StateMachine {
                 if wait != 0 {
                     self.state = State::Bits(wait - 1, bits);
                 } else {
+                    self.sampled = Some(mark);
                     if mark {
                         self.data = 0x80 | (self.data >> 1);
                     } else {
@@ -78,6 +89,7 @@ impl StateMachine {
                 if wait != 0 {
                     self.state = State::WaitForStop(wait - 1);
                 } else {
+                    self.sampled = Some(mark);
                     if mark {
                         self.data = self.data >> 3;
                         self.state = State::Reset;
@@ -125,3 +125,34 @@ pub fn decode(val: u8, state: SymbolState) -> Symbol {
         },
     }
 }
+
+fn code_for(c: char, state: SymbolState) -> Option<u8> {
+    (0..=0x1f).find(|&val| decode(val, state).char() == Some(c))
+}
+
+/// Inverse of `decode` for a whole string. The output starts with a letters
+/// shift and inserts further shifts as needed. Characters that ITA2 cannot
+/// represent are dropped.
+pub fn encode(text: &str) -> Vec<u8> {
+    let mut state = SymbolState::Letters;
+    let mut codes = vec![0b11111];
+
+    for c in text.chars().map(|c| c.to_ascii_uppercase()) {
+        if let Some(code) = code_for(c, state) {
+            codes.push(code);
+            continue;
+        }
+
+        let (other, shift) = match state {
+            SymbolState::Letters => (SymbolState::Figures, 0b11011),
+            SymbolState::Figures => (SymbolState::Letters, 0b11111),
+        };
+        if let Some(code) = code_for(c, other) {
+            codes.push(shift);
+            codes.push(code);
+            state = other;
+        }
+    }
+
+    codes
+}
@@ -0,0 +1,144 @@
+use std::f32::consts::PI;
+
+use crate::configuration::TunerSettings;
+use crate::dsp::fir::{FirBuilder, SymmetricFir};
+use crate::types::{ComplexType, SampleType};
+use crate::units::Frequency;
+
+const KERNEL_LENGTH: usize = 201;
+
+// Added either side of the tuner's passband.
+const GUARD: f32 = 50.0;
+
+/// Moves the tuner's passband in a complex stream to where a real decoder
+/// expects it. The passband is filtered at baseband first, so whatever sits
+/// on the other side of zero is rejected rather than folded on top of it.
+pub struct Downconverter {
+    sample_rate: f32,
+    cutoff: f32,
+    i_filter: SymmetricFir,
+    q_filter: SymmetricFir,
+    down_phase: f32,
+    up_phase: f32,
+}
+
+impl Downconverter {
+    pub fn new(sample_rate: Frequency) -> Self {
+        let mut downconverter = Self {
+            sample_rate: sample_rate.value(),
+            cutoff: 0.0,
+            i_filter: SymmetricFir::new(&[1.0]),
+            q_filter: SymmetricFir::new(&[1.0]),
+            down_phase: 0.0,
+            up_phase: 0.0,
+        };
+        downconverter.set_cutoff(GUARD);
+        downconverter
+    }
+
+    fn set_cutoff(&mut self, cutoff: f32) {
+        let cutoff = cutoff.clamp(GUARD, 0.45 * self.sample_rate);
+        if (cutoff - self.cutoff).abs() < 1.0 {
+            return;
+        }
+
+        let lpf = FirBuilder::low_pass(
+            KERNEL_LENGTH,
+            Frequency::Hertz(self.sample_rate),
+            Frequency::Hertz(cutoff),
+        );
+        self.i_filter = lpf.build_symmetric();
+        self.q_filter = lpf.build_symmetric();
+        self.cutoff = cutoff;
+    }
+
+    /// Returns a real signal in which the tuner's carrier sits at `target`.
+    pub fn run(
+        &mut self,
+        samples: &[ComplexType],
+        tuner: &TunerSettings,
+        target: Frequency,
+    ) -> Vec<SampleType> {
+        let middle = (tuner.lower + tuner.upper) / 2.0;
+        self.set_cutoff((tuner.upper - tuner.lower).abs() / 2.0 + GUARD);
+
+        let down_step = -2.0 * PI * (tuner.carrier + middle) / self.sample_rate;
+        let up_step = 2.0 * PI * (target.value() + middle) / self.sample_rate;
+
+        let (i, q): (Vec<_>, Vec<_>) = samples
+            .iter()
+            .map(|sample| {
+                let baseband = sample * ComplexType::from_polar(1.0, self.down_phase);
+                self.down_phase = (self.down_phase + down_step) % (2.0 * PI);
+                (baseband.re, baseband.im)
+            })
+            .unzip();
+
+        let mut filtered_i = vec![0.0; i.len()];
+        let mut filtered_q = vec![0.0; q.len()];
+        self.i_filter.process(&i, &mut filtered_i);
+        self.q_filter.process(&q, &mut filtered_q);
+
+        filtered_i
+            .into_iter()
+            .zip(filtered_q)
+            .map(|(i, q)| {
+                let shifted = ComplexType::new(i, q) * ComplexType::from_polar(1.0, self.up_phase);
+                self.up_phase = (self.up_phase + up_step) % (2.0 * PI);
+                shifted.re
+            })
+            .collect()
+    }
+
+    /// Like `run`, for real input. Only its positive frequencies are kept,
+    /// which carry half of each tone, so they're doubled to keep the level.
+    pub fn run_real(
+        &mut self,
+        samples: &[SampleType],
+        tuner: &TunerSettings,
+        target: Frequency,
+    ) -> Vec<SampleType> {
+        let samples: Vec<ComplexType> = samples
+            .iter()
+            .map(|sample| ComplexType::new(2.0 * sample, 0.0))
+            .collect();
+        self.run(&samples, tuner, target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(f: f32, sample_rate: f32, len: usize) -> Vec<ComplexType> {
+        (0..len)
+            .map(|n| ComplexType::from_polar(1.0, 2.0 * PI * f * n as f32 / sample_rate))
+            .collect()
+    }
+
+    fn rms(data: &[SampleType]) -> f32 {
+        (data.iter().map(|s| s * s).sum::<f32>() / data.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_image_rejection() {
+        let sample_rate = 8000.0;
+        let tuner = TunerSettings {
+            carrier: -2000.0,
+            lower: 0.0,
+            upper: 200.0,
+            ..Default::default()
+        };
+        let target = Frequency::Hertz(1000.0);
+
+        // In the passband
+        let mut downconverter = Downconverter::new(Frequency::Hertz(sample_rate));
+        let wanted = downconverter.run(&tone(-1900.0, sample_rate, 4000), &tuner, target);
+        assert!((rms(&wanted[KERNEL_LENGTH..]) - 0.5_f32.sqrt()).abs() < 0.05);
+
+        // The same distance from zero, on the other side
+        let mut downconverter = Downconverter::new(Frequency::Hertz(sample_rate));
+        let image = downconverter.run(&tone(1900.0, sample_rate, 4000), &tuner, target);
+        assert!(rms(&image[KERNEL_LENGTH..]) < 0.05);
+    }
+}
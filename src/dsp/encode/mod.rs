@@ -0,0 +1,3 @@
+mod rtty;
+
+pub use rtty::{keying as rtty_keying, RttyEncoder};
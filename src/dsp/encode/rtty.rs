@@ -0,0 +1,136 @@
+use std::f32::consts::PI;
+
+use crate::configuration::TunerSettings;
+use crate::dsp::decode::ita2_encode;
+use crate::types::SampleType;
+use crate::units::Frequency;
+
+const AMPLITUDE: f32 = 0.5;
+
+// Mark idle sent before and after the text, in bits.
+const IDLE: f32 = 10.0;
+
+/// One start bit, five data bits LSB first and 1.5 stop bits. Each entry is
+/// (mark, length in bits).
+pub fn frame(code: u8) -> [(bool, f32); 7] {
+    let bit = |i: u8| ((code >> i) & 1 == 1, 1.0);
+    [
+        (false, 1.0),
+        bit(0),
+        bit(1),
+        bit(2),
+        bit(3),
+        bit(4),
+        (true, 1.5),
+    ]
+}
+
+/// Frames for `text`, back to back.
+pub fn keying(text: &str) -> Vec<(bool, f32)> {
+    ita2_encode(text).into_iter().flat_map(frame).collect()
+}
+
+/// Phase-continuous AFSK modulator for ITA2 text.
+pub struct RttyEncoder {
+    sample_rate: f32,
+    space: f32,
+    shift: f32,
+    baud: f32,
+    phase: f32,
+}
+
+impl RttyEncoder {
+    pub fn new(
+        sample_rate: Frequency,
+        space: Frequency,
+        shift: Frequency,
+        baud: Frequency,
+    ) -> Self {
+        Self {
+            sample_rate: sample_rate.value(),
+            space: space.value(),
+            shift: shift.value(),
+            baud: baud.value(),
+            phase: 0.0,
+        }
+    }
+
+    /// 170 Hz shift, 45.45 Bd, placed in the tuner passband the way `Rtty`
+    /// expects: space is one baud above the carrier.
+    pub fn from_tuner(tuner: &TunerSettings, sample_rate: Frequency) -> Self {
+        let baud = Frequency::Hertz(45.45);
+        Self::new(
+            sample_rate,
+            tuner.carrier() + baud,
+            Frequency::Hertz(170.0),
+            baud,
+        )
+    }
+
+    /// Modulate `text` with a mark idle on either side. The phase carries
+    /// over between calls.
+    pub fn encode(&mut self, text: &str) -> Vec<SampleType> {
+        let mut bits = vec![(true, IDLE)];
+        bits.extend(keying(text));
+        bits.push((true, IDLE));
+
+        let samples_per_bit = self.sample_rate / self.baud;
+        let mut samples = Vec::new();
+        let mut end = 0.0;
+        for (mark, length) in bits {
+            let f = if mark {
+                self.space + self.shift
+            } else {
+                self.space
+            };
+            let step = 2.0 * PI * f / self.sample_rate;
+
+            end += length * samples_per_bit;
+            while (samples.len() as f32) < end {
+                samples.push(AMPLITUDE * self.phase.sin());
+                self.phase = (self.phase + step) % (2.0 * PI);
+            }
+        }
+
+        samples
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame() {
+        // 'A' is sent as 11000
+        assert_eq!(
+            frame(0b00011),
+            [
+                (false, 1.0),
+                (true, 1.0),
+                (true, 1.0),
+                (false, 1.0),
+                (false, 1.0),
+                (false, 1.0),
+                (true, 1.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_length() {
+        let sample_rate = Frequency::Hertz(8000.0);
+        let baud = Frequency::Hertz(50.0);
+        let mut encoder = RttyEncoder::new(
+            sample_rate,
+            Frequency::Hertz(1000.0),
+            Frequency::Hertz(170.0),
+            baud,
+        );
+
+        // Letters shift plus two characters, 7.5 bits each, plus idle.
+        let samples = encoder.encode("AB");
+        let bits = 3.0 * 7.5 + 2.0 * IDLE;
+        assert_eq!(samples.len(), (bits * 8000.0 / 50.0) as usize);
+    }
+}
@@ -1,26 +1,44 @@
-use std::collections::VecDeque;
-use std::iter::zip;
+use crate::dsp::fir::block::{correlate, dot, DelayLine};
+use crate::dsp::fir::overlap_save::{OverlapSave, OVERLAP_SAVE_LENGTH};
 
 #[derive(Debug)]
 pub struct AsymmetricFir {
     coeff: Vec<f32>,
-    state: VecDeque<f32>,
+    delay_line: DelayLine,
+    overlap_save: Option<OverlapSave>,
 }
 
 impl AsymmetricFir {
     pub fn new(coeff: &[f32]) -> Self {
+        let overlap_save = if coeff.len() >= OVERLAP_SAVE_LENGTH {
+            Some(OverlapSave::new(coeff))
+        } else {
+            None
+        };
         Self {
             coeff: Vec::from(coeff),
-            state: vec![f32::default(); coeff.len()].into(),
+            delay_line: DelayLine::new(coeff.len()),
+            overlap_save,
         }
     }
 
+    pub fn coefficients(&self) -> Vec<f32> {
+        self.coeff.clone()
+    }
+
     pub fn update(&mut self, input: f32) -> f32 {
-        self.state.pop_front();
-        self.state.push_back(input);
+        dot(&self.coeff, self.delay_line.push(input))
+    }
 
-        zip(self.coeff.iter(), self.state.iter())
-            .map(|(c, x)| c * x)
-            .sum()
+    /// Same as calling `update` for each sample, but faster.
+    pub fn process(&mut self, input: &[f32], output: &mut [f32]) {
+        assert_eq!(input.len(), output.len());
+        let buffer = self.delay_line.extend(input);
+        match &mut self.overlap_save {
+            Some(overlap_save) if input.len() >= self.coeff.len() => {
+                overlap_save.correlate(buffer, output)
+            }
+            _ => correlate(&self.coeff, buffer, output),
+        }
     }
 }
@@ -0,0 +1,95 @@
+// How many samples the delay line takes before it moves its history back to
+// the start of the buffer.
+const SLACK: usize = 4096;
+
+/// Sum of products, over eight lanes so the compiler can vectorize it.
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    let mut lanes = [0f32; 8];
+    let a_chunks = a.chunks_exact(8);
+    let b_chunks = b.chunks_exact(8);
+    let tail: f32 = a_chunks
+        .remainder()
+        .iter()
+        .zip(b_chunks.remainder())
+        .map(|(a, b)| a * b)
+        .sum();
+    for (a, b) in a_chunks.zip(b_chunks) {
+        for lane in 0..8 {
+            lanes[lane] += a[lane] * b[lane];
+        }
+    }
+    lanes.iter().sum::<f32>() + tail
+}
+
+/// `output[n]` is the kernel against `buffer[n..]`, for a buffer
+/// `coeff.len() - 1` longer than the output.
+pub fn correlate(coeff: &[f32], buffer: &[f32], output: &mut [f32]) {
+    assert_eq!(buffer.len(), output.len() + coeff.len() - 1);
+    for (n, output) in output.iter_mut().enumerate() {
+        *output = dot(coeff, &buffer[n..n + coeff.len()]);
+    }
+}
+
+/// The samples a kernel of `length` taps spans, kept contiguous and oldest
+/// first, so whole blocks can be filtered from one slice.
+#[derive(Debug)]
+pub struct DelayLine {
+    length: usize,
+    buffer: Vec<f32>,
+}
+
+impl DelayLine {
+    pub fn new(length: usize) -> Self {
+        assert!(length > 0);
+        let mut buffer = Vec::with_capacity(length - 1 + SLACK);
+        buffer.resize(length - 1, 0.);
+        Self { length, buffer }
+    }
+
+    // Drops all but the history the next sample needs.
+    fn compact(&mut self) {
+        let start = self.buffer.len() - (self.length - 1);
+        self.buffer.drain(..start);
+    }
+
+    /// Adds a sample, and returns the newest `length` samples.
+    pub fn push(&mut self, sample: f32) -> &[f32] {
+        if self.buffer.len() >= self.length - 1 + SLACK {
+            self.compact();
+        }
+        self.buffer.push(sample);
+        &self.buffer[self.buffer.len() - self.length..]
+    }
+
+    /// Adds a block, and returns it with the `length - 1` samples before it.
+    pub fn extend(&mut self, samples: &[f32]) -> &[f32] {
+        self.compact();
+        self.buffer.extend_from_slice(samples);
+        &self.buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_line() {
+        let mut delay_line = DelayLine::new(3);
+        assert_eq!(delay_line.push(1.), &[0., 0., 1.]);
+        assert_eq!(delay_line.extend(&[2., 3.]), &[0., 1., 2., 3.]);
+        for n in 4..10000 {
+            let window = delay_line.push(n as f32);
+            assert_eq!(window, &[n as f32 - 2., n as f32 - 1., n as f32]);
+        }
+        assert_eq!(delay_line.extend(&[]), &[9998., 9999.]);
+    }
+
+    #[test]
+    fn test_dot() {
+        let a: Vec<f32> = (0..21).map(|i| i as f32).collect();
+        let b: Vec<f32> = (0..21).map(|i| (i % 3) as f32).collect();
+        let expected: f32 = a.iter().zip(&b).map(|(a, b)| a * b).sum();
+        assert_eq!(dot(&a, &b), expected);
+    }
+}
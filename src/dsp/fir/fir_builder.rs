@@ -1,23 +1,23 @@
 use std::f32::consts::PI;
 
-use realfft::RealFftPlanner;
-
-use crate::dsp::fir::AsymmetricFir;
+use crate::dsp::fir::remez::{estimate_length, remez};
+use crate::dsp::fir::{AsymmetricFir, Band, SymmetricFir, Window};
 use crate::units::Frequency;
 
-fn low_pass_coeff(kernel_length: usize, f_c: f32) -> Vec<f32> {
-    let mut coeff: Vec<_> = (0..kernel_length)
-        .map(|i| {
-            if i == kernel_length / 2 {
-                let m = kernel_length as f32;
-                let i = i as f32;
-                2. * PI * f_c * (0.42 - (0.5 * ((2. * PI * i) / m)) + (0.8 * ((4. * PI * i) / m)))
+fn low_pass_coeff(kernel_length: usize, f_c: f32, window: Window) -> Vec<f32> {
+    let center = (kernel_length - 1) as f32 / 2.;
+    let mut coeff: Vec<_> = window
+        .weights(kernel_length)
+        .into_iter()
+        .enumerate()
+        .map(|(i, w)| {
+            let x = i as f32 - center;
+            let sinc = if x == 0. {
+                2. * PI * f_c
             } else {
-                let m = kernel_length as f32;
-                let i = i as f32;
-                (2. * PI * f_c * (i - m / 2.)).sin() / (i - m / 2.)
-                    * (0.42 - (0.5 * ((2. * PI * i) / m)) + (0.8 * ((4. * PI * i) / m)))
-            }
+                (2. * PI * f_c * x).sin() / x
+            };
+            sinc * w
         })
         .collect();
 
@@ -28,85 +28,88 @@ fn low_pass_coeff(kernel_length: usize, f_c: f32) -> Vec<f32> {
     coeff
 }
 
-fn spectrally_invert(coeff: &mut Vec<f32>) {
+/// Turns a unity gain low-pass into the matching high-pass. Only odd
+/// lengths have a center tap to do this with.
+fn spectrally_invert(coeff: &mut [f32]) {
+    assert_eq!(coeff.len() % 2, 1);
     coeff.iter_mut().for_each(|c| *c *= -1.);
     let center_idx = coeff.len() / 2;
     coeff[center_idx] += 1.;
-    let sum: f32 = coeff.iter().sum();
-    coeff.iter_mut().for_each(|c| *c /= sum);
 }
 
-fn high_pass_coeff(kernel_length: usize, f_c: f32) -> Vec<f32> {
-    let mut coeff = low_pass_coeff(kernel_length, f_c);
+fn high_pass_coeff(kernel_length: usize, f_c: f32, window: Window) -> Vec<f32> {
+    let mut coeff = low_pass_coeff(kernel_length, f_c, window);
     spectrally_invert(&mut coeff);
     coeff
 }
 
-fn band_pass_coeff(kernel_length: usize, f_c1: f32, f_c2: f32) -> Vec<f32> {
-    let c1 = high_pass_coeff(kernel_length, f_c1);
-    let c2 = low_pass_coeff(kernel_length, f_c2);
-
-    let mut real_planner = RealFftPlanner::<f64>::new();
-
-    let r2c = real_planner.plan_fft_forward(kernel_length);
-
-    let mut c1_input = r2c.make_input_vec();
-    for (idx, val) in c1.into_iter().enumerate() {
-        c1_input[idx] = val as f64;
-    }
-    let mut c1_spectrum = r2c.make_output_vec();
-    r2c.process(&mut c1_input, &mut c1_spectrum).unwrap();
-
-    let mut c2_input = r2c.make_input_vec();
-    for (idx, val) in c2.into_iter().enumerate() {
-        c2_input[idx] = val as f64;
-    }
-    let mut c2_spectrum = r2c.make_output_vec();
-    r2c.process(&mut c2_input, &mut c2_spectrum).unwrap();
-
-    let mut multiplied = r2c.make_output_vec();
-    for i in 0..c1_spectrum.len() {
-        multiplied[i] = c1_spectrum[i] * c2_spectrum[i];
-    }
-
-    let c2r = real_planner.plan_fft_inverse(kernel_length);
-
-    let mut outdata = c2r.make_output_vec();
-
-    c2r.process(&mut multiplied, &mut outdata).unwrap();
+/// The difference of two low-passes, which keeps unity gain in the passband.
+fn band_pass_coeff(kernel_length: usize, f_c1: f32, f_c2: f32, window: Window) -> Vec<f32> {
+    let c1 = low_pass_coeff(kernel_length, f_c1, window);
+    let c2 = low_pass_coeff(kernel_length, f_c2, window);
+    c2.iter().zip(c1).map(|(c2, c1)| c2 - c1).collect()
+}
 
-    let mut coeff: Vec<_> = outdata.into_iter().map(|f| f as f32).collect();
-    let sum: f32 = coeff.iter().sum();
-    coeff.iter_mut().for_each(|c| *c /= sum);
+fn band_reject_coeff(kernel_length: usize, f_c1: f32, f_c2: f32, window: Window) -> Vec<f32> {
+    let mut coeff = band_pass_coeff(kernel_length, f_c1, f_c2, window);
+    spectrally_invert(&mut coeff);
     coeff
 }
 
+/// Windowed ideal Hilbert transformer. Even taps are zero. The kernel is
+/// antisymmetric, so it is reversed here to suit the oldest-first order of
+/// `AsymmetricFir`.
+fn hilbert_coeff(kernel_length: usize, window: Window) -> Vec<f32> {
+    window
+        .weights(kernel_length)
+        .into_iter()
+        .enumerate()
+        .map(|(i, w)| {
+            let k = (kernel_length / 2) as i32 - i as i32;
+            if k % 2 == 0 {
+                0.
+            } else {
+                2. / (PI * k as f32) * w
+            }
+        })
+        .collect()
+}
+
 enum Mode {
     LowPass(usize, f32),
     HighPass(usize, f32),
     BandPass(usize, f32, f32),
     BandReject(usize, f32, f32),
+    Hilbert(usize),
+    Equiripple(usize, Frequency, Vec<Band>),
 }
 
 pub struct FirBuilder {
     mode: Mode,
+    window: Window,
 }
 
 impl FirBuilder {
+    fn new(mode: Mode) -> Self {
+        Self {
+            mode,
+            window: Window::default(),
+        }
+    }
+
     pub fn low_pass(length: usize, input_sample_rate: Frequency, cutoff: Frequency) -> Self {
         let cutoff = cutoff.value() / input_sample_rate.value();
         assert!(cutoff < 0.5);
-        Self {
-            mode: Mode::LowPass(length, cutoff),
-        }
+        Self::new(Mode::LowPass(length, cutoff))
     }
 
+    /// `length` must be odd: an even kernel has no center tap to invert
+    /// around, and is zero at Nyquist whatever the taps.
     pub fn high_pass(length: usize, input_sample_rate: Frequency, cutoff: Frequency) -> Self {
         let cutoff = cutoff.value() / input_sample_rate.value();
         assert!(cutoff < 0.5);
-        Self {
-            mode: Mode::HighPass(length, cutoff),
-        }
+        assert_eq!(length % 2, 1, "high-pass filters need an odd length");
+        Self::new(Mode::HighPass(length, cutoff))
     }
 
     pub fn band_pass(
@@ -120,11 +123,10 @@ impl FirBuilder {
         assert!(cutoff1 < 0.5);
         assert!(cutoff2 < 0.5);
         assert!(cutoff1 < cutoff2);
-        Self {
-            mode: Mode::BandPass(length, cutoff1, cutoff2),
-        }
+        Self::new(Mode::BandPass(length, cutoff1, cutoff2))
     }
 
+    /// `length` must be odd, as for `high_pass`.
     pub fn band_reject(
         length: usize,
         input_sample_rate: Frequency,
@@ -136,19 +138,246 @@ impl FirBuilder {
         assert!(cutoff1 < 0.5);
         assert!(cutoff2 < 0.5);
         assert!(cutoff1 < cutoff2);
-        Self {
-            mode: Mode::BandReject(length, cutoff1, cutoff2),
+        assert_eq!(length % 2, 1, "band-reject filters need an odd length");
+        Self::new(Mode::BandReject(length, cutoff1, cutoff2))
+    }
+
+    /// Shifts every frequency by -90 degrees. The output is delayed by
+    /// `length / 2` samples.
+    pub fn hilbert(length: usize) -> Self {
+        assert_eq!(length % 2, 1);
+        Self::new(Mode::Hilbert(length))
+    }
+
+    /// Parks-McClellan design, as short as `bands` allow. The bands must be
+    /// in order and must not overlap. The window is not used.
+    pub fn equiripple(input_sample_rate: Frequency, bands: &[Band]) -> Self {
+        assert!(!bands.is_empty());
+        for band in bands {
+            assert!(band.start.value() <= band.end.value());
+            assert!(band.end.value() <= input_sample_rate.value() / 2.);
+        }
+        let length = estimate_length(input_sample_rate, bands);
+        Self::new(Mode::Equiripple(length, input_sample_rate, bands.to_vec()))
+    }
+
+    /// Equiripple low-pass, flat to `pass` within `ripple` dB and at least
+    /// `attenuation` dB down from `stop`.
+    pub fn equiripple_low_pass(
+        input_sample_rate: Frequency,
+        pass: Frequency,
+        stop: Frequency,
+        ripple: f32,
+        attenuation: f32,
+    ) -> Self {
+        assert!(pass.value() < stop.value());
+        Self::equiripple(
+            input_sample_rate,
+            &[
+                Band::pass(Frequency::Hertz(0.), pass, ripple),
+                Band::stop(stop, input_sample_rate / 2., attenuation),
+            ],
+        )
+    }
+
+    pub fn with_window(mut self, window: Window) -> Self {
+        self.window = window;
+        self
+    }
+
+    pub fn length(&self) -> usize {
+        match self.mode {
+            Mode::LowPass(m, _)
+            | Mode::HighPass(m, _)
+            | Mode::BandPass(m, _, _)
+            | Mode::BandReject(m, _, _)
+            | Mode::Hilbert(m)
+            | Mode::Equiripple(m, _, _) => m,
+        }
+    }
+
+    pub fn coefficients(&self) -> Vec<f32> {
+        let window = self.window;
+        match &self.mode {
+            Mode::LowPass(m, f_c) => low_pass_coeff(*m, *f_c, window),
+            Mode::HighPass(m, f_c) => high_pass_coeff(*m, *f_c, window),
+            Mode::BandPass(m, f_c1, f_c2) => band_pass_coeff(*m, *f_c1, *f_c2, window),
+            Mode::BandReject(m, f_c1, f_c2) => band_reject_coeff(*m, *f_c1, *f_c2, window),
+            Mode::Hilbert(m) => hilbert_coeff(*m, window),
+            Mode::Equiripple(m, sample_rate, bands) => remez(*m, *sample_rate, bands),
         }
     }
 
     pub fn build_asymmetric(&self) -> AsymmetricFir {
-        let coeff = match self.mode {
-            Mode::LowPass(m, f_c) => low_pass_coeff(m, f_c),
-            Mode::HighPass(m, f_c) => high_pass_coeff(m, f_c),
-            Mode::BandPass(m, f_c1, f_c2) => band_pass_coeff(m, f_c1, f_c2),
-            _ => panic!(),
-        };
-
-        AsymmetricFir::new(&coeff)
+        AsymmetricFir::new(&self.coefficients())
+    }
+
+    /// Everything but the Hilbert transformer is symmetric, and odd lengths
+    /// can use `SymmetricFir`, which needs half the multiplies.
+    pub fn build_symmetric(&self) -> SymmetricFir {
+        if let Mode::Hilbert(_) = self.mode {
+            panic!("The Hilbert transformer is antisymmetric.");
+        }
+        SymmetricFir::new(&self.coefficients())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::iter::zip;
+
+    const FS: f32 = 8000.;
+
+    /// Magnitude response in dB at `hz`.
+    fn response(coeff: &[f32], hz: f32) -> f32 {
+        let w = 2. * PI * hz / FS;
+        let (re, im) = coeff.iter().enumerate().fold((0., 0.), |(re, im), (n, c)| {
+            (re + c * (w * n as f32).cos(), im - c * (w * n as f32).sin())
+        });
+        10. * (re * re + im * im).log10()
+    }
+
+    fn hz(value: f32) -> Frequency {
+        Frequency::Hertz(value)
+    }
+
+    fn is_symmetric(coeff: &[f32]) -> bool {
+        zip(coeff, coeff.iter().rev()).all(|(a, b)| (a - b).abs() < 1e-6)
+    }
+
+    #[test]
+    fn test_low_pass() {
+        // Centered on the middle of the kernel, odd length or even, so the
+        // phase is linear.
+        for length in [100, 101] {
+            let coeff = FirBuilder::low_pass(length, hz(FS), hz(1000.)).coefficients();
+            assert!(is_symmetric(&coeff), "{}", length);
+            assert!(response(&coeff, 0.).abs() < 0.01);
+            assert!((response(&coeff, 1000.) + 6.).abs() < 0.5);
+            assert!(response(&coeff, 1400.) < -70.);
+        }
+    }
+
+    #[test]
+    fn test_high_pass() {
+        // The inverted kernel sums to nearly nothing, so it must not be
+        // normalized again.
+        let coeff = FirBuilder::high_pass(101, hz(FS), hz(1000.)).coefficients();
+        assert!(is_symmetric(&coeff));
+        assert!(response(&coeff, FS / 2.).abs() < 0.01);
+        assert!(response(&coeff, 3000.).abs() < 0.01);
+        assert!(response(&coeff, 600.) < -70.);
+    }
+
+    #[test]
+    fn test_band_pass() {
+        let coeff = FirBuilder::band_pass(201, hz(FS), hz(1000.), hz(2000.)).coefficients();
+        assert_eq!(coeff.len(), 201);
+        assert!(is_symmetric(&coeff));
+        assert!(response(&coeff, 1500.).abs() < 0.01);
+        assert!((response(&coeff, 1000.) + 6.).abs() < 0.5);
+        assert!((response(&coeff, 2000.) + 6.).abs() < 0.5);
+        assert!(response(&coeff, 500.) < -70.);
+        assert!(response(&coeff, 2500.) < -70.);
+    }
+
+    #[test]
+    fn test_band_reject() {
+        let coeff = FirBuilder::band_reject(201, hz(FS), hz(1000.), hz(2000.)).coefficients();
+
+        assert!(response(&coeff, 0.).abs() < 0.01);
+        assert!(response(&coeff, 500.).abs() < 0.01);
+        assert!(response(&coeff, 3000.).abs() < 0.01);
+        assert!(response(&coeff, 1500.) < -60.);
+    }
+
+    #[test]
+    #[should_panic(expected = "high-pass filters need an odd length")]
+    fn test_high_pass_even_length() {
+        FirBuilder::high_pass(100, hz(FS), hz(1000.));
+    }
+
+    #[test]
+    #[should_panic(expected = "band-reject filters need an odd length")]
+    fn test_band_reject_even_length() {
+        FirBuilder::band_reject(200, hz(FS), hz(1000.), hz(2000.));
+    }
+
+    #[test]
+    fn test_windows() {
+        for (window, attenuation) in [
+            (Window::Hann, 40.),
+            (Window::Blackman, 70.),
+            (Window::Kaiser(90.), 85.),
+        ] {
+            let coeff = FirBuilder::low_pass(201, hz(FS), hz(1000.))
+                .with_window(window)
+                .coefficients();
+            assert!(response(&coeff, 0.).abs() < 0.01, "{:?}", window);
+            assert!(
+                response(&coeff, 1400.) < -attenuation,
+                "{:?} {}",
+                window,
+                response(&coeff, 1400.)
+            );
+        }
+    }
+
+    #[test]
+    fn test_equiripple() {
+        let builder = FirBuilder::equiripple_low_pass(hz(FS), hz(1000.), hz(1400.), 0.5, 60.);
+        let coeff = builder.coefficients();
+        assert_eq!(coeff.len(), builder.length());
+        assert!((50..120).contains(&coeff.len()), "{}", coeff.len());
+
+        for f in (0..=1000).step_by(50) {
+            let gain = response(&coeff, f as f32);
+            assert!(gain.abs() < 0.3, "{} Hz: {} dB", f, gain);
+        }
+        for f in (1400..=4000).step_by(50) {
+            let gain = response(&coeff, f as f32);
+            assert!(gain < -57., "{} Hz: {} dB", f, gain);
+        }
+    }
+
+    #[test]
+    fn test_symmetric_matches_asymmetric() {
+        let builder = FirBuilder::band_pass(63, hz(FS), hz(500.), hz(1500.));
+        let mut asymmetric = builder.build_asymmetric();
+        let mut symmetric = builder.build_symmetric();
+
+        for i in 0..500 {
+            let x = (i as f32 * 0.37).sin() + (i as f32 * 1.91).cos();
+            let expected = asymmetric.update(x);
+            assert!((symmetric.update(x) - expected).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_process_matches_update() {
+        // Short enough to sum directly, and long enough to go by FFT.
+        for length in [31, 255] {
+            let builder = FirBuilder::band_pass(length, hz(FS), hz(500.), hz(1500.));
+            let input: Vec<f32> = (0..2500)
+                .map(|i| (i as f32 * 0.37).sin() + (i as f32 * 1.91).cos())
+                .collect();
+            let mut reference = builder.build_asymmetric();
+            let expected: Vec<f32> = input.iter().map(|x| reference.update(*x)).collect();
+
+            let mut asymmetric = builder.build_asymmetric();
+            let mut symmetric = builder.build_symmetric();
+            let mut start = 0;
+            for block in [1, 7, 300, 1000, 13, 1179] {
+                let input = &input[start..start + block];
+                let expected = &expected[start..start + block];
+                let mut output = vec![0.; block];
+                asymmetric.process(input, &mut output);
+                assert!(zip(&output, expected).all(|(a, b)| (a - b).abs() < 1e-5));
+                symmetric.process(input, &mut output);
+                assert!(zip(&output, expected).all(|(a, b)| (a - b).abs() < 1e-5));
+                start += block;
+            }
+        }
     }
 }
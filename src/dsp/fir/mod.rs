@@ -1,7 +1,14 @@
 mod asymmetric_fir;
+mod block;
 mod fir_builder;
+mod overlap_save;
+mod remez;
 mod symmetric_fir;
+mod window;
 
 pub use asymmetric_fir::AsymmetricFir;
+pub(crate) use block::{dot, DelayLine};
 pub use fir_builder::FirBuilder;
+pub use remez::Band;
 pub use symmetric_fir::SymmetricFir;
+pub use window::Window;
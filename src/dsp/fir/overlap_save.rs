@@ -0,0 +1,108 @@
+use std::fmt;
+use std::sync::Arc;
+
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
+use rustfft::num_complex::Complex;
+
+/// Kernels at least this long filter blocks by FFT.
+pub const OVERLAP_SAVE_LENGTH: usize = 64;
+
+/// Filters blocks by FFT, for kernels long enough that it beats the direct
+/// sum. Each transform yields its size less the kernel's length of output.
+pub struct OverlapSave {
+    length: usize,
+    fft: Arc<dyn RealToComplex<f32>>,
+    ifft: Arc<dyn ComplexToReal<f32>>,
+    // Transform of the kernel reversed, with the inverse's 1/N folded in.
+    kernel: Vec<Complex<f32>>,
+    segment: Vec<f32>,
+    spectrum: Vec<Complex<f32>>,
+}
+
+impl fmt::Debug for OverlapSave {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OverlapSave")
+            .field("length", &self.length)
+            .field("size", &self.segment.len())
+            .finish()
+    }
+}
+
+impl OverlapSave {
+    /// `coeff` in the same order as `correlate` takes it.
+    pub fn new(coeff: &[f32]) -> Self {
+        let length = coeff.len();
+        let size = (4 * length).next_power_of_two();
+        let mut planner = RealFftPlanner::<f32>::new();
+        let fft = planner.plan_fft_forward(size);
+        let ifft = planner.plan_fft_inverse(size);
+
+        let mut segment = vec![0.; size];
+        for (sample, c) in segment.iter_mut().zip(coeff.iter().rev()) {
+            *sample = c / size as f32;
+        }
+        let mut kernel = fft.make_output_vec();
+        fft.process(&mut segment, &mut kernel).unwrap();
+
+        Self {
+            length,
+            spectrum: fft.make_output_vec(),
+            fft,
+            ifft,
+            kernel,
+            segment,
+        }
+    }
+
+    /// Same as `block::correlate`.
+    pub fn correlate(&mut self, buffer: &[f32], output: &mut [f32]) {
+        assert_eq!(buffer.len(), output.len() + self.length - 1);
+        let size = self.segment.len();
+        let step = size - (self.length - 1);
+
+        for (k, output) in output.chunks_mut(step).enumerate() {
+            let start = k * step;
+            let input = &buffer[start..(start + size).min(buffer.len())];
+            self.segment[..input.len()].copy_from_slice(input);
+            self.segment[input.len()..].fill(0.);
+
+            self.fft
+                .process(&mut self.segment, &mut self.spectrum)
+                .unwrap();
+            for (x, h) in self.spectrum.iter_mut().zip(&self.kernel) {
+                *x *= h;
+            }
+            // Both ends are real already, bar rounding.
+            self.spectrum[0].im = 0.;
+            self.spectrum.last_mut().unwrap().im = 0.;
+            self.ifft
+                .process(&mut self.spectrum, &mut self.segment)
+                .unwrap();
+
+            // The first length - 1 wrapped around.
+            let valid = &self.segment[self.length - 1..];
+            output.copy_from_slice(&valid[..output.len()]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::fir::block::correlate;
+
+    #[test]
+    fn test_overlap_save() {
+        let coeff: Vec<f32> = (0..100).map(|i| ((i * 7) % 13) as f32 - 6.).collect();
+        let buffer: Vec<f32> = (0..2099).map(|i| ((i * 5) % 11) as f32 - 5.).collect();
+
+        let mut expected = vec![0.; 2000];
+        correlate(&coeff, &buffer, &mut expected);
+        let mut output = vec![0.; 2000];
+        OverlapSave::new(&coeff).correlate(&buffer, &mut output);
+
+        for (a, b) in output.iter().zip(&expected) {
+            assert!((a - b).abs() < 1e-2, "{} {}", a, b);
+        }
+    }
+}
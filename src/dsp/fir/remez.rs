@@ -0,0 +1,241 @@
+use std::f64::consts::PI;
+
+use crate::units::Frequency;
+
+const GRID_DENSITY: usize = 16;
+const MAX_ITERATIONS: usize = 40;
+
+/// One band of an equiripple specification.
+#[derive(Clone, Copy, Debug)]
+pub struct Band {
+    pub start: Frequency,
+    pub end: Frequency,
+    pub gain: f32,
+    /// Largest allowed deviation from `gain`, as a ratio.
+    pub deviation: f32,
+}
+
+impl Band {
+    /// Unity gain, with at most `ripple` dB peak to peak.
+    pub fn pass(start: Frequency, end: Frequency, ripple: f32) -> Self {
+        let ripple = 10f32.powf(ripple / 20.);
+        Self {
+            start,
+            end,
+            gain: 1.,
+            deviation: (ripple - 1.) / (ripple + 1.),
+        }
+    }
+
+    /// At least `attenuation` dB down.
+    pub fn stop(start: Frequency, end: Frequency, attenuation: f32) -> Self {
+        Self {
+            start,
+            end,
+            gain: 0.,
+            deviation: 10f32.powf(-attenuation / 20.),
+        }
+    }
+}
+
+/// Odd length estimate for meeting `bands`, from Kaiser's formula applied to
+/// the narrowest transition and the tightest ripple.
+pub fn estimate_length(sample_rate: Frequency, bands: &[Band]) -> usize {
+    let transition = bands
+        .windows(2)
+        .map(|pair| (pair[1].start.value() - pair[0].end.value()) / sample_rate.value())
+        .fold(0.5, f32::min);
+    let deviation = bands.iter().map(|band| band.deviation).fold(1., f32::min);
+    assert!(transition > 0.);
+
+    let attenuation = -20. * deviation.log10();
+    let length = ((attenuation - 7.95) / (14.36 * transition)).ceil().max(3.) as usize;
+    length | 1
+}
+
+struct GridPoint {
+    frequency: f64,
+    desired: f64,
+    weight: f64,
+}
+
+fn grid(sample_rate: Frequency, bands: &[Band], extremals: usize) -> Vec<GridPoint> {
+    let step = 0.5 / (GRID_DENSITY * extremals) as f64;
+    let largest = bands
+        .iter()
+        .map(|band| band.deviation as f64)
+        .fold(0., f64::max);
+
+    let mut grid = Vec::new();
+    for band in bands {
+        let start = (band.start.value() / sample_rate.value()) as f64;
+        let end = (band.end.value() / sample_rate.value()) as f64;
+        let points = (((end - start) / step).ceil() as usize).max(1);
+        for i in 0..=points {
+            grid.push(GridPoint {
+                frequency: start + (end - start) * i as f64 / points as f64,
+                desired: band.gain as f64,
+                weight: largest / band.deviation as f64,
+            });
+        }
+    }
+    grid
+}
+
+/// Lagrange interpolation through the current extremals, in barycentric
+/// form.
+struct Interpolator {
+    x: Vec<f64>,
+    weights: Vec<f64>,
+    values: Vec<f64>,
+}
+
+impl Interpolator {
+    fn new(x: Vec<f64>, values: Vec<f64>) -> Self {
+        // The factor of 2 keeps the products from underflowing.
+        let weights = (0..x.len())
+            .map(|k| {
+                let product: f64 = (0..x.len())
+                    .filter(|j| *j != k)
+                    .map(|j| 2. * (x[k] - x[j]))
+                    .product();
+                1. / product
+            })
+            .collect();
+        Self { x, weights, values }
+    }
+
+    fn evaluate(&self, x: f64) -> f64 {
+        let mut numerator = 0.;
+        let mut denominator = 0.;
+        for k in 0..self.x.len() {
+            let d = x - self.x[k];
+            if d.abs() < 1e-12 {
+                return self.values[k];
+            }
+            numerator += self.weights[k] / d * self.values[k];
+            denominator += self.weights[k] / d;
+        }
+        numerator / denominator
+    }
+}
+
+/// Picks `count` alternating peaks of `error`, or None if there are too few.
+fn extremals(grid: &[GridPoint], error: &[f64], delta: f64, count: usize) -> Option<Vec<usize>> {
+    let mut peaks: Vec<usize> = Vec::new();
+    for i in 0..error.len() {
+        let left = i.checked_sub(1).map(|j| error[j].abs()).unwrap_or(0.);
+        let right = error.get(i + 1).map(|e| e.abs()).unwrap_or(0.);
+        let edge = i == 0
+            || i + 1 == error.len()
+            || grid[i].desired != grid[i - 1].desired
+            || grid[i].desired != grid[i + 1].desired;
+        let magnitude = error[i].abs();
+        if magnitude < delta.abs() * (1. - 1e-9) {
+            continue;
+        }
+        if !edge && (magnitude < left || magnitude < right) {
+            continue;
+        }
+
+        match peaks.last() {
+            // Of neighbouring peaks with the same sign keep the larger.
+            Some(&last) if error[last].signum() == error[i].signum() => {
+                if magnitude > error[last].abs() {
+                    *peaks.last_mut().unwrap() = i;
+                }
+            }
+            _ => peaks.push(i),
+        }
+    }
+
+    while peaks.len() > count {
+        let first = error[peaks[0]].abs();
+        let last = error[*peaks.last().unwrap()].abs();
+        if first < last {
+            peaks.remove(0);
+        } else {
+            peaks.pop();
+        }
+    }
+
+    (peaks.len() == count).then_some(peaks)
+}
+
+/// Parks-McClellan design of a symmetric, odd length kernel.
+pub fn remez(kernel_length: usize, sample_rate: Frequency, bands: &[Band]) -> Vec<f32> {
+    assert_eq!(kernel_length % 2, 1);
+    let half = kernel_length / 2;
+    let count = half + 2;
+    let grid = grid(sample_rate, bands, count);
+    assert!(grid.len() > count, "Too many taps for the bands.");
+
+    let x: Vec<f64> = grid
+        .iter()
+        .map(|point| (2. * PI * point.frequency).cos())
+        .collect();
+
+    let mut picked: Vec<usize> = (0..count)
+        .map(|k| k * (grid.len() - 1) / (count - 1))
+        .collect();
+    let mut interpolator = None;
+    for _ in 0..MAX_ITERATIONS {
+        let xs: Vec<f64> = picked.iter().map(|&i| x[i]).collect();
+        let b = Interpolator::new(xs.clone(), vec![0.; count]).weights;
+
+        let mut numerator = 0.;
+        let mut denominator = 0.;
+        let mut sign = 1.;
+        for (k, &i) in picked.iter().enumerate() {
+            numerator += b[k] * grid[i].desired;
+            denominator += sign * b[k] / grid[i].weight;
+            sign = -sign;
+        }
+        let delta = numerator / denominator;
+
+        // The response passes through the first count - 1 extremals, off the
+        // desired value by delta with alternating sign.
+        let mut sign = 1.;
+        let values: Vec<f64> = picked
+            .iter()
+            .map(|&i| {
+                let value = grid[i].desired - sign * delta / grid[i].weight;
+                sign = -sign;
+                value
+            })
+            .collect();
+        let current = Interpolator::new(xs[..count - 1].to_vec(), values[..count - 1].to_vec());
+
+        let error: Vec<f64> = grid
+            .iter()
+            .zip(&x)
+            .map(|(point, x)| point.weight * (point.desired - current.evaluate(*x)))
+            .collect();
+        let worst = error.iter().fold(0., |worst: f64, e| worst.max(e.abs()));
+        interpolator = Some(current);
+
+        match extremals(&grid, &error, delta, count) {
+            Some(next) if next != picked => picked = next,
+            _ => break,
+        }
+        if (worst - delta.abs()) <= 1e-6 * delta.abs() {
+            break;
+        }
+    }
+    let interpolator = interpolator.unwrap();
+
+    // Sample the amplitude response and take the inverse DFT.
+    let n = kernel_length as f64;
+    let amplitude: Vec<f64> = (0..=half)
+        .map(|k| interpolator.evaluate((2. * PI * k as f64 / n).cos()))
+        .collect();
+    (0..kernel_length)
+        .map(|i| {
+            let offset = i as f64 - half as f64;
+            let sum: f64 = (1..=half)
+                .map(|k| 2. * amplitude[k] * (2. * PI * k as f64 * offset / n).cos())
+                .sum();
+            ((amplitude[0] + sum) / n) as f32
+        })
+        .collect()
+}
@@ -1,10 +1,22 @@
-use std::collections::VecDeque;
 use std::iter::zip;
 
+use crate::dsp::fir::block::{correlate, dot, DelayLine};
+use crate::dsp::fir::overlap_save::{OverlapSave, OVERLAP_SAVE_LENGTH};
+
+/// FIR filter for symmetric kernels. Samples that share a coefficient are
+/// added before multiplying, which halves the multiplies per sample.
 #[derive(Debug)]
 pub struct SymmetricFir {
+    // First half of the kernel, center tap last.
     coeff: Vec<f32>,
-    state: VecDeque<f32>,
+    delay_line: DelayLine,
+    // Scratch for the folded samples.
+    folded: Vec<f32>,
+    // Blocks are long enough to vectorize over the whole kernel, which beats
+    // folding it: about twice the throughput at 31 and 63 taps in the
+    // "Symmetric FIR block" group of benches/fir.rs.
+    kernel: Vec<f32>,
+    overlap_save: Option<OverlapSave>,
 }
 
 impl SymmetricFir {
@@ -14,19 +26,57 @@ impl SymmetricFir {
             1,
             "SymmetricFir must have an odd number of coefficients."
         );
+        let scale = coeff.iter().fold(0f32, |max, c| max.max(c.abs()));
+        assert!(
+            zip(coeff.iter(), coeff.iter().rev()).all(|(a, b)| (a - b).abs() <= 1e-5 * scale),
+            "SymmetricFir coefficients must be symmetric."
+        );
 
+        let overlap_save = if coeff.len() >= OVERLAP_SAVE_LENGTH {
+            Some(OverlapSave::new(coeff))
+        } else {
+            None
+        };
+        let half = coeff.len() / 2 + 1;
         Self {
-            coeff: Vec::from(coeff),
-            state: vec![f32::default(); coeff.len()].into(),
+            coeff: Vec::from(&coeff[..half]),
+            delay_line: DelayLine::new(coeff.len()),
+            folded: vec![0.; half],
+            kernel: Vec::from(coeff),
+            overlap_save,
         }
     }
 
+    /// The whole kernel, unfolded.
+    pub fn coefficients(&self) -> Vec<f32> {
+        self.kernel.clone()
+    }
+
+    // The kernel against `window`, which is as long as the kernel.
+    fn apply(coeff: &[f32], folded: &mut [f32], window: &[f32]) -> f32 {
+        let half = coeff.len() - 1;
+        let pairs = zip(&window[..half], window[half + 1..].iter().rev());
+        for (folded, (a, b)) in zip(&mut folded[..half], pairs) {
+            *folded = a + b;
+        }
+        folded[half] = window[half];
+        dot(coeff, folded)
+    }
+
     pub fn update(&mut self, input: f32) -> f32 {
-        self.state.pop_front();
-        self.state.push_back(input);
+        let window = self.delay_line.push(input);
+        Self::apply(&self.coeff, &mut self.folded, window)
+    }
 
-        zip(self.coeff.iter(), self.state.iter())
-            .map(|(c, x)| c * x)
-            .sum()
+    /// Same as calling `update` for each sample, but faster.
+    pub fn process(&mut self, input: &[f32], output: &mut [f32]) {
+        assert_eq!(input.len(), output.len());
+        let buffer = self.delay_line.extend(input);
+        match &mut self.overlap_save {
+            Some(overlap_save) if input.len() >= self.kernel.len() => {
+                overlap_save.correlate(buffer, output)
+            }
+            _ => correlate(&self.kernel, buffer, output),
+        }
     }
 }
@@ -0,0 +1,85 @@
+use std::f32::consts::PI;
+
+/// Window applied to the ideal (sinc) kernel by `FirBuilder`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Window {
+    Hann,
+    #[default]
+    Blackman,
+    /// Kaiser window shaped for this stopband attenuation in dB.
+    Kaiser(f32),
+}
+
+// Modified Bessel function of the first kind, order zero.
+fn bessel_i0(x: f32) -> f32 {
+    let mut sum = 1.;
+    let mut term = 1.;
+    let mut k = 1.;
+    while term > 1e-9 * sum {
+        term *= (x / (2. * k)) * (x / (2. * k));
+        sum += term;
+        k += 1.;
+    }
+    sum
+}
+
+impl Window {
+    /// Kaiser's empirical beta for a stopband attenuation in dB.
+    fn kaiser_beta(attenuation: f32) -> f32 {
+        if attenuation > 50. {
+            0.1102 * (attenuation - 8.7)
+        } else if attenuation >= 21. {
+            0.5842 * (attenuation - 21.).powf(0.4) + 0.07886 * (attenuation - 21.)
+        } else {
+            0.
+        }
+    }
+
+    pub fn weights(&self, kernel_length: usize) -> Vec<f32> {
+        if kernel_length == 1 {
+            return vec![1.];
+        }
+
+        let m = (kernel_length - 1) as f32;
+        match *self {
+            Self::Hann => (0..kernel_length)
+                .map(|i| 0.5 - 0.5 * (2. * PI * i as f32 / m).cos())
+                .collect(),
+            Self::Blackman => (0..kernel_length)
+                .map(|i| {
+                    let n = i as f32;
+                    0.42 - 0.5 * (2. * PI * n / m).cos() + 0.08 * (4. * PI * n / m).cos()
+                })
+                .collect(),
+            Self::Kaiser(attenuation) => {
+                let beta = Self::kaiser_beta(attenuation);
+                (0..kernel_length)
+                    .map(|i| {
+                        let x = 2. * i as f32 / m - 1.;
+                        bessel_i0(beta * (1. - x * x).max(0.).sqrt()) / bessel_i0(beta)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weights() {
+        for window in [Window::Hann, Window::Blackman, Window::Kaiser(60.)] {
+            let weights = window.weights(11);
+            assert!((weights[5] - 1.).abs() < 1e-6, "{:?}", window);
+            for i in 0..11 {
+                assert!((weights[i] - weights[10 - i]).abs() < 1e-6, "{:?}", window);
+            }
+        }
+
+        assert!(Window::Hann.weights(11)[0].abs() < 1e-6);
+        // Without shaping, Kaiser is rectangular.
+        assert!(Window::Kaiser(0.).weights(11).iter().all(|w| *w == 1.));
+    }
+}
@@ -0,0 +1,85 @@
+use std::f32::consts::PI;
+
+use crate::dsp::fir::{AsymmetricFir, DelayLine, FirBuilder};
+use crate::types::SampleType;
+use crate::units::Frequency;
+
+// Long enough for the Hilbert transformer to hold down to about 100 Hz.
+const HILBERT_SECONDS: f32 = 0.025;
+
+/// Moves real audio up or down in frequency. The Hilbert transform gives the
+/// analytic signal, which has no negative frequencies to drag along, so it
+/// can be mixed without an image.
+pub struct FrequencyShifter {
+    sample_rate: f32,
+    hilbert: AsymmetricFir,
+    // Holds the real part back by the Hilbert transformer's delay.
+    delay_line: DelayLine,
+    phase: f32,
+}
+
+impl FrequencyShifter {
+    pub fn new(sample_rate: Frequency) -> Self {
+        let length = (HILBERT_SECONDS * sample_rate.value()) as usize / 2 * 2 + 1;
+        Self {
+            sample_rate: sample_rate.value(),
+            hilbert: FirBuilder::hilbert(length).build_asymmetric(),
+            delay_line: DelayLine::new(length / 2 + 1),
+            phase: 0.0,
+        }
+    }
+
+    /// Shifts `samples` by `shift`, which may be negative. Anything moved
+    /// below 0 Hz folds back over.
+    pub fn process(&mut self, samples: &[SampleType], shift: Frequency) -> Vec<SampleType> {
+        let mut imaginary = vec![0.0; samples.len()];
+        self.hilbert.process(samples, &mut imaginary);
+        let real = self.delay_line.extend(samples);
+
+        let step = 2.0 * PI * shift.value() / self.sample_rate;
+        real.iter()
+            .zip(imaginary)
+            .map(|(re, im)| {
+                let (sin, cos) = self.phase.sin_cos();
+                self.phase = (self.phase + step) % (2.0 * PI);
+                re * cos - im * sin
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FS: f32 = 8000.0;
+
+    // Amplitude of `hz` in `samples`.
+    fn amplitude(samples: &[f32], hz: f32) -> f32 {
+        let (i, q) = samples
+            .iter()
+            .enumerate()
+            .fold((0.0, 0.0), |(i, q), (n, x)| {
+                let phase = 2.0 * PI * hz * n as f32 / FS;
+                (i + x * phase.cos(), q + x * phase.sin())
+            });
+        2.0 * (i * i + q * q).sqrt() / samples.len() as f32
+    }
+
+    #[test]
+    fn test_shift() {
+        for (tone, shift) in [(1500.0, -700.0), (400.0, 1000.0)] {
+            let input: Vec<f32> = (0..2 * FS as usize)
+                .map(|n| (2.0 * PI * tone * n as f32 / FS).sin())
+                .collect();
+            let mut shifter = FrequencyShifter::new(Frequency::Hertz(FS));
+            let output = shifter.process(&input, Frequency::Hertz(shift));
+
+            let settled = &output[FS as usize..];
+            assert!((amplitude(settled, tone + shift) - 1.0).abs() < 0.02);
+            // Nothing at the image, or left where it was.
+            assert!(amplitude(settled, tone - shift) < 0.02);
+            assert!(amplitude(settled, tone) < 0.02);
+        }
+    }
+}
@@ -0,0 +1,212 @@
+mod morse;
+mod varicode;
+
+use std::f32::consts::PI;
+
+use rand::distributions::Distribution;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use statrs::distribution::Normal;
+
+use crate::configuration::{GeneratorSettings, Waveform};
+use crate::dsp::encode::rtty_keying;
+use crate::types::SampleType;
+use crate::units::Frequency;
+
+const AMPLITUDE: f32 = 0.25;
+
+// SNR is quoted in this bandwidth, as is customary for HF digital modes.
+const SNR_BANDWIDTH: Frequency = Frequency::Hertz(2500.0);
+
+// Keeps CW keying clicks out of the waterfall.
+const CW_RISE_TIME: f32 = 0.005;
+
+// Idle time between repeats of the text, in symbols.
+const RTTY_IDLE: f32 = 10.0;
+const PSK_IDLE: usize = 32;
+
+/// (level, length in symbols). The level is mark for RTTY, key down for CW
+/// and "no phase reversal" for PSK.
+type Keying = Vec<(bool, f32)>;
+
+fn psk_keying(text: &str) -> Keying {
+    let mut keying = vec![(false, 1.0); PSK_IDLE];
+    keying.extend(varicode::encode(text).into_iter().map(|bit| (bit, 1.0)));
+    keying
+}
+
+fn keying(settings: &GeneratorSettings) -> Keying {
+    match settings.waveform {
+        Waveform::Rtty => [(true, RTTY_IDLE)]
+            .into_iter()
+            .chain(rtty_keying(&settings.text))
+            .collect(),
+        Waveform::Cw => morse::encode(&settings.text),
+        Waveform::Psk => psk_keying(&settings.text),
+        _ => Vec::new(),
+    }
+}
+
+/// Synthesizes the test signal described by `GeneratorSettings`, optionally
+/// with additive white Gaussian noise. Keyed modes repeat their text forever.
+pub struct SignalGenerator {
+    settings: GeneratorSettings,
+    sample_rate: f32,
+
+    phases: Vec<f32>,
+    sweep_time: f32,
+
+    keying: Keying,
+    symbol: usize,
+    clock: f32,
+    envelope: f32,
+    polarity: f32,
+
+    pink: [f32; 7],
+    rng: StdRng,
+    white: Normal,
+    noise: Option<Normal>,
+}
+
+impl SignalGenerator {
+    pub fn new(settings: &GeneratorSettings, sample_rate: Frequency) -> Self {
+        let sample_rate = sample_rate.value();
+
+        let noise = if settings.noise {
+            let carrier_power = AMPLITUDE.powi(2) / 2.0;
+            let noise_power = carrier_power / 10_f32.powf(settings.snr_db / 10.0);
+            let variance = noise_power * (sample_rate / 2.0) / SNR_BANDWIDTH.value();
+            Some(Normal::new(0.0, variance.sqrt() as f64).unwrap())
+        } else {
+            None
+        };
+
+        Self {
+            settings: settings.clone(),
+            sample_rate,
+            phases: vec![0.0; settings.tones.max(1)],
+            sweep_time: 0.0,
+            keying: keying(settings),
+            symbol: 0,
+            clock: 0.0,
+            envelope: 0.0,
+            polarity: 1.0,
+            pink: Default::default(),
+            rng: StdRng::from_entropy(),
+            white: Normal::new(0.0, AMPLITUDE as f64).unwrap(),
+            noise,
+        }
+    }
+
+    /// Seed the noise source, so that the output is reproducible.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    pub fn settings(&self) -> &GeneratorSettings {
+        &self.settings
+    }
+
+    pub fn generate(&mut self, len: usize) -> Vec<SampleType> {
+        (0..len).map(|_| self.next_sample()).collect()
+    }
+
+    fn next_sample(&mut self) -> SampleType {
+        let frequency = self.settings.frequency;
+
+        let signal = match self.settings.waveform {
+            Waveform::Tone => AMPLITUDE * self.oscillate(0, frequency),
+            Waveform::Multitone => {
+                let tones = self.phases.len();
+                let sum: f32 = (0..tones)
+                    .map(|i| self.oscillate(i, frequency + i as f32 * self.settings.spacing))
+                    .sum();
+                AMPLITUDE * sum / tones as f32
+            }
+            Waveform::Sweep => {
+                let period = self.settings.sweep_period.max(f32::EPSILON);
+                let progress = self.sweep_time / period;
+                self.sweep_time = (self.sweep_time + 1.0 / self.sample_rate) % period;
+                let f = frequency + (self.settings.sweep_stop - frequency) * progress;
+                AMPLITUDE * self.oscillate(0, f)
+            }
+            Waveform::WhiteNoise => self.white.sample(&mut self.rng) as f32,
+            Waveform::PinkNoise => self.pink(),
+            Waveform::Rtty => {
+                let (mark, _, _) = self.next_symbol();
+                let f = if mark {
+                    frequency + self.settings.shift
+                } else {
+                    frequency
+                };
+                AMPLITUDE * self.oscillate(0, f)
+            }
+            Waveform::Cw => {
+                let (key_down, _, _) = self.next_symbol();
+                let step = 1.0 / (CW_RISE_TIME * self.sample_rate);
+                self.envelope = if key_down {
+                    (self.envelope + step).min(1.0)
+                } else {
+                    (self.envelope - step).max(0.0)
+                };
+                AMPLITUDE * self.envelope * self.oscillate(0, frequency)
+            }
+            Waveform::Psk => {
+                let (steady, progress, finished) = self.next_symbol();
+                // Reversals pass through zero on a raised cosine.
+                let amplitude = if steady {
+                    self.polarity
+                } else {
+                    self.polarity * (PI * progress).cos()
+                };
+                if finished && !steady {
+                    self.polarity = -self.polarity;
+                }
+                AMPLITUDE * amplitude * self.oscillate(0, frequency)
+            }
+        };
+
+        match &self.noise {
+            Some(noise) => signal + noise.sample(&mut self.rng) as f32,
+            None => signal,
+        }
+    }
+
+    fn oscillate(&mut self, index: usize, f: f32) -> f32 {
+        let phase = self.phases[index];
+        self.phases[index] = (phase + 2.0 * PI * f / self.sample_rate) % (2.0 * PI);
+        phase.sin()
+    }
+
+    /// Returns the current level, how far through the symbol we are, and
+    /// whether this was the symbol's last sample.
+    fn next_symbol(&mut self) -> (bool, f32, bool) {
+        let (level, length) = self.keying[self.symbol];
+        let progress = self.clock / length;
+
+        self.clock += self.settings.baud / self.sample_rate;
+        let finished = self.clock >= length;
+        if finished {
+            self.clock -= length;
+            self.symbol = (self.symbol + 1) % self.keying.len();
+        }
+
+        (level, progress, finished)
+    }
+
+    /// Paul Kellet's refined pink noise filter.
+    fn pink(&mut self) -> f32 {
+        let white = self.white.sample(&mut self.rng) as f32;
+        let b = &mut self.pink;
+        b[0] = 0.99886 * b[0] + white * 0.0555179;
+        b[1] = 0.99332 * b[1] + white * 0.0750759;
+        b[2] = 0.96900 * b[2] + white * 0.153852;
+        b[3] = 0.86650 * b[3] + white * 0.3104856;
+        b[4] = 0.55000 * b[4] + white * 0.5329522;
+        b[5] = -0.7616 * b[5] - white * 0.0168980;
+        let pink = b.iter().sum::<f32>() + white * 0.5362;
+        b[6] = white * 0.115926;
+        pink * 0.11
+    }
+}
@@ -0,0 +1,80 @@
+fn code(c: char) -> Option<&'static str> {
+    let code = match c {
+        'A' => ".-",
+        'B' => "-...",
+        'C' => "-.-.",
+        'D' => "-..",
+        'E' => ".",
+        'F' => "..-.",
+        'G' => "--.",
+        'H' => "....",
+        'I' => "..",
+        'J' => ".---",
+        'K' => "-.-",
+        'L' => ".-..",
+        'M' => "--",
+        'N' => "-.",
+        'O' => "---",
+        'P' => ".--.",
+        'Q' => "--.-",
+        'R' => ".-.",
+        'S' => "...",
+        'T' => "-",
+        'U' => "..-",
+        'V' => "...-",
+        'W' => ".--",
+        'X' => "-..-",
+        'Y' => "-.--",
+        'Z' => "--..",
+        '0' => "-----",
+        '1' => ".----",
+        '2' => "..---",
+        '3' => "...--",
+        '4' => "....-",
+        '5' => ".....",
+        '6' => "-....",
+        '7' => "--...",
+        '8' => "---..",
+        '9' => "----.",
+        '.' => ".-.-.-",
+        ',' => "--..--",
+        '?' => "..--..",
+        '/' => "-..-.",
+        '=' => "-...-",
+        '+' => ".-.-.",
+        '-' => "-....-",
+        '\'' => ".----.",
+        '(' => "-.--.",
+        ')' => "-.--.-",
+        ':' => "---...",
+        '"' => ".-..-.",
+        '@' => ".--.-.",
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// Key down/up periods, measured in dots. Ends with a word space so the text
+/// can be repeated back to back.
+pub fn encode(text: &str) -> Vec<(bool, f32)> {
+    let mut keying = Vec::new();
+
+    for c in text.chars().map(|c| c.to_ascii_uppercase()) {
+        if c.is_whitespace() {
+            keying.push((false, 7.0));
+            continue;
+        }
+
+        if let Some(code) = code(c) {
+            for element in code.chars() {
+                let length = if element == '.' { 1.0 } else { 3.0 };
+                keying.push((true, length));
+                keying.push((false, 1.0));
+            }
+            keying.push((false, 2.0));
+        }
+    }
+
+    keying.push((false, 7.0));
+    keying
+}
@@ -0,0 +1,140 @@
+// PSK31 varicode for ' ' through '~'.
+const PRINTABLE: [&str; 95] = [
+    "1",
+    "111111111",
+    "101011111",
+    "111110101",
+    "111011011",
+    "1011010101",
+    "1010111011",
+    "101111111",
+    "11111011",
+    "11110111",
+    "101101111",
+    "111011111",
+    "1110101",
+    "110101",
+    "1010111",
+    "110101111",
+    "10110111",
+    "10111101",
+    "11101101",
+    "11111111",
+    "101110111",
+    "101011011",
+    "101101011",
+    "110101101",
+    "110101011",
+    "110110111",
+    "11110101",
+    "110111101",
+    "111101101",
+    "1010101",
+    "111010111",
+    "1010101111",
+    "1010111101",
+    "1111101",
+    "11101011",
+    "10101101",
+    "10110101",
+    "1110111",
+    "11011011",
+    "11111101",
+    "101010101",
+    "1111111",
+    "111111101",
+    "101111101",
+    "11010111",
+    "10111011",
+    "11011101",
+    "10101011",
+    "11010101",
+    "111011101",
+    "10101111",
+    "1101111",
+    "1101101",
+    "101010111",
+    "110110101",
+    "101011101",
+    "101110101",
+    "101111011",
+    "1010101101",
+    "111110111",
+    "111101111",
+    "111111011",
+    "1010111111",
+    "101101101",
+    "1011011111",
+    "1011",
+    "1011111",
+    "101111",
+    "101101",
+    "11",
+    "111101",
+    "1011011",
+    "101011",
+    "1101",
+    "111101011",
+    "10111111",
+    "11011",
+    "111011",
+    "1111",
+    "111",
+    "111111",
+    "110111111",
+    "10101",
+    "10111",
+    "101",
+    "110111",
+    "1111011",
+    "1101011",
+    "11011111",
+    "1011101",
+    "111010101",
+    "1010110111",
+    "110111011",
+    "1010110101",
+    "1011010111",
+];
+
+fn code(c: char) -> Option<&'static str> {
+    match c {
+        '\n' => Some("11101"),
+        '\r' => Some("11111"),
+        ' '..='~' => Some(PRINTABLE[c as usize - ' ' as usize]),
+        _ => None,
+    }
+}
+
+/// Bits to send, where `false` is a phase reversal. Each character is
+/// followed by the "00" separator.
+pub fn encode(text: &str) -> Vec<bool> {
+    text.chars()
+        .filter_map(code)
+        .flat_map(|code| code.chars().map(|b| b == '1').chain([false, false]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_are_self_synchronizing() {
+        let mut seen = std::collections::HashSet::new();
+        for c in (' '..='~').chain(['\n', '\r']) {
+            let code = code(c).unwrap();
+            assert!(code.starts_with('1') && code.ends_with('1'), "{:?}", c);
+            assert!(!code.contains("00"), "{:?}", c);
+            assert!(seen.insert(code), "{:?} is not unique", c);
+        }
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(
+            encode("e t"),
+            vec![true, true, false, false, true, false, false, true, false, true, false, false]
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use rustfft::num_complex::Complex;
+
+/// Second order section in transposed direct form II. Runs in f64, which
+/// narrow filters at high sample rates need to stay accurate.
+#[derive(Clone, Debug)]
+pub struct Biquad {
+    b: [f64; 3],
+    // a0 is 1.
+    a: [f64; 2],
+    state: [f64; 2],
+}
+
+impl Biquad {
+    pub fn new(b: [f64; 3], a: [f64; 2]) -> Self {
+        Self {
+            b,
+            a,
+            state: [0.; 2],
+        }
+    }
+
+    /// Numerator and denominator, in powers of z^-1.
+    pub fn coefficients(&self) -> ([f64; 3], [f64; 3]) {
+        (self.b, [1., self.a[0], self.a[1]])
+    }
+
+    pub fn update(&mut self, input: f32) -> f32 {
+        let x = input as f64;
+        let y = self.b[0] * x + self.state[0];
+        self.state[0] = self.b[1] * x - self.a[0] * y + self.state[1];
+        self.state[1] = self.b[2] * x - self.a[1] * y;
+        y as f32
+    }
+
+    /// Response at `w` radians per sample.
+    pub fn response(&self, w: f64) -> Complex<f64> {
+        let z1 = Complex::from_polar(1., -w);
+        let z2 = z1 * z1;
+        (self.b[0] + z1 * self.b[1] + z2 * self.b[2]) / (1. + z1 * self.a[0] + z2 * self.a[1])
+    }
+}
+
+/// Biquads in series.
+#[derive(Clone, Debug)]
+pub struct BiquadCascade {
+    sections: Vec<Biquad>,
+}
+
+impl BiquadCascade {
+    pub fn new(sections: Vec<Biquad>) -> Self {
+        Self { sections }
+    }
+
+    pub fn sections(&self) -> &[Biquad] {
+        &self.sections
+    }
+
+    pub fn update(&mut self, input: f32) -> f32 {
+        self.sections
+            .iter_mut()
+            .fold(input, |sample, section| section.update(sample))
+    }
+
+    pub fn response(&self, w: f64) -> Complex<f64> {
+        self.sections
+            .iter()
+            .map(|section| section.response(w))
+            .product()
+    }
+}
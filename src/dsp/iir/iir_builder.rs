@@ -0,0 +1,271 @@
+use std::f64::consts::PI;
+
+use rustfft::num_complex::Complex;
+
+use crate::dsp::iir::{Biquad, BiquadCascade, Prototype};
+use crate::units::Frequency;
+
+use super::prototype::Zpk;
+
+type C64 = Complex<f64>;
+
+/// Groups roots into conjugate pairs, then pairs up the real ones. An odd
+/// one out gets a root at the origin, which makes a first order section.
+fn pair(roots: &[C64]) -> Vec<(C64, C64)> {
+    const TOLERANCE: f64 = 1e-9;
+    let mut pairs: Vec<(C64, C64)> = roots
+        .iter()
+        .filter(|r| r.im > TOLERANCE)
+        .map(|r| (*r, r.conj()))
+        .collect();
+
+    let mut real: Vec<f64> = roots
+        .iter()
+        .filter(|r| r.im.abs() <= TOLERANCE)
+        .map(|r| r.re)
+        .collect();
+    real.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    for chunk in real.chunks(2) {
+        let second = chunk.get(1).copied().unwrap_or(0.);
+        pairs.push((C64::new(chunk[0], 0.), C64::new(second, 0.)));
+    }
+    pairs
+}
+
+// (1 - r1 z^-1)(1 - r2 z^-1)
+fn quadratic((r1, r2): (C64, C64)) -> [f64; 3] {
+    [1., -(r1 + r2).re, (r1 * r2).re]
+}
+
+/// Second order sections, the least resonant first, each with the zeros
+/// closest to its poles.
+fn sections(zpk: Zpk) -> BiquadCascade {
+    let mut poles = pair(&zpk.poles);
+    let mut zeros = pair(&zpk.zeros);
+    let resonance = |p: &(C64, C64)| p.0.norm().max(p.1.norm());
+    poles.sort_by(|a, b| resonance(a).partial_cmp(&resonance(b)).unwrap());
+
+    let mut biquads = Vec::with_capacity(poles.len());
+    for (i, pole) in poles.into_iter().enumerate() {
+        let distance = |z: &(C64, C64)| (z.0 - pole.0).norm();
+        let nearest = (0..zeros.len()).min_by(|a, b| {
+            distance(&zeros[*a])
+                .partial_cmp(&distance(&zeros[*b]))
+                .unwrap()
+        });
+        let mut b = nearest
+            .map(|index| quadratic(zeros.remove(index)))
+            .unwrap_or([1., 0., 0.]);
+        if i == 0 {
+            b.iter_mut().for_each(|b| *b *= zpk.gain);
+        }
+        let a = quadratic(pole);
+        biquads.push(Biquad::new(b, [a[1], a[2]]));
+    }
+    BiquadCascade::new(biquads)
+}
+
+/// The analog frequency the bilinear transform maps `f` (a fraction of the
+/// sample rate) onto, for a sample rate of 1.
+fn prewarp(f: f32) -> f64 {
+    2. * (PI * f as f64).tan()
+}
+
+enum Mode {
+    Low(f32),
+    High(f32),
+    Band(f32, f32),
+}
+
+pub struct IirBuilder {
+    prototype: Prototype,
+    order: usize,
+    mode: Mode,
+}
+
+impl IirBuilder {
+    pub fn low_pass(
+        prototype: Prototype,
+        order: usize,
+        input_sample_rate: Frequency,
+        cutoff: Frequency,
+    ) -> Self {
+        let cutoff = cutoff.value() / input_sample_rate.value();
+        assert!(cutoff < 0.5);
+        Self {
+            prototype,
+            order,
+            mode: Mode::Low(cutoff),
+        }
+    }
+
+    pub fn high_pass(
+        prototype: Prototype,
+        order: usize,
+        input_sample_rate: Frequency,
+        cutoff: Frequency,
+    ) -> Self {
+        let cutoff = cutoff.value() / input_sample_rate.value();
+        assert!(cutoff < 0.5);
+        Self {
+            prototype,
+            order,
+            mode: Mode::High(cutoff),
+        }
+    }
+
+    /// Twice `order` poles.
+    pub fn band_pass(
+        prototype: Prototype,
+        order: usize,
+        input_sample_rate: Frequency,
+        cutoff1: Frequency,
+        cutoff2: Frequency,
+    ) -> Self {
+        let cutoff1 = cutoff1.value() / input_sample_rate.value();
+        let cutoff2 = cutoff2.value() / input_sample_rate.value();
+        assert!(cutoff1 < 0.5);
+        assert!(cutoff2 < 0.5);
+        assert!(cutoff1 < cutoff2);
+        Self {
+            prototype,
+            order,
+            mode: Mode::Band(cutoff1, cutoff2),
+        }
+    }
+
+    pub fn build(&self) -> BiquadCascade {
+        let prototype = self.prototype.zpk(self.order);
+        let analog = match self.mode {
+            Mode::Low(f_c) => prototype.low_pass(prewarp(f_c)),
+            Mode::High(f_c) => prototype.high_pass(prewarp(f_c)),
+            Mode::Band(f_c1, f_c2) => prototype.band_pass(prewarp(f_c1), prewarp(f_c2)),
+        };
+        sections(analog.bilinear(1.))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FS: f32 = 44100.;
+
+    fn hz(value: f32) -> Frequency {
+        Frequency::Hertz(value)
+    }
+
+    /// Digital and prewarped analog frequency for `f` Hz.
+    fn frequencies(f: f32) -> (f64, f64) {
+        let f = f / FS;
+        (2. * PI * f as f64, prewarp(f))
+    }
+
+    fn db(power: f64) -> f64 {
+        10. * power.log10()
+    }
+
+    fn chebyshev(n: usize, x: f64) -> f64 {
+        if x.abs() <= 1. {
+            (n as f64 * x.acos()).cos()
+        } else {
+            (n as f64 * x.abs().acosh()).cosh() * x.signum().powi(n as i32)
+        }
+    }
+
+    fn assert_matches(filter: &BiquadCascade, target: impl Fn(f64) -> f64) {
+        for f in [10., 100., 500., 900., 1000., 1100., 2000., 5000., 15000.] {
+            let (w, analog) = frequencies(f);
+            let actual = db(filter.response(w).norm_sqr());
+            let expected = db(target(analog));
+            if expected > -150. {
+                assert!(
+                    (actual - expected).abs() < 1e-3,
+                    "{} Hz: {} dB, expected {} dB",
+                    f,
+                    actual,
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_butterworth() {
+        let (_, cutoff) = frequencies(1000.);
+        for order in [1, 2, 5, 8] {
+            let filter =
+                IirBuilder::low_pass(Prototype::Butterworth, order, hz(FS), hz(1000.)).build();
+            assert_matches(&filter, |w| 1. / (1. + (w / cutoff).powi(2 * order as i32)));
+
+            let filter =
+                IirBuilder::high_pass(Prototype::Butterworth, order, hz(FS), hz(1000.)).build();
+            assert_matches(&filter, |w| 1. / (1. + (cutoff / w).powi(2 * order as i32)));
+        }
+    }
+
+    #[test]
+    fn test_butterworth_band_pass() {
+        let (_, low) = frequencies(900.);
+        let (_, high) = frequencies(1100.);
+        let filter =
+            IirBuilder::band_pass(Prototype::Butterworth, 3, hz(FS), hz(900.), hz(1100.)).build();
+        assert_eq!(filter.sections().len(), 3);
+        assert_matches(&filter, |w| {
+            let x = (w * w - low * high) / (w * (high - low));
+            1. / (1. + x.powi(6))
+        });
+    }
+
+    #[test]
+    fn test_chebyshev() {
+        let (_, cutoff) = frequencies(1000.);
+        for order in [3, 4] {
+            let ripple = 1.0;
+            let filter =
+                IirBuilder::low_pass(Prototype::ChebyshevI(ripple), order, hz(FS), hz(1000.))
+                    .build();
+            let epsilon2 = 10f64.powf(ripple as f64 / 10.) - 1.;
+            assert_matches(&filter, |w| {
+                1. / (1. + epsilon2 * chebyshev(order, w / cutoff).powi(2))
+            });
+
+            let attenuation = 60.0;
+            let filter = IirBuilder::low_pass(
+                Prototype::ChebyshevII(attenuation),
+                order,
+                hz(FS),
+                hz(1000.),
+            )
+            .build();
+            let delta2 = 1. / (10f64.powf(attenuation as f64 / 10.) - 1.);
+            assert_matches(&filter, |w| {
+                let t2 = delta2 * chebyshev(order, cutoff / w).powi(2);
+                t2 / (1. + t2)
+            });
+        }
+    }
+
+    #[test]
+    fn test_bessel() {
+        let (_, cutoff) = frequencies(1000.);
+        // The third order Bessel filter, normalised for unit delay.
+        let analog = |w: f64| {
+            let s = C64::new(0., w);
+            15. / (s * s * s + 6. * s * s + 15. * s + 15.)
+        };
+        // Which is 3 dB down at this many rad/s.
+        let corner = 1.7556723686812106;
+
+        let filter = IirBuilder::low_pass(Prototype::Bessel, 3, hz(FS), hz(1000.)).build();
+        assert_matches(&filter, |w| analog(w / cutoff * corner).norm_sqr());
+        assert!((db(filter.response(frequencies(1000.).0).norm_sqr()) + 3.0103).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_step_response() {
+        let mut filter = IirBuilder::low_pass(Prototype::Butterworth, 4, hz(FS), hz(100.)).build();
+        let settled = (0..FS as usize).map(|_| filter.update(1.)).last().unwrap();
+        assert!((settled - 1.).abs() < 1e-4, "{}", settled);
+    }
+}
@@ -0,0 +1,7 @@
+mod biquad;
+mod iir_builder;
+mod prototype;
+
+pub use biquad::{Biquad, BiquadCascade};
+pub use iir_builder::IirBuilder;
+pub use prototype::Prototype;
@@ -0,0 +1,230 @@
+use std::f64::consts::PI;
+
+use rustfft::num_complex::Complex;
+
+type C64 = Complex<f64>;
+
+/// Analog filter as zeros, poles and gain.
+#[derive(Clone, Debug)]
+pub struct Zpk {
+    pub zeros: Vec<C64>,
+    pub poles: Vec<C64>,
+    pub gain: f64,
+}
+
+/// Analog low-pass designs, normalised to a cutoff of 1 rad/s.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Prototype {
+    /// Maximally flat passband. -3 dB at the cutoff.
+    Butterworth,
+    /// Passband ripple in dB, which the cutoff is the edge of.
+    ChebyshevI(f32),
+    /// Stopband attenuation in dB, which starts at the cutoff.
+    ChebyshevII(f32),
+    /// Maximally flat group delay. -3 dB at the cutoff.
+    Bessel,
+}
+
+// The angles the Butterworth and Chebyshev poles are placed at.
+fn angles(order: usize) -> impl Iterator<Item = f64> {
+    let n = order as f64;
+    (0..order).map(move |k| PI * (2. * k as f64 - n + 1.) / (2. * n))
+}
+
+fn product(values: &[C64]) -> C64 {
+    values.iter().map(|v| -v).product()
+}
+
+/// Roots of a polynomial, highest power first, by Durand-Kerner iteration.
+fn roots(coeff: &[f64]) -> Vec<C64> {
+    let degree = coeff.len() - 1;
+    let monic: Vec<f64> = coeff.iter().map(|c| c / coeff[0]).collect();
+    let evaluate = |x: C64| monic.iter().fold(C64::new(0., 0.), |acc, c| acc * x + c);
+
+    let seed = C64::new(0.4, 0.9);
+    let mut roots: Vec<C64> = (0..degree).map(|k| seed.powu(k as u32)).collect();
+    for _ in 0..500 {
+        let mut change: f64 = 0.;
+        for i in 0..degree {
+            let denominator: C64 = (0..degree)
+                .filter(|j| *j != i)
+                .map(|j| roots[i] - roots[j])
+                .product();
+            let step = evaluate(roots[i]) / denominator;
+            roots[i] -= step;
+            change = change.max(step.norm());
+        }
+        if change < 1e-14 {
+            break;
+        }
+    }
+    roots
+}
+
+/// Reverse Bessel polynomial, highest power first.
+fn bessel_polynomial(order: usize) -> Vec<f64> {
+    // a_k = (2n - k)! / (2^(n - k) k! (n - k)!) for the k-th power.
+    let n = order;
+    (0..=n)
+        .rev()
+        .map(|k| {
+            let numerator: f64 = ((n - k + 1)..=(2 * n - k)).map(|i| i as f64).product();
+            let denominator: f64 =
+                2f64.powi((n - k) as i32) * (1..=k).map(|i| i as f64).product::<f64>();
+            numerator / denominator
+        })
+        .collect()
+}
+
+impl Prototype {
+    pub fn zpk(&self, order: usize) -> Zpk {
+        assert!(order > 0);
+        match *self {
+            Self::Butterworth => {
+                let poles: Vec<C64> = angles(order)
+                    .map(|theta| -C64::from_polar(1., theta))
+                    .collect();
+                Zpk {
+                    zeros: Vec::new(),
+                    poles,
+                    gain: 1.,
+                }
+            }
+            Self::ChebyshevI(ripple) => {
+                let epsilon = (10f64.powf(ripple as f64 / 10.) - 1.).sqrt();
+                let mu = (1. / epsilon).asinh() / order as f64;
+                let poles: Vec<C64> = angles(order)
+                    .map(|theta| -(C64::new(mu, theta)).sinh())
+                    .collect();
+                let mut gain = product(&poles).re;
+                if order % 2 == 0 {
+                    gain /= (1. + epsilon * epsilon).sqrt();
+                }
+                Zpk {
+                    zeros: Vec::new(),
+                    poles,
+                    gain,
+                }
+            }
+            Self::ChebyshevII(attenuation) => {
+                let delta = 1. / (10f64.powf(attenuation as f64 / 10.) - 1.).sqrt();
+                let mu = (1. / delta).asinh() / order as f64;
+                let poles: Vec<C64> = angles(order)
+                    .map(|theta| {
+                        let p = -C64::from_polar(1., theta);
+                        1. / C64::new(mu.sinh() * p.re, mu.cosh() * p.im)
+                    })
+                    .collect();
+                // The middle angle of an odd order is zero, and has no zero.
+                let zeros: Vec<C64> = angles(order)
+                    .filter(|theta| theta.abs() > 1e-9)
+                    .map(|theta| C64::new(0., 1. / theta.sin()))
+                    .collect();
+                let gain = (product(&poles) / product(&zeros)).re;
+                Zpk { zeros, poles, gain }
+            }
+            Self::Bessel => {
+                let coeff = bessel_polynomial(order);
+                let poles = roots(&coeff);
+                let gain = *coeff.last().unwrap();
+                let mut zpk = Zpk {
+                    zeros: Vec::new(),
+                    poles,
+                    gain,
+                };
+
+                // Move the -3 dB point to 1 rad/s.
+                let magnitude = |w: f64| zpk.response(C64::new(0., w)).norm();
+                let (mut low, mut high): (f64, f64) = (0.1, 10.);
+                for _ in 0..100 {
+                    let middle = (low * high).sqrt();
+                    if magnitude(middle) > 0.5f64.sqrt() {
+                        low = middle;
+                    } else {
+                        high = middle;
+                    }
+                }
+                zpk.scale(1. / low);
+                zpk
+            }
+        }
+    }
+}
+
+impl Zpk {
+    /// H(s).
+    pub fn response(&self, s: C64) -> C64 {
+        let zeros: C64 = self.zeros.iter().map(|z| s - z).product();
+        let poles: C64 = self.poles.iter().map(|p| s - p).product();
+        self.gain * zeros / poles
+    }
+
+    /// Moves a cutoff of 1 rad/s to `w`.
+    fn scale(&mut self, w: f64) {
+        let degree = self.poles.len() as i32 - self.zeros.len() as i32;
+        self.zeros.iter_mut().for_each(|z| *z *= w);
+        self.poles.iter_mut().for_each(|p| *p *= w);
+        self.gain *= w.powi(degree);
+    }
+
+    pub fn low_pass(mut self, w: f64) -> Self {
+        self.scale(w);
+        self
+    }
+
+    pub fn high_pass(self, w: f64) -> Self {
+        let degree = self.poles.len() - self.zeros.len();
+        let gain = self.gain * (product(&self.zeros) / product(&self.poles)).re;
+        let mut zeros: Vec<C64> = self.zeros.iter().map(|z| w / z).collect();
+        zeros.extend(vec![C64::new(0., 0.); degree]);
+        Self {
+            zeros,
+            poles: self.poles.iter().map(|p| w / p).collect(),
+            gain,
+        }
+    }
+
+    /// Passband from `w1` to `w2`. Each pole and zero becomes two.
+    pub fn band_pass(self, w1: f64, w2: f64) -> Self {
+        let degree = self.poles.len() - self.zeros.len();
+        let w0 = (w1 * w2).sqrt();
+        let bandwidth = w2 - w1;
+        let split = |values: &[C64]| -> Vec<C64> {
+            values
+                .iter()
+                .flat_map(|v| {
+                    let half = v * bandwidth / 2.;
+                    let root = (half * half - w0 * w0).sqrt();
+                    [half + root, half - root]
+                })
+                .collect()
+        };
+
+        let mut zeros = split(&self.zeros);
+        zeros.extend(vec![C64::new(0., 0.); degree]);
+        Self {
+            zeros,
+            poles: split(&self.poles),
+            gain: self.gain * bandwidth.powi(degree as i32),
+        }
+    }
+
+    /// Bilinear transform for `sample_rate`, with zeros at infinity moved to
+    /// Nyquist. Frequencies should have been prewarped.
+    pub fn bilinear(self, sample_rate: f64) -> Self {
+        let fs2 = 2. * sample_rate;
+        let degree = self.poles.len() - self.zeros.len();
+        let gain = self.gain
+            * (self.zeros.iter().map(|z| fs2 - z).product::<C64>()
+                / self.poles.iter().map(|p| fs2 - p).product::<C64>())
+            .re;
+
+        let mut zeros: Vec<C64> = self.zeros.iter().map(|z| (fs2 + z) / (fs2 - z)).collect();
+        zeros.extend(vec![C64::new(-1., 0.); degree]);
+        Self {
+            zeros,
+            poles: self.poles.iter().map(|p| (fs2 + p) / (fs2 - p)).collect(),
+            gain,
+        }
+    }
+}
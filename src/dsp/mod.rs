@@ -1,11 +1,18 @@
 mod aggregator;
 pub mod correlator;
-mod decode;
-mod downsample;
+pub(crate) mod decode;
+mod downconvert;
+pub mod encode;
 pub mod fft;
-mod fir;
+pub mod fir;
+pub mod frequency_shifter;
+pub mod generator;
 pub mod ifft;
+pub mod iir;
+pub mod noise;
 mod processor;
+pub mod resampler;
+pub mod response;
 pub mod rx;
 mod turbo;
 pub mod waterfall_processor;
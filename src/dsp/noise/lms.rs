@@ -0,0 +1,48 @@
+use crate::dsp::fir::{dot, DelayLine};
+
+// Keeps the step finite through silence.
+const EPSILON: f32 = 1e-6;
+
+/// Normalized LMS predictor. It adapts a filter that predicts each sample
+/// from those at least `delay` samples before it. Whatever correlates over
+/// that delay (tones, carriers) is predicted; noise is left in the error.
+#[derive(Debug)]
+pub struct Lms {
+    taps: usize,
+    weights: Vec<f32>,
+    delay_line: DelayLine,
+    // Step size, as a fraction of the way to the best fit for each sample.
+    rate: f32,
+    // How much the weights decay by each sample, which keeps them bounded.
+    leak: f32,
+}
+
+impl Lms {
+    pub fn new(taps: usize, delay: usize, rate: f32, leak: f32) -> Self {
+        assert!(taps > 0 && delay > 0);
+        Self {
+            taps,
+            weights: vec![0.; taps],
+            delay_line: DelayLine::new(taps + delay),
+            rate,
+            leak,
+        }
+    }
+
+    /// Returns the prediction of `input`, and the error in it.
+    pub fn update(&mut self, input: f32) -> (f32, f32) {
+        let window = self.delay_line.push(input);
+        let reference = &window[..self.taps];
+
+        let prediction = dot(&self.weights, reference);
+        let error = input - prediction;
+
+        let step = self.rate * error / (dot(reference, reference) + EPSILON);
+        let decay = 1. - self.leak;
+        for (weight, x) in self.weights.iter_mut().zip(reference) {
+            *weight = decay * *weight + step * x;
+        }
+
+        (prediction, error)
+    }
+}
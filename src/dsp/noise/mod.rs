@@ -0,0 +1,226 @@
+mod lms;
+mod noise_blanker;
+
+use crate::configuration::NoiseSettings;
+use crate::types::ComplexType;
+use crate::units::Frequency;
+
+use lms::Lms;
+use noise_blanker::NoiseBlanker;
+
+/// How far back the noise reducer looks, and the gap before it, which
+/// decorrelates the noise but not the signals.
+const NOISE_REDUCTION_SECONDS: f32 = 0.004;
+const NOISE_REDUCTION_DELAY_SECONDS: f32 = 0.0005;
+const NOISE_REDUCTION_RATE: f32 = 0.01;
+const NOISE_REDUCTION_LEAK: f32 = 1e-4;
+
+/// The notch adapts slowly, so that only what stays put is removed.
+const NOTCH_SECONDS: f32 = 0.002;
+const NOTCH_DELAY_SECONDS: f32 = 0.002;
+const NOTCH_RATE: f32 = 0.001;
+const NOTCH_LEAK: f32 = 1e-5;
+
+/// Optional clean-up of the input ahead of the waterfall and decoder. I/Q
+/// input goes through a second set of adaptive filters for Q.
+pub struct Denoiser {
+    blanker: NoiseBlanker<f32>,
+    complex_blanker: NoiseBlanker<ComplexType>,
+    notch: [Lms; 2],
+    noise_reducer: [Lms; 2],
+}
+
+impl Denoiser {
+    pub fn new(sample_rate: Frequency) -> Self {
+        let samples = |seconds: f32| ((seconds * sample_rate.value()).round() as usize).max(1);
+        let notch = || {
+            Lms::new(
+                samples(NOTCH_SECONDS),
+                samples(NOTCH_DELAY_SECONDS),
+                NOTCH_RATE,
+                NOTCH_LEAK,
+            )
+        };
+        let noise_reducer = || {
+            Lms::new(
+                samples(NOISE_REDUCTION_SECONDS),
+                samples(NOISE_REDUCTION_DELAY_SECONDS),
+                NOISE_REDUCTION_RATE,
+                NOISE_REDUCTION_LEAK,
+            )
+        };
+        Self {
+            blanker: NoiseBlanker::new(sample_rate),
+            complex_blanker: NoiseBlanker::new(sample_rate),
+            notch: [notch(), notch()],
+            noise_reducer: [noise_reducer(), noise_reducer()],
+        }
+    }
+
+    /// Runs the enabled stages in place. Impulses are blanked first, so they
+    /// don't upset the adaptive filters.
+    pub fn process(&mut self, samples: &mut [f32], settings: &NoiseSettings) {
+        for sample in samples {
+            if settings.blanker {
+                *sample = self
+                    .blanker
+                    .update(*sample, sample.abs(), settings.blanker_threshold);
+            }
+            if settings.notch {
+                let (_, error) = self.notch[0].update(*sample);
+                *sample = error;
+            }
+            if settings.noise_reduction {
+                let (prediction, _) = self.noise_reducer[0].update(*sample);
+                *sample = prediction;
+            }
+        }
+    }
+
+    /// The same as `process`, with I and Q filtered apart. A pulse blanks
+    /// both.
+    pub fn process_complex(&mut self, samples: &mut [ComplexType], settings: &NoiseSettings) {
+        for sample in samples {
+            if settings.blanker {
+                *sample =
+                    self.complex_blanker
+                        .update(*sample, sample.norm(), settings.blanker_threshold);
+            }
+            if settings.notch {
+                let (_, re) = self.notch[0].update(sample.re);
+                let (_, im) = self.notch[1].update(sample.im);
+                *sample = ComplexType::new(re, im);
+            }
+            if settings.noise_reduction {
+                let (re, _) = self.noise_reducer[0].update(sample.re);
+                let (im, _) = self.noise_reducer[1].update(sample.im);
+                *sample = ComplexType::new(re, im);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use rand::distributions::Distribution;
+    use rand::prelude::*;
+    use statrs::distribution::Normal;
+
+    use super::*;
+
+    const FS: f32 = 8000.;
+
+    fn tone(hz: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (2. * PI * hz * i as f32 / FS).sin())
+            .collect()
+    }
+
+    fn power(samples: &[f32]) -> f32 {
+        samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32
+    }
+
+    fn process(samples: &[f32], settings: NoiseSettings) -> Vec<f32> {
+        let mut samples = samples.to_vec();
+        Denoiser::new(Frequency::Hertz(FS)).process(&mut samples, &settings);
+        samples
+    }
+
+    #[test]
+    fn test_noise_reduction() {
+        let len = 4 * FS as usize;
+        let clean = tone(1000., len);
+        let normal = Normal::new(0., 1.).unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+        let noisy: Vec<f32> = clean
+            .iter()
+            .map(|x| x + normal.sample(&mut rng) as f32)
+            .collect();
+
+        let output = process(
+            &noisy,
+            NoiseSettings {
+                noise_reduction: true,
+                ..Default::default()
+            },
+        );
+
+        // What is left once it has converged, against the clean tone.
+        let settled = len / 2..;
+        let error = |samples: &[f32]| {
+            let error: Vec<f32> = samples[settled.clone()]
+                .iter()
+                .zip(&clean[settled.clone()])
+                .map(|(x, c)| x - c)
+                .collect();
+            power(&error)
+        };
+        let improvement = 10. * (error(&noisy) / error(&output)).log10();
+        assert!(improvement > 6., "{} dB", improvement);
+    }
+
+    #[test]
+    fn test_notch() {
+        let len = 2 * FS as usize;
+        let output = process(
+            &tone(1500., len),
+            NoiseSettings {
+                notch: true,
+                ..Default::default()
+            },
+        );
+
+        let attenuation = 10. * (power(&output[len / 2..]) / 0.5).log10();
+        assert!(attenuation < -20., "{} dB", attenuation);
+    }
+
+    #[test]
+    fn test_complex_notch() {
+        let len = 2 * FS as usize;
+        let mut samples: Vec<ComplexType> = (0..len)
+            .map(|i| ComplexType::from_polar(1., -2. * PI * 1500. * i as f32 / FS))
+            .collect();
+        let settings = NoiseSettings {
+            notch: true,
+            ..Default::default()
+        };
+        Denoiser::new(Frequency::Hertz(FS)).process_complex(&mut samples, &settings);
+
+        let settled = &samples[len / 2..];
+        let power = settled.iter().map(|x| x.norm_sqr()).sum::<f32>() / settled.len() as f32;
+        let attenuation = 10. * power.log10();
+        assert!(attenuation < -20., "{} dB", attenuation);
+    }
+
+    #[test]
+    fn test_blanker() {
+        let len = FS as usize;
+        let clean = tone(1000., len);
+        let mut noisy = clean.clone();
+        for start in (1000..len).step_by(1000) {
+            noisy[start] += 50.;
+            noisy[start + 1] -= 40.;
+        }
+
+        let output = process(
+            &noisy,
+            NoiseSettings {
+                blanker: true,
+                ..Default::default()
+            },
+        );
+
+        // Nothing gets through bigger than the tone.
+        assert!(output.iter().all(|x| x.abs() <= 1.), "pulse got through");
+        // And the tone itself is left alone, bar the blanked stretches.
+        let lead = (FS * 0.0005).ceil() as usize;
+        let passed = output[lead..]
+            .iter()
+            .zip(&clean)
+            .filter(|(x, c)| (*x - *c).abs() < 1e-6)
+            .count();
+        assert!(passed as f32 > 0.95 * (len - lead) as f32, "{}", passed);
+    }
+}
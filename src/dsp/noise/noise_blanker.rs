@@ -0,0 +1,63 @@
+use std::collections::VecDeque;
+
+use crate::units::Frequency;
+
+// How long the average level is taken over.
+const AVERAGE_SECONDS: f32 = 0.05;
+// Blanking starts this long before a pulse is seen...
+const LEAD_SECONDS: f32 = 0.0005;
+// ...and carries on this long after.
+const HANG_SECONDS: f32 = 0.002;
+
+/// Silences impulses well above the average level. The output is delayed by
+/// the lead time, so the rising edge of a pulse is blanked too. Samples are
+/// real audio or I/Q.
+#[derive(Debug)]
+pub struct NoiseBlanker<T> {
+    alpha: f32,
+    average: f32,
+    // Samples until the average means anything.
+    settling: usize,
+    lead: usize,
+    hang: usize,
+    delay: VecDeque<T>,
+    // Samples left to blank.
+    blanking: usize,
+}
+
+impl<T: Copy + Default> NoiseBlanker<T> {
+    pub fn new(sample_rate: Frequency) -> Self {
+        let samples = |seconds: f32| (seconds * sample_rate.value()).ceil() as usize;
+        let lead = samples(LEAD_SECONDS);
+        Self {
+            alpha: 1. / samples(AVERAGE_SECONDS) as f32,
+            average: 0.,
+            settling: samples(AVERAGE_SECONDS),
+            lead,
+            hang: samples(HANG_SECONDS),
+            delay: vec![T::default(); lead].into(),
+            blanking: 0,
+        }
+    }
+
+    /// `magnitude` is the size of `input`, which is what's compared against
+    /// the average.
+    pub fn update(&mut self, input: T, magnitude: f32, threshold: f32) -> T {
+        if self.settling == 0 && magnitude > threshold * self.average {
+            self.blanking = self.lead + self.hang;
+        } else if self.blanking == 0 {
+            // Pulses are kept out of the average.
+            self.average += self.alpha * (magnitude - self.average);
+            self.settling = self.settling.saturating_sub(1);
+        }
+
+        self.delay.push_back(input);
+        let output = self.delay.pop_front().unwrap();
+        if self.blanking > 0 {
+            self.blanking -= 1;
+            T::default()
+        } else {
+            output
+        }
+    }
+}
@@ -2,29 +2,33 @@ use egui::ColorImage;
 use tokio::sync::mpsc::{Receiver, Sender};
 
 use crate::configuration::Configuration;
+use crate::dsp::response::FilterTaps;
 use crate::message::MessageSender;
+use crate::scope::Trace;
 use crate::statistics::Statistics;
-use crate::types::SampleType;
+use crate::types::{SampleType, Samples};
 
 use super::rx::Rx;
 use super::waterfall_processor::WaterfallProcessor;
 
 pub struct Processor {
-    receiver: Receiver<Vec<SampleType>>,
+    receiver: Receiver<Samples>,
     rx: Rx,
     wp: WaterfallProcessor,
 }
 
 impl Processor {
     pub fn new(
-        receiver: Receiver<Vec<SampleType>>,
+        receiver: Receiver<Samples>,
         sender: Sender<ColorImage>,
-        plot_sender: Sender<Vec<SampleType>>,
+        plot_sender: Sender<Vec<Trace>>,
+        monitor_sender: Sender<Vec<SampleType>>,
         message_sender: MessageSender,
         config: &Configuration,
     ) -> Self {
         let rx = Rx::new(config)
             .with_plot_sender(plot_sender)
+            .with_monitor_sender(monitor_sender)
             .with_message_sender(message_sender);
 
         let wp = WaterfallProcessor::new(sender, config);
@@ -32,13 +36,38 @@ impl Processor {
         Self { receiver, rx, wp }
     }
 
+    pub fn filters(&self) -> Vec<FilterTaps> {
+        self.rx.filters()
+    }
+
+    /// How many rows the waterfall has scrolled by.
+    pub fn rows(&self) -> u64 {
+        self.wp.rows()
+    }
+
+    /// The latest spectrum of real input in dB, from 0 Hz to Nyquist. Empty
+    /// for complex input.
+    pub fn spectrum(&self) -> &[f32] {
+        self.wp.spectrum()
+    }
+
     pub fn run(&mut self, config: &Configuration, stats: &mut Statistics) {
         while let Ok(samples) = self.receiver.try_recv() {
             // use std::time::Instant;
             // let now = Instant::now();
 
-            self.rx.run(samples.clone(), config, stats);
-            self.wp.run(samples, config, stats);
+            match samples {
+                Samples::Real(mut samples) => {
+                    self.rx.clean(&mut samples, config);
+                    self.rx.run(samples.clone(), config, stats);
+                    self.wp.run(samples, config, stats);
+                }
+                Samples::Complex(mut samples) => {
+                    self.rx.clean_complex(&mut samples, config);
+                    self.rx.run_complex(&samples, config, stats);
+                    self.wp.run_complex(samples, config, stats);
+                }
+            }
 
             // let elapsed = now.elapsed();
             // println!("Elapsed: {:.2?}", elapsed);
@@ -0,0 +1,206 @@
+use std::f32::consts::PI;
+
+use crate::dsp::fir::{dot, DelayLine, FirBuilder, Window};
+use crate::dsp::response::FilterTaps;
+use crate::units::Frequency;
+
+// Flat up to this fraction of the lower of the two rates, and down by
+// ATTENUATION dB from the complement of it. What lands between the two
+// only aliases onto frequencies above the passband.
+const PASSBAND: f32 = 0.4;
+const ATTENUATION: f32 = 70.;
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Up (L) and down (M) factors, in lowest terms, that take `input` to
+/// `output`. Rates are rounded to whole hertz.
+pub fn ratio(input: Frequency, output: Frequency) -> (usize, usize) {
+    let input = input.value().round() as usize;
+    let output = output.value().round() as usize;
+    assert!(input > 0 && output > 0);
+    let divisor = gcd(input, output);
+    (output / divisor, input / divisor)
+}
+
+/// Polyphase rational resampler. Conceptually it zero-stuffs the input up by
+/// L, low-pass filters, and keeps every M-th sample, but only the taps that
+/// meet non-zero input are ever computed, once per output sample.
+#[derive(Debug)]
+pub struct Resampler {
+    up: usize,
+    down: usize,
+    input_sample_rate: Frequency,
+    pub output_sample_rate: Frequency,
+    // The prototype low-pass, at L times the input rate.
+    prototype: Vec<f32>,
+    // phases[p][taps - 1 - k] = L h[p + kL], which weighs the input k
+    // samples back, so each lines up with the delay line oldest first.
+    phases: Vec<Vec<f32>>,
+    delay_line: DelayLine,
+    // Where the next output falls after the newest input, in 1/L samples.
+    offset: usize,
+}
+
+impl Resampler {
+    pub fn new(input_sample_rate: Frequency, output_sample_rate: Frequency) -> Self {
+        let (up, down) = ratio(input_sample_rate, output_sample_rate);
+        let upsampled_rate = input_sample_rate * up;
+
+        let narrowest = input_sample_rate.value().min(output_sample_rate.value());
+        let transition = (1. - 2. * PASSBAND) * narrowest;
+
+        // Kaiser's estimate of the length, rounded up to whole phases.
+        let delta_w = 2. * PI * transition / upsampled_rate.value();
+        let length = ((ATTENUATION - 8.) / (2.285 * delta_w)).ceil() as usize;
+        let taps = (length + up - 1) / up;
+
+        let prototype =
+            FirBuilder::low_pass(taps * up, upsampled_rate, Frequency::Hertz(narrowest / 2.))
+                .with_window(Window::Kaiser(ATTENUATION))
+                .coefficients();
+
+        let phases = (0..up)
+            .map(|phase| {
+                (0..taps)
+                    .rev()
+                    .map(|k| up as f32 * prototype[phase + k * up])
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            up,
+            down,
+            input_sample_rate,
+            output_sample_rate,
+            prototype,
+            phases,
+            delay_line: DelayLine::new(taps),
+            offset: 0,
+        }
+    }
+
+    /// The anti-alias filter, at L times the input rate.
+    pub fn filter(&self) -> FilterTaps {
+        FilterTaps::new(
+            "Resampler anti-alias",
+            self.input_sample_rate * self.up,
+            self.prototype.clone(),
+        )
+    }
+
+    /// For decimating, where each input gives at most one output.
+    pub fn update(&mut self, input: f32) -> Option<f32> {
+        assert!(self.down >= self.up);
+        let mut output = None;
+        let window = self.delay_line.push(input);
+        outputs(
+            &self.phases,
+            self.down,
+            &mut self.offset,
+            window,
+            |sample| output = Some(sample),
+        );
+        output
+    }
+
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        let mut output = Vec::with_capacity(input.len() * self.up / self.down + 1);
+        let taps = self.phases[0].len();
+        let buffer = self.delay_line.extend(input);
+        for n in 0..input.len() {
+            let window = &buffer[n..n + taps];
+            outputs(
+                &self.phases,
+                self.down,
+                &mut self.offset,
+                window,
+                |sample| output.push(sample),
+            );
+        }
+        output
+    }
+}
+
+// Every output that falls after the newest sample in `window`, where
+// `offset` is how far after it the next one is.
+fn outputs(
+    phases: &[Vec<f32>],
+    down: usize,
+    offset: &mut usize,
+    window: &[f32],
+    mut emit: impl FnMut(f32),
+) {
+    let up = phases.len();
+    while *offset < up {
+        emit(dot(&phases[*offset], window));
+        *offset += down;
+    }
+    *offset -= up;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(hz: f32, sample_rate: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (2. * PI * hz * i as f32 / sample_rate).sin())
+            .collect()
+    }
+
+    // RMS of the second half, once the filter has settled.
+    fn rms(samples: &[f32]) -> f32 {
+        let settled = &samples[samples.len() / 2..];
+        (settled.iter().map(|x| x * x).sum::<f32>() / settled.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_ratio() {
+        let hz = Frequency::Hertz;
+        assert_eq!(ratio(hz(44100.), hz(4000.)), (40, 441));
+        assert_eq!(ratio(hz(48000.), hz(4000.)), (1, 12));
+        assert_eq!(ratio(hz(8000.), hz(12000.)), (3, 2));
+    }
+
+    #[test]
+    fn test_exact_rate() {
+        let mut resampler = Resampler::new(Frequency::Hertz(44100.), Frequency::Hertz(4000.));
+        let output = resampler.process(&vec![0.; 44100 * 3]);
+        assert_eq!(output.len(), 12000);
+    }
+
+    #[test]
+    fn test_resample() {
+        for (input, output) in [(44100., 4000.), (22050., 4000.), (8000., 12000.)] {
+            let new = || Resampler::new(Frequency::Hertz(input), Frequency::Hertz(output));
+            let len = input as usize;
+
+            // A tone in the passband comes through at the same frequency
+            // and level.
+            let resampled = new().process(&tone(1000., input, len));
+            assert!((rms(&resampled) - 0.5f32.sqrt()).abs() < 0.01);
+            let settled = &resampled[resampled.len() / 2..];
+            let (i, q) = settled.iter().enumerate().fold((0., 0.), |(i, q), (n, x)| {
+                let phase = 2. * PI * 1000. * n as f32 / output;
+                (i + x * phase.cos(), q + x * phase.sin())
+            });
+            let amplitude = 2. * (i * i + q * q).sqrt() / settled.len() as f32;
+            assert!((amplitude - 1.).abs() < 0.01, "{} to {}", input, output);
+
+            // What would alias into the passband doesn't.
+            let narrowest = input.min(output);
+            let alias = narrowest - 500.;
+            if alias < input / 2. {
+                let resampled = new().process(&tone(alias, input, len));
+                assert!(rms(&resampled) < 1e-3, "{} to {}", input, output);
+            }
+        }
+    }
+}
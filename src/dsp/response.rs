@@ -0,0 +1,268 @@
+use std::f64::consts::PI;
+use std::fmt;
+
+use realfft::RealFftPlanner;
+use rustfft::num_complex::Complex;
+
+use crate::dsp::iir::BiquadCascade;
+use crate::units::Frequency;
+
+type C64 = Complex<f64>;
+
+/// What a filter is made of: FIR taps, or (numerator, denominator)
+/// polynomials in z^-1 for each biquad of an IIR.
+#[derive(Clone, Debug)]
+pub enum Kernel {
+    Fir(Vec<f32>),
+    Iir(Vec<([f64; 3], [f64; 3])>),
+}
+
+impl fmt::Display for Kernel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fir(coeff) => write!(f, "{} taps", coeff.len()),
+            Self::Iir(sections) => write!(f, "{} biquads", sections.len()),
+        }
+    }
+}
+
+/// A filter's kernel, labelled for display.
+#[derive(Clone, Debug)]
+pub struct FilterTaps {
+    pub name: String,
+    pub sample_rate: Frequency,
+    pub kernel: Kernel,
+}
+
+impl FilterTaps {
+    pub fn new(name: &str, sample_rate: Frequency, coefficients: Vec<f32>) -> Self {
+        Self {
+            name: name.to_owned(),
+            sample_rate,
+            kernel: Kernel::Fir(coefficients),
+        }
+    }
+
+    pub fn iir(name: &str, sample_rate: Frequency, cascade: &BiquadCascade) -> Self {
+        let sections = cascade
+            .sections()
+            .iter()
+            .map(|section| section.coefficients())
+            .collect();
+        Self {
+            name: name.to_owned(),
+            sample_rate,
+            kernel: Kernel::Iir(sections),
+        }
+    }
+
+    pub fn response(&self, points: usize) -> Response {
+        match &self.kernel {
+            Kernel::Fir(coeff) => Response::new(coeff, self.sample_rate, points),
+            Kernel::Iir(sections) => {
+                let sections: Vec<(Vec<f64>, Vec<f64>)> = sections
+                    .iter()
+                    .map(|(b, a)| (b.to_vec(), a.to_vec()))
+                    .collect();
+                Response::from_sections(&sections, self.sample_rate, points)
+            }
+        }
+    }
+}
+
+// The transform of a polynomial in z^-1 at `w`, and its group delay in
+// samples, which is Re(DFT(n c[n]) / DFT(c[n])).
+fn evaluate(coeff: &[f64], w: f64) -> (C64, f64) {
+    let mut value = C64::new(0., 0.);
+    let mut weighted = C64::new(0., 0.);
+    for (n, c) in coeff.iter().enumerate() {
+        let z = C64::from_polar(1., -w * n as f64);
+        value += c * z;
+        weighted += n as f64 * c * z;
+    }
+    let delay = if value.norm_sqr() > 1e-24 {
+        (weighted / value).re
+    } else {
+        0.
+    };
+    (value, delay)
+}
+
+/// Frequency response of a kernel from 0 Hz to Nyquist.
+pub struct Response {
+    pub frequency: Vec<f32>,
+    /// Gain in dB.
+    pub magnitude: Vec<f32>,
+    /// Unwrapped phase in radians.
+    pub phase: Vec<f32>,
+    /// Group delay in seconds.
+    pub group_delay: Vec<f32>,
+}
+
+impl Response {
+    /// An FIR's response by FFT, so long kernels stay cheap. Folding the
+    /// kernel onto the transform length samples its transform exactly at the
+    /// bins.
+    pub fn new(coeff: &[f32], sample_rate: Frequency, points: usize) -> Self {
+        let length = 2 * (points - 1).max(1);
+        let mut value = vec![0.; length];
+        let mut weighted = vec![0.; length];
+        for (n, c) in coeff.iter().enumerate() {
+            value[n % length] += *c as f64;
+            weighted[n % length] += n as f64 * *c as f64;
+        }
+
+        let mut planner = RealFftPlanner::<f64>::new();
+        let fft = planner.plan_fft_forward(length);
+        let transform = |input: &mut Vec<f64>| {
+            let mut output = fft.make_output_vec();
+            fft.process(input, &mut output).unwrap();
+            output
+        };
+        let value = transform(&mut value);
+        let weighted = transform(&mut weighted);
+
+        Self::from_values(
+            (0..points).map(|k| {
+                let delay = if value[k].norm_sqr() > 1e-24 {
+                    (weighted[k] / value[k]).re
+                } else {
+                    0.
+                };
+                (value[k], delay)
+            }),
+            sample_rate,
+            points,
+        )
+    }
+
+    /// Response of (numerator, denominator) sections in series.
+    pub fn from_sections(
+        sections: &[(Vec<f64>, Vec<f64>)],
+        sample_rate: Frequency,
+        points: usize,
+    ) -> Self {
+        let values = (0..points).map(|k| {
+            let w = PI * k as f64 / (points - 1).max(1) as f64;
+            let mut h = C64::new(1., 0.);
+            let mut delay = 0.;
+            for (numerator, denominator) in sections {
+                let (b, b_delay) = evaluate(numerator, w);
+                let (a, a_delay) = evaluate(denominator, w);
+                h *= b / a;
+                delay += b_delay - a_delay;
+            }
+            (h, delay)
+        });
+        Self::from_values(values, sample_rate, points)
+    }
+
+    // From the transform and group delay in samples at each of `points`.
+    fn from_values(
+        values: impl Iterator<Item = (C64, f64)>,
+        sample_rate: Frequency,
+        points: usize,
+    ) -> Self {
+        let sample_rate = sample_rate.value() as f64;
+        let mut response = Self {
+            frequency: Vec::with_capacity(points),
+            magnitude: Vec::with_capacity(points),
+            phase: Vec::with_capacity(points),
+            group_delay: Vec::with_capacity(points),
+        };
+
+        let mut last_phase = 0.;
+        let mut unwrap = 0.;
+        for (k, (h, delay)) in values.enumerate() {
+            let f = 0.5 * k as f64 / (points - 1).max(1) as f64;
+            let phase = h.arg();
+            if k > 0 {
+                let step = phase - last_phase;
+                if step > PI {
+                    unwrap -= 2. * PI;
+                } else if step < -PI {
+                    unwrap += 2. * PI;
+                }
+            }
+            last_phase = phase;
+
+            response.frequency.push((f * sample_rate) as f32);
+            response
+                .magnitude
+                .push((10. * h.norm_sqr().max(1e-20).log10()) as f32);
+            response.phase.push((phase + unwrap) as f32);
+            response.group_delay.push((delay / sample_rate) as f32);
+        }
+
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::fir::FirBuilder;
+    use crate::dsp::iir::{IirBuilder, Prototype};
+
+    #[test]
+    fn test_response() {
+        let fs = Frequency::Hertz(8000.);
+        let coeff = FirBuilder::low_pass(101, fs, Frequency::Hertz(1000.)).coefficients();
+        let response = Response::new(&coeff, fs, 201);
+
+        assert_eq!(response.frequency[200], 4000.);
+        assert!(response.magnitude[0].abs() < 0.01);
+        assert!(response.magnitude[150] < -60.);
+
+        // Linear phase: a constant delay of half the kernel, and the phase
+        // falls in step with it.
+        for k in 0..40 {
+            assert!((response.group_delay[k] * 8000. - 50.).abs() < 0.01);
+            let expected = -2. * std::f32::consts::PI * response.frequency[k] * 50. / 8000.;
+            assert!((response.phase[k] - expected).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_long_kernel() {
+        // Longer than the transform, so it has to fold.
+        let fs = Frequency::Hertz(8000.);
+        let coeff = FirBuilder::low_pass(1001, fs, Frequency::Hertz(1000.)).coefficients();
+        let response = Response::new(&coeff, fs, 65);
+        let coeff: Vec<f64> = coeff.iter().map(|c| *c as f64).collect();
+        let direct = Response::from_sections(&[(coeff, vec![1.])], fs, 65);
+
+        for k in 0..65 {
+            assert!((response.magnitude[k] - direct.magnitude[k]).abs() < 1e-3);
+            if direct.magnitude[k] > -60. {
+                assert!((response.group_delay[k] - direct.group_delay[k]).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_iir_response() {
+        let fs = Frequency::Hertz(8000.);
+        let cascade =
+            IirBuilder::low_pass(Prototype::Butterworth, 4, fs, Frequency::Hertz(1000.)).build();
+        let response = FilterTaps::iir("Butterworth", fs, &cascade).response(201);
+
+        for k in 0..201 {
+            let w = 2. * PI * response.frequency[k] as f64 / 8000.;
+            let expected = 10. * cascade.response(w).norm_sqr().log10();
+            // Nyquist is a zero.
+            if expected > -150. {
+                assert!((response.magnitude[k] as f64 - expected).abs() < 1e-3);
+            }
+        }
+        assert!((response.magnitude[50] + 3.01).abs() < 0.01);
+
+        // Group delay is the slope of the phase.
+        for k in 1..40 {
+            let slope = (response.phase[k + 1] - response.phase[k - 1])
+                / (response.frequency[k + 1] - response.frequency[k - 1]);
+            let delay = -slope / (2. * std::f32::consts::PI);
+            assert!((response.group_delay[k] - delay).abs() < 1e-5);
+        }
+    }
+}
@@ -1,57 +1,95 @@
+mod prefilter;
+
 use tokio::sync::mpsc::error::TrySendError;
 use tokio::sync::mpsc::Sender;
 use wasm_timer::Instant;
 
-use crate::configuration::{AudioSampleRate, Configuration};
+use crate::configuration::{AudioSampleRate, Configuration, MonitorSource, Probe};
 use crate::dsp::aggregator::Aggregator;
-use crate::dsp::decode::Rtty;
-use crate::message::MessageSender;
+use crate::dsp::decode::{PskDemodulator, Rtty};
+use crate::dsp::downconvert::Downconverter;
+use crate::dsp::frequency_shifter::FrequencyShifter;
+use crate::dsp::noise::Denoiser;
+use crate::dsp::response::FilterTaps;
+use crate::message::{DecoderEvent, MessageSender};
+use crate::scope::Trace;
 use crate::statistics::Statistics;
-use crate::types::SampleType;
+use crate::types::{ComplexType, SampleType};
+use crate::units::Frequency;
+
+use prefilter::Prefilter;
+
+/// Samples are processed, sent on to the monitor and decoded text streamed
+/// out, in slices this long, in seconds, whatever the input rate.
+pub const SLICE_DURATION: f32 = 0.25;
+
+fn slice_len(sample_rate: AudioSampleRate) -> usize {
+    (SLICE_DURATION * sample_rate.as_frequency().value()).round() as usize
+}
 
 pub struct Rx {
-    plot_sender: Option<Sender<Vec<SampleType>>>,
+    plot_sender: Option<Sender<Vec<Trace>>>,
+    monitor_sender: Option<Sender<Vec<SampleType>>>,
     message_sender: Option<MessageSender>,
     sample_rate: AudioSampleRate,
     aggregator: Aggregator,
+    downconverter: Downconverter,
+    denoiser: Denoiser,
+    prefilter: Prefilter,
+    shifter: FrequencyShifter,
     decoder: Rtty,
+    // Only runs for the scope.
+    psk: PskDemodulator,
 }
 
 impl Rx {
     pub fn new(config: &Configuration) -> Self {
         let sample_rate = config.audio_sample_rate;
-        let aggregator = Aggregator::new(11025);
+        let aggregator = Aggregator::new(slice_len(sample_rate));
 
+        let downconverter = Downconverter::new(sample_rate.into());
+        let denoiser = Denoiser::new(sample_rate.into());
+        let prefilter = Prefilter::new(sample_rate.into());
+        let shifter = FrequencyShifter::new(sample_rate.into());
         let decoder = Rtty::new(sample_rate.into());
+        let psk = PskDemodulator::new(sample_rate.into());
         Self {
             plot_sender: Default::default(),
+            monitor_sender: Default::default(),
             message_sender: Default::default(),
             sample_rate,
             aggregator,
+            downconverter,
+            denoiser,
+            prefilter,
+            shifter,
             decoder,
+            psk,
         }
     }
 
-    pub fn with_plot_sender(mut self, plot_sender: Sender<Vec<SampleType>>) -> Self {
+    pub fn with_plot_sender(mut self, plot_sender: Sender<Vec<Trace>>) -> Self {
         self.plot_sender = Some(plot_sender);
         self
     }
 
+    /// Audio for the monitor output, at the input rate.
+    pub fn with_monitor_sender(mut self, monitor_sender: Sender<Vec<SampleType>>) -> Self {
+        self.monitor_sender = Some(monitor_sender);
+        self
+    }
+
     pub fn with_message_sender(mut self, message_sender: MessageSender) -> Self {
         self.message_sender = Some(message_sender);
         self
     }
 
-    pub fn run(
-        &mut self,
-        new_samples: Vec<SampleType>,
-        config: &Configuration,
-        stats: &mut Statistics,
-    ) {
+    fn update_sample_rate(&mut self, config: &Configuration) {
         let sample_rate = config.audio_sample_rate;
         if sample_rate != self.sample_rate {
             // I HATE THIS
             let plot_sender = self.plot_sender.clone();
+            let monitor_sender = self.monitor_sender.clone();
             let message_sender = self.message_sender.clone();
             let mut rx = Self::new(config);
 
@@ -59,40 +97,194 @@ impl Rx {
                 rx = rx.with_plot_sender(sender);
             }
 
+            if let Some(sender) = monitor_sender {
+                rx = rx.with_monitor_sender(sender);
+            }
+
             if let Some(sender) = message_sender {
                 rx = rx.with_message_sender(sender);
             }
 
             *self = rx;
         }
+    }
+
+    /// The filters samples go through on their way to the decoder.
+    pub fn filters(&self) -> Vec<FilterTaps> {
+        let mut filters: Vec<FilterTaps> = self.prefilter.filter().into_iter().collect();
+        filters.extend(self.decoder.filters());
+        filters
+    }
+
+    /// Runs the enabled noise stages over real audio, in place. This comes
+    /// ahead of both `run` and the waterfall, so they both show the effect.
+    pub fn clean(&mut self, samples: &mut [SampleType], config: &Configuration) {
+        self.update_sample_rate(config);
+        self.denoiser.process(samples, &config.noise);
+    }
+
+    /// Like `clean`, for I/Q ahead of `run_complex`.
+    pub fn clean_complex(&mut self, samples: &mut [ComplexType], config: &Configuration) {
+        self.update_sample_rate(config);
+        self.denoiser.process_complex(samples, &config.noise);
+    }
+
+    /// The decoder listens at fixed audio tones, so the tuner's passband is
+    /// moved there before decoding.
+    pub fn run_complex(
+        &mut self,
+        new_samples: &[ComplexType],
+        config: &Configuration,
+        stats: &mut Statistics,
+    ) {
+        self.update_sample_rate(config);
+        let samples = self
+            .downconverter
+            .run(new_samples, &config.tuner, Rtty::CARRIER);
+        self.decode(samples, false, config, stats);
+    }
+
+    /// Real audio is moved the same way as complex input, a slice at a time,
+    /// so that the raw input is still there for the monitor and scope.
+    pub fn run(
+        &mut self,
+        new_samples: Vec<SampleType>,
+        config: &Configuration,
+        stats: &mut Statistics,
+    ) {
+        self.update_sample_rate(config);
+        self.decode(new_samples, true, config, stats);
+    }
+
+    /// Sends either what came in or the filtered channel to the monitor.
+    /// `carrier` is where the tuner's carrier is in `raw`.
+    fn monitor(
+        &mut self,
+        raw: &[SampleType],
+        carrier: Frequency,
+        channel: &[SampleType],
+        config: &Configuration,
+    ) {
+        let settings = &config.monitor;
+        let sender = match &self.monitor_sender {
+            Some(sender) if settings.enabled => sender,
+            _ => return,
+        };
+
+        let (audio, carrier) = match settings.source {
+            MonitorSource::Raw => (raw, carrier),
+            MonitorSource::Channel => (channel, Rtty::CARRIER),
+        };
+        let audio = if settings.shift {
+            let shift = Frequency::Hertz(settings.pitch) - carrier;
+            self.shifter.process(audio, shift)
+        } else {
+            audio.to_vec()
+        };
+
+        if let Err(err) = sender.try_send(audio) {
+            match err {
+                TrySendError::Full(_) => (),
+                TrySendError::Closed(_) => (),
+            }
+        }
+    }
+
+    /// `tune` is set when the tuner's carrier is still where it was received,
+    /// rather than already moved to `Rtty::CARRIER`.
+    fn decode(
+        &mut self,
+        new_samples: Vec<SampleType>,
+        tune: bool,
+        config: &Configuration,
+        stats: &mut Statistics,
+    ) {
+        let carrier = if tune {
+            config.tuner.carrier()
+        } else {
+            Rtty::CARRIER
+        };
+        self.prefilter.set_passband(
+            Rtty::CARRIER + config.tuner.lower(),
+            Rtty::CARRIER + config.tuner.upper(),
+        );
 
         self.aggregator.aggregate(new_samples);
 
         while let Some(samples) = self.aggregator.get_slice() {
             let now = Instant::now();
 
-            let mut output_samples: Vec<f32> = Vec::new();
-            for sample in samples {
-                let (out, message) = self.decoder.update(sample);
-                if let Some(out) = out {
-                    output_samples.push(out);
+            let tuned = if tune {
+                self.downconverter
+                    .run_real(&samples, &config.tuner, Rtty::CARRIER)
+            } else {
+                samples.clone()
+            };
+            let mut output_samples = vec![0.; samples.len()];
+            self.prefilter.process(&tuned, &mut output_samples);
+
+            self.monitor(&samples, carrier, &output_samples, config);
+
+            // Nothing is probed unless the scope is listening.
+            let probes: &[Probe] = match self.plot_sender {
+                Some(_) => config.scope.active_probes(),
+                None => &[],
+            };
+            let sample_rate = self.sample_rate.as_frequency();
+            let mut traces = Vec::new();
+            if probes.contains(&Probe::Input) {
+                traces.push(Trace::new(Probe::Input, sample_rate, samples.clone()));
+            }
+            if probes.contains(&Probe::Channel) {
+                traces.push(Trace::new(
+                    Probe::Channel,
+                    sample_rate,
+                    output_samples.clone(),
+                ));
+            }
+            if probes.contains(&Probe::PskI) || probes.contains(&Probe::PskQ) {
+                let (i, q): (Vec<_>, Vec<_>) = self
+                    .psk
+                    .process(&output_samples, Rtty::CARRIER)
+                    .iter()
+                    .map(|symbol| (symbol.re, symbol.im))
+                    .unzip();
+                for (probe, samples) in [(Probe::PskI, i), (Probe::PskQ, q)] {
+                    if probes.contains(&probe) {
+                        traces.push(Trace::new(probe, PskDemodulator::BAUD, samples));
+                    }
                 }
+            }
 
-                if let Some(message) = message {
-                    if let Some(sender) = &self.message_sender {
-                        if let Err(err) = sender.try_send(message) {
-                            match err {
-                                //TrySendError::Full(_) => println!("Plot ui falling behind"),
-                                TrySendError::Full(_) => (),
-                                TrySendError::Closed(_) => (),
-                            }
+            let mut text = String::new();
+            let messages = self
+                .decoder
+                .process(&output_samples, probes, &mut traces, &mut text);
+            let frequency = config.display_frequency(config.tuner.carrier());
+            let mut events = Vec::new();
+            if !text.is_empty() {
+                events.push(DecoderEvent::Text(text));
+            }
+            for message in messages {
+                let message = Box::new(message.with_frequency(frequency));
+                events.push(DecoderEvent::Message(message));
+            }
+            for event in events {
+                if let Some(sender) = &self.message_sender {
+                    if let Err(err) = sender.try_send(event) {
+                        match err {
+                            //TrySendError::Full(_) => println!("Plot ui falling behind"),
+                            TrySendError::Full(_) => (),
+                            TrySendError::Closed(_) => (),
                         }
                     }
                 }
             }
 
+            // In the order they were picked, so the first is triggered on.
+            traces.sort_by_key(|trace| probes.iter().position(|probe| *probe == trace.probe));
             if let Some(sender) = &self.plot_sender {
-                if let Err(err) = sender.try_send(output_samples) {
+                if let Err(err) = sender.try_send(traces) {
                     match err {
                         //TrySendError::Full(_) => println!("Plot ui falling behind"),
                         TrySendError::Full(_) => (),
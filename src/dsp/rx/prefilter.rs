@@ -0,0 +1,81 @@
+use crate::dsp::fir::{FirBuilder, SymmetricFir};
+use crate::dsp::response::FilterTaps;
+use crate::units::Frequency;
+
+const KERNEL_LENGTH: usize = 255;
+
+// Cutoffs closer than this to 0 Hz or Nyquist are left open.
+const MARGIN: f32 = 10.0;
+
+/// Band-pass between the tuner's cutoffs, ahead of the decoder.
+pub struct Prefilter {
+    sample_rate: f32,
+    passband: (f32, f32),
+    // None passes everything.
+    fir: Option<SymmetricFir>,
+}
+
+impl Prefilter {
+    pub fn new(sample_rate: Frequency) -> Self {
+        Self {
+            sample_rate: sample_rate.value(),
+            passband: (0.0, 0.0),
+            fir: None,
+        }
+    }
+
+    /// Rebuilds the filter if the passband has moved.
+    pub fn set_passband(&mut self, lower: Frequency, upper: Frequency) {
+        let passband = (lower.value(), upper.value());
+        if passband == self.passband {
+            return;
+        }
+        self.passband = passband;
+
+        let sample_rate = Frequency::Hertz(self.sample_rate);
+        let nyquist = self.sample_rate / 2.0;
+        let lower = passband.0.min(passband.1);
+        let upper = passband.0.max(passband.1);
+        let has_lower = lower > MARGIN && lower < nyquist - MARGIN;
+        let has_upper = upper > MARGIN && upper < nyquist - MARGIN;
+
+        let builder = match (has_lower, has_upper) {
+            (true, true) if upper > lower => Some(FirBuilder::band_pass(
+                KERNEL_LENGTH,
+                sample_rate,
+                Frequency::Hertz(lower),
+                Frequency::Hertz(upper),
+            )),
+            (true, false) => Some(FirBuilder::high_pass(
+                KERNEL_LENGTH,
+                sample_rate,
+                Frequency::Hertz(lower),
+            )),
+            (false, true) => Some(FirBuilder::low_pass(
+                KERNEL_LENGTH,
+                sample_rate,
+                Frequency::Hertz(upper),
+            )),
+            _ => None,
+        };
+
+        self.fir = builder.map(|builder| builder.build_symmetric());
+    }
+
+    pub fn filter(&self) -> Option<FilterTaps> {
+        self.fir.as_ref().map(|fir| {
+            FilterTaps::new(
+                "Prefilter",
+                Frequency::Hertz(self.sample_rate),
+                fir.coefficients(),
+            )
+        })
+    }
+
+    pub fn process(&mut self, input: &[f32], output: &mut [f32]) {
+        match &mut self.fir {
+            Some(fir) => fir.process(input, output),
+            None => output.copy_from_slice(input),
+        }
+    }
+}
@@ -5,6 +5,7 @@ use egui::{Color32, ColorImage};
 use realfft::RealFftPlanner;
 use realfft::RealToComplex;
 use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
 use tokio::sync::mpsc::error::TrySendError;
 use tokio::sync::mpsc::Sender;
 use wasm_timer::Instant;
@@ -12,53 +13,150 @@ use wasm_timer::Instant;
 use crate::configuration::Configuration;
 use crate::dsp::aggregator::Aggregator;
 use crate::statistics::Statistics;
-use crate::types::{SampleType, PLOT_DEPTH};
+use crate::types::{ComplexType, SampleType, PLOT_DEPTH};
 
 use super::turbo::get_color;
 
-pub struct WaterfallProcessor {
-    fft: Arc<dyn RealToComplex<f32>>,
-    fft_depth: usize,
+/// The scrolling waterfall image. Rows are pushed in at the bottom and the
+/// zoomed, scrolled part is cropped out for display.
+struct Canvas {
     image: Option<ColorImage>,
     pixels: VecDeque<Color32>,
     scroll: f32,
+    // How many rows have been pushed.
+    rows: u64,
+}
+
+impl Canvas {
+    fn new(config: &Configuration) -> Self {
+        Self {
+            image: None,
+            pixels: VecDeque::new(),
+            scroll: config.scroll,
+            rows: 0,
+        }
+    }
+
+    /// Add a row of magnitudes, one per displayed bin, and return the image
+    /// to display.
+    fn push(
+        &mut self,
+        magnitudes: impl Iterator<Item = f32>,
+        fft_depth: usize,
+        config: &Configuration,
+    ) -> ColorImage {
+        if let Some(image) = &self.image {
+            if image.size[0] != config.effective_len() {
+                let image =
+                    ColorImage::new([config.effective_len(), PLOT_DEPTH], Color32::default());
+                self.pixels =
+                    VecDeque::from(vec![Color32::BLACK; config.effective_len() * PLOT_DEPTH]);
+                self.scroll = config.scroll;
+                self.rows = 0;
+                self.image = Some(image);
+            }
+        } else {
+            let image = ColorImage::new([config.effective_len(), PLOT_DEPTH], Color32::default());
+            self.pixels = VecDeque::from(vec![Color32::BLACK; config.effective_len() * PLOT_DEPTH]);
+            self.scroll = config.scroll;
+            self.image = Some(image);
+        }
+
+        let m = 255.0 / (config.max_db - config.min_db);
+        let scale_func = |x| m * (x - config.min_db);
+
+        self.pixels.drain(..config.effective_len());
+        self.rows += 1;
+
+        magnitudes
+            .map(|f| f / (fft_depth as f32).sqrt()) // Normalization
+            .map(|f| 10.0 * f.log10()) // dB
+            .map(scale_func)
+            .map(|f| f.clamp(0.0, 255.0))
+            .map(|f| f as usize)
+            .map(get_color)
+            .map(|[r, g, b]| Color32::from_rgb(r, g, b))
+            .for_each(|pixel| self.pixels.push_back(pixel));
+
+        let zoomed_length = config.zoomed_length();
+        let scroll_start = config.scroll_start();
+        let scroll_stop = config.scroll_stop();
+
+        let mut cropped_pixels: Vec<Color32> = Vec::with_capacity(zoomed_length * PLOT_DEPTH);
+        for y in 0..PLOT_DEPTH {
+            let offset = y * config.effective_len();
+            for x in scroll_start..scroll_stop {
+                // TODO: The access on pixels can be out of bounds
+                cropped_pixels.push(self.pixels[offset + x]);
+            }
+        }
+
+        ColorImage {
+            size: [zoomed_length, PLOT_DEPTH],
+            pixels: cropped_pixels,
+        }
+    }
+}
+
+pub struct WaterfallProcessor {
+    fft: Arc<dyn RealToComplex<f32>>,
+    complex_fft: Arc<dyn Fft<f32>>,
+    fft_depth: usize,
+    canvas: Canvas,
     aggregator: Aggregator,
+    complex_aggregator: Aggregator<ComplexType>,
     sender: Sender<ColorImage>,
+    // The latest real spectrum in dB, from 0 Hz to Nyquist.
+    spectrum: Vec<f32>,
     // plot: Sender<Vec<SampleType>>,
 }
 
 impl WaterfallProcessor {
     pub fn new(sender: Sender<ColorImage>, config: &Configuration) -> Self {
         let mut planner = RealFftPlanner::<f32>::new();
+        let mut complex_planner = FftPlanner::<f32>::new();
         let fft_depth = config.fft_depth;
-        let scroll = config.scroll;
         let fft = planner.plan_fft_forward(fft_depth);
+        let complex_fft = complex_planner.plan_fft_forward(fft_depth);
         let aggregator = Aggregator::new(fft_depth);
+        let complex_aggregator = Aggregator::new(fft_depth);
 
         Self {
             fft,
+            complex_fft,
             fft_depth,
-            image: None,
-            pixels: VecDeque::new(),
-            scroll,
+            canvas: Canvas::new(config),
             // plot,
             aggregator,
+            complex_aggregator,
             sender,
+            spectrum: Vec::new(),
+        }
+    }
+
+    fn update_fft_depth(&mut self, config: &Configuration) {
+        if self.fft_depth != config.fft_depth {
+            *self = Self::new(self.sender.clone(), config);
         }
     }
 
+    pub fn spectrum(&self) -> &[f32] {
+        &self.spectrum
+    }
+
+    /// How many rows the waterfall has scrolled by. Starts over when the
+    /// waterfall does.
+    pub fn rows(&self) -> u64 {
+        self.canvas.rows
+    }
+
     pub fn run(
         &mut self,
         new_samples: Vec<SampleType>,
         config: &Configuration,
         stats: &mut Statistics,
     ) {
-        if self.fft_depth != config.fft_depth {
-            self.fft_depth = config.fft_depth;
-            let mut planner = RealFftPlanner::<f32>::new();
-            self.fft = planner.plan_fft_forward(self.fft_depth);
-            self.aggregator = Aggregator::new(self.fft_depth);
-        }
+        self.update_fft_depth(config);
 
         self.aggregator.aggregate(new_samples);
 
@@ -78,67 +176,65 @@ impl WaterfallProcessor {
 
             let now = Instant::now();
 
-            if let Some(image) = &self.image {
-                if image.size[0] != config.effective_len() {
-                    let image =
-                        ColorImage::new([config.effective_len(), PLOT_DEPTH], Color32::default());
-                    self.pixels =
-                        VecDeque::from(vec![Color32::BLACK; config.effective_len() * PLOT_DEPTH]);
-                    self.scroll = config.scroll;
-                    self.image = Some(image);
-                }
-            } else {
-                let image =
-                    ColorImage::new([config.effective_len(), PLOT_DEPTH], Color32::default());
-                self.pixels =
-                    VecDeque::from(vec![Color32::BLACK; config.effective_len() * PLOT_DEPTH]);
-                self.scroll = config.scroll;
-                self.image = Some(image);
-            }
-
             let mut spectrum = self.fft.make_output_vec();
             self.fft.process(&mut samples, &mut spectrum).unwrap();
 
+            let normalization = (self.fft_depth as f32).sqrt();
+            self.spectrum = spectrum
+                .iter()
+                .map(|c| 10.0 * (c.norm() / normalization).log10())
+                .collect();
+
             if config.effective_len() < self.fft_depth {
                 spectrum.resize(config.effective_len(), Complex::default());
             }
 
-            let m = 255.0 / (config.max_db - config.min_db);
-            let scale_func = |x| m * (x - config.min_db);
-
-            self.pixels.drain(..config.effective_len());
-
-            spectrum
-                .into_iter()
-                .map(|c| c.norm()) // Magnitude
-                .map(|f| f / (self.fft_depth as f32).sqrt()) // Normalization
-                .map(|f| 10.0 * f.log10()) // dB
-                .map(scale_func)
-                .map(|f| f.clamp(0.0, 255.0))
-                .map(|f| f as usize)
-                .map(get_color)
-                .map(|[r, g, b]| Color32::from_rgb(r, g, b))
-                .for_each(|pixel| self.pixels.push_back(pixel));
-
-            let zoomed_length = config.zoomed_length();
-            let scroll_start = config.scroll_start();
-            let scroll_stop = config.scroll_stop();
-
-            let mut cropped_pixels: Vec<Color32> = Vec::with_capacity(zoomed_length * PLOT_DEPTH);
-            for y in 0..PLOT_DEPTH {
-                let offset = y * config.effective_len();
-                for x in scroll_start..scroll_stop {
-                    // TODO: The access on pixels can be out of bounds
-                    cropped_pixels.push(self.pixels[offset + x]);
-                }
-            }
+            let magnitudes = spectrum.into_iter().map(|c| c.norm());
+            permit.send(self.canvas.push(magnitudes, self.fft_depth, config));
+
+            let elapsed = now.elapsed();
+            stats.waterfall.push(elapsed);
+        }
+    }
 
-            let cropped_image = ColorImage {
-                size: [zoomed_length, PLOT_DEPTH],
-                pixels: cropped_pixels,
+    /// Like `run`, but shows negative frequencies too.
+    pub fn run_complex(
+        &mut self,
+        new_samples: Vec<ComplexType>,
+        config: &Configuration,
+        stats: &mut Statistics,
+    ) {
+        self.update_fft_depth(config);
+
+        self.complex_aggregator.aggregate(new_samples);
+        self.spectrum.clear();
+
+        while let Some(mut spectrum) = self.complex_aggregator.get_slice() {
+            let permit = match self.sender.try_reserve() {
+                Err(err) => {
+                    match err {
+                        TrySendError::Full(_) => {
+                            println!("Waterfall UI is falling behind, dropping samples.")
+                        }
+                        TrySendError::Closed(_) => (),
+                    }
+                    continue;
+                }
+                Ok(permit) => permit,
             };
 
-            permit.send(cropped_image);
+            let now = Instant::now();
+
+            self.complex_fft.process(&mut spectrum);
+
+            // Put -Fs/2 first, then trim symmetrically around zero.
+            spectrum.rotate_left(self.fft_depth / 2);
+            let len = config.effective_len().min(self.fft_depth);
+            let start = (self.fft_depth - len) / 2;
+
+            let magnitudes = spectrum[start..start + len].iter().map(|c| c.norm());
+            permit.send(self.canvas.push(magnitudes, self.fft_depth, config));
+
             let elapsed = now.elapsed();
             stats.waterfall.push(elapsed);
         }
@@ -1,19 +1,139 @@
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::Stream;
+use cpal::{Device, Sample, SampleRate, Stream, StreamConfig, SupportedStreamConfig};
 use tokio::sync::mpsc::error::TrySendError;
 use tokio::sync::mpsc::Sender;
 
-use crate::configuration::{AudioSampleRate, Configuration};
+use crate::configuration::{AudioSampleRate, ChannelMode, Configuration};
+use crate::input::channel_mixer::ChannelMixer;
 use crate::input::Source;
-use crate::types::SampleType;
-use crate::units::Frequency;
+use crate::types::Samples;
 
 pub struct Audio {
-    sender: Sender<Vec<SampleType>>,
-    _stream: Stream,
+    sender: Sender<Samples>,
+    // None when the device couldn't be opened as configured.
+    _stream: Option<Stream>,
 
     sample_rate: AudioSampleRate,
     device_name: String,
+    channel_mode: ChannelMode,
+    status: String,
+}
+
+/// Pick a config that runs at the requested rate, preferring stereo when the
+/// channel mode needs it. I/Q needs two channels. Everything downstream
+/// assumes the configured rate, so a device that can't run at it is refused,
+/// with the rates it can run at.
+fn choose_config(
+    device: &Device,
+    sample_rate: AudioSampleRate,
+    channel_mode: ChannelMode,
+) -> Result<SupportedStreamConfig, String> {
+    let rate = sample_rate.as_frequency().value() as u32;
+    let stereo = channel_mode != ChannelMode::Left;
+
+    let mut configs: Vec<_> = device
+        .supported_input_configs()
+        .map_err(|err| err.to_string())?
+        .filter(|c| channel_mode != ChannelMode::Iq || c.channels() >= 2)
+        .collect();
+    if configs.is_empty() {
+        return Err(match channel_mode {
+            ChannelMode::Iq => "I/Q needs a device with two channels".to_string(),
+            _ => "No supported input configs".to_string(),
+        });
+    }
+    configs.sort_by_key(|c| {
+        (
+            stereo && c.channels() < 2,
+            c.sample_format() != cpal::SampleFormat::F32,
+        )
+    });
+
+    let supported = configs
+        .iter()
+        .find(|c| c.min_sample_rate().0 <= rate && rate <= c.max_sample_rate().0);
+
+    match supported {
+        Some(config) => Ok(config.clone().with_sample_rate(SampleRate(rate))),
+        None => {
+            let mut rates: Vec<String> = configs
+                .iter()
+                .map(|c| match (c.min_sample_rate().0, c.max_sample_rate().0) {
+                    (min, max) if min == max => format!("{} Hz", min),
+                    (min, max) => format!("{}-{} Hz", min, max),
+                })
+                .collect();
+            rates.sort();
+            rates.dedup();
+            Err(format!(
+                "does not support {}, only {}",
+                sample_rate,
+                rates.join(", ")
+            ))
+        }
+    }
+}
+
+fn find_device(name: &str) -> Result<Device, String> {
+    let host = cpal::default_host();
+    if name == "Default" {
+        return host
+            .default_input_device()
+            .ok_or_else(|| "no input device".to_string());
+    }
+    host.input_devices()
+        .map_err(|err| err.to_string())?
+        .find(|d| matches!(d.name(), Ok(n) if n == name))
+        .ok_or_else(|| "no such device".to_string())
+}
+
+fn open(
+    config: &Configuration,
+    sender: Sender<Samples>,
+) -> Result<(Stream, SupportedStreamConfig), String> {
+    let device = find_device(&config.input_device)?;
+    let supported = choose_config(&device, config.audio_sample_rate, config.input_channel)?;
+    let mixer = ChannelMixer::new(config.input_channel, supported.channels() as usize);
+
+    let stream_config = supported.config();
+    let stream = match supported.sample_format() {
+        cpal::SampleFormat::F32 => build_stream::<f32>(&device, &stream_config, mixer, sender),
+        cpal::SampleFormat::I16 => build_stream::<i16>(&device, &stream_config, mixer, sender),
+        cpal::SampleFormat::U16 => build_stream::<u16>(&device, &stream_config, mixer, sender),
+    }
+    .map_err(|err| err.to_string())?;
+    stream.play().map_err(|err| err.to_string())?;
+
+    Ok((stream, supported))
+}
+
+fn build_stream<T: Sample>(
+    device: &Device,
+    config: &StreamConfig,
+    mut mixer: ChannelMixer,
+    sender: Sender<Samples>,
+) -> Result<Stream, cpal::BuildStreamError> {
+    let err_fn = move |_err| {
+        // react to errors here.
+    };
+
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &_| {
+            let data: Vec<_> = data.iter().map(|v| v.to_f32()).collect();
+            let samples = mixer.mix(&data);
+
+            if let Err(err) = sender.try_send(samples) {
+                match err {
+                    TrySendError::Full(_) => {
+                        println!("Waterfall processor falling behind")
+                    }
+                    TrySendError::Closed(_) => (),
+                }
+            }
+        },
+        err_fn,
+    )
 }
 
 impl Audio {
@@ -26,183 +146,46 @@ impl Audio {
             .collect()
     }
 
-    pub fn new(sender: Sender<Vec<SampleType>>, config: &Configuration) -> Self {
-        let sample_rate = config.audio_sample_rate;
-
-        let host = cpal::default_host();
-
-        let device_name = config.input_device.to_string();
-        let device = if device_name == "Default" {
-            host.default_input_device().expect("No input device")
-        } else {
-            host.input_devices()
-                .unwrap()
-                .into_iter()
-                .find(|d| d.name().unwrap() == device_name)
-                .unwrap()
-        };
-
-        println!("Using device {}", device.name().unwrap());
-
-        let mut supported_configs_range = device
-            .supported_input_configs()
-            .expect("error while querying configs");
-
-        let config = supported_configs_range
-            .next()
-            .expect("no supported config?!")
-            .with_max_sample_rate();
-
-        let err_fn = move |_err| {
-            // react to errors here.
+    pub fn new(sender: Sender<Samples>, config: &Configuration) -> Self {
+        let (stream, status) = match open(config, sender.clone()) {
+            Ok((stream, supported)) => {
+                let status = format!(
+                    "{}, {} channels at {} Hz",
+                    config.input_device,
+                    supported.channels(),
+                    supported.sample_rate().0
+                );
+                (Some(stream), status)
+            }
+            Err(err) => (None, format!("{}: {}", config.input_device, err)),
         };
 
-        let stream_sender = sender.clone();
-        let stream = match config.channels() {
-            1 => match config.sample_format() {
-                cpal::SampleFormat::F32 => device.build_input_stream(
-                    &config.into(),
-                    move |data: &[f32], _: &_| {
-                        let samples: Vec<_> = data.iter().copied().collect();
-
-                        if let Err(err) = stream_sender.try_send(samples) {
-                            match err {
-                                TrySendError::Full(_) => {
-                                    println!("Waterfall processor falling behind")
-                                }
-                                TrySendError::Closed(_) => (),
-                            }
-                        }
-                    },
-                    err_fn,
-                ),
-                cpal::SampleFormat::I16 => device.build_input_stream(
-                    &config.into(),
-                    move |data: &[i16], _: &_| {
-                        let samples = data.iter().map(|&v| v as f32).map(|v| v / 32768.0);
-                        let samples: Vec<_> = samples.collect();
-
-                        if let Err(err) = stream_sender.try_send(samples) {
-                            match err {
-                                TrySendError::Full(_) => {
-                                    println!("Waterfall processor falling behind")
-                                }
-                                TrySendError::Closed(_) => (),
-                            }
-                        }
-                    },
-                    err_fn,
-                ),
-                cpal::SampleFormat::U16 => device.build_input_stream(
-                    &config.into(),
-                    move |data: &[u16], _: &_| {
-                        let samples = data
-                            .iter()
-                            .map(|&v| v as f32)
-                            .map(|v| v / 32768.0)
-                            .map(|v| v - 1.0);
-                        let samples: Vec<_> = samples.collect();
-
-                        if let Err(err) = stream_sender.try_send(samples) {
-                            match err {
-                                TrySendError::Full(_) => {
-                                    println!("Waterfall processor falling behind")
-                                }
-                                TrySendError::Closed(_) => (),
-                            }
-                        }
-                    },
-                    err_fn,
-                ),
-            },
-            2 => match config.sample_format() {
-                cpal::SampleFormat::F32 => device.build_input_stream(
-                    &config.into(),
-                    move |data: &[f32], _: &_| {
-                        let samples = data.iter().step_by(2).copied();
-                        let samples: Vec<_> = samples.collect();
-
-                        if let Err(err) = stream_sender.try_send(samples) {
-                            match err {
-                                TrySendError::Full(_) => {
-                                    println!("Waterfall processor falling behind")
-                                }
-                                TrySendError::Closed(_) => (),
-                            }
-                        }
-                    },
-                    err_fn,
-                ),
-                cpal::SampleFormat::I16 => device.build_input_stream(
-                    &config.into(),
-                    move |data: &[i16], _: &_| {
-                        let samples = data
-                            .iter()
-                            .step_by(2)
-                            .map(|&v| v as f32)
-                            .map(|v| v / 32768.0);
-                        let samples: Vec<_> = samples.collect();
-
-                        if let Err(err) = stream_sender.try_send(samples) {
-                            match err {
-                                TrySendError::Full(_) => {
-                                    println!("Waterfall processor falling behind")
-                                }
-                                TrySendError::Closed(_) => (),
-                            }
-                        }
-                    },
-                    err_fn,
-                ),
-                cpal::SampleFormat::U16 => device.build_input_stream(
-                    &config.into(),
-                    move |data: &[u16], _: &_| {
-                        let samples = data
-                            .iter()
-                            .step_by(2)
-                            .map(|&v| v as f32)
-                            .map(|v| v / 32768.0)
-                            .map(|v| v - 1.0);
-                        let samples: Vec<_> = samples.collect();
-
-                        if let Err(err) = stream_sender.try_send(samples) {
-                            match err {
-                                TrySendError::Full(_) => {
-                                    println!("Waterfall processor falling behind")
-                                }
-                                TrySendError::Closed(_) => (),
-                            }
-                        }
-                    },
-                    err_fn,
-                ),
-            },
-            _ => panic!("Only supports 1 or 2 channels"),
-        }
-        .expect("Unable to build stream");
-
-        stream.play().expect("Unable to play stream");
-
-        println!("Audio initialized");
-
         Self {
             sender,
-            // config,
-            sample_rate,
             _stream: stream,
-            device_name,
+            sample_rate: config.audio_sample_rate,
+            device_name: config.input_device.to_string(),
+            channel_mode: config.input_channel,
+            status,
         }
     }
 }
 
 impl Source for Audio {
     fn run(&mut self, config: &Configuration) {
-        if config.audio_sample_rate != self.sample_rate || config.input_device != self.device_name {
+        if config.audio_sample_rate != self.sample_rate
+            || config.input_device != self.device_name
+            || config.input_channel != self.channel_mode
+        {
             *self = Audio::new(self.sender.clone(), config);
         }
     }
 
-    fn get_tx(&self) -> Sender<Vec<SampleType>> {
+    fn get_tx(&self) -> Sender<Samples> {
         self.sender.clone()
     }
+
+    fn status(&self) -> Option<String> {
+        Some(self.status.clone())
+    }
 }
@@ -0,0 +1,81 @@
+use crate::configuration::ChannelMode;
+use crate::types::{ComplexType, SampleType, Samples};
+
+/// Turns interleaved frames into the stream we process: mono for the real
+/// modes, I/Q for `ChannelMode::Iq`. Mono inputs are passed through for the
+/// real modes; sources must not ask for I/Q from them.
+pub struct ChannelMixer {
+    mode: ChannelMode,
+    channels: usize,
+}
+
+impl ChannelMixer {
+    pub fn new(mode: ChannelMode, channels: usize) -> Self {
+        Self { mode, channels }
+    }
+
+    pub fn mix(&mut self, data: &[SampleType]) -> Samples {
+        if self.channels < 2 {
+            return Samples::Real(data.to_vec());
+        }
+
+        let frames = data.chunks_exact(self.channels);
+        match self.mode {
+            ChannelMode::Left => Samples::Real(frames.map(|frame| frame[0]).collect()),
+            ChannelMode::Right => Samples::Real(frames.map(|frame| frame[1]).collect()),
+            ChannelMode::Sum => {
+                Samples::Real(frames.map(|frame| (frame[0] + frame[1]) / 2.0).collect())
+            }
+            ChannelMode::Iq => Samples::Complex(
+                frames
+                    .map(|frame| ComplexType::new(frame[0], frame[1]))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn real(samples: Samples) -> Vec<SampleType> {
+        match samples {
+            Samples::Real(samples) => samples,
+            Samples::Complex(_) => panic!("expected real samples"),
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let data = vec![1.0, 2.0, 3.0, 4.0];
+        assert_eq!(
+            real(ChannelMixer::new(ChannelMode::Left, 2).mix(&data)),
+            vec![1.0, 3.0]
+        );
+        assert_eq!(
+            real(ChannelMixer::new(ChannelMode::Right, 2).mix(&data)),
+            vec![2.0, 4.0]
+        );
+        assert_eq!(
+            real(ChannelMixer::new(ChannelMode::Sum, 2).mix(&data)),
+            vec![1.5, 3.5]
+        );
+        assert_eq!(
+            real(ChannelMixer::new(ChannelMode::Right, 1).mix(&data)),
+            data
+        );
+    }
+
+    #[test]
+    fn test_iq() {
+        let data = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        match ChannelMixer::new(ChannelMode::Iq, 3).mix(&data) {
+            Samples::Complex(samples) => assert_eq!(
+                samples,
+                vec![ComplexType::new(1.0, 2.0), ComplexType::new(4.0, 5.0)]
+            ),
+            Samples::Real(_) => panic!("expected complex samples"),
+        }
+    }
+}
@@ -8,21 +8,21 @@ use wasm_timer::Instant;
 
 use crate::configuration::{AudioSampleRate, Configuration};
 use crate::input::Source;
-use crate::types::SampleType;
+use crate::types::{SampleType, Samples};
 
 #[derive(RustEmbed)]
 #[folder = "example_signals/"]
 struct Asset;
 
 pub struct Example<'a> {
-    sender: Sender<Vec<SampleType>>,
+    sender: Sender<Samples>,
     sample_rate: AudioSampleRate,
     last_time: Option<Instant>,
     signal: WavReader<BufReader<std::io::Cursor<Cow<'a, [u8]>>>>,
 }
 
 impl<'a> Example<'a> {
-    pub fn new(sender: Sender<Vec<SampleType>>, config: &Configuration) -> Self {
+    pub fn new(sender: Sender<Samples>, config: &Configuration) -> Self {
         let sample_rate = config.audio_sample_rate;
 
         // let file = Asset::get("210703_133430.wav").unwrap();
@@ -67,7 +67,7 @@ impl<'a> Source for Example<'a> {
                     samples.push(sample / 2_f32.powf(16.));
                 });
 
-                self.sender.try_send(samples).unwrap();
+                self.sender.try_send(Samples::Real(samples)).unwrap();
             }
 
             Some(now)
@@ -76,7 +76,7 @@ impl<'a> Source for Example<'a> {
         };
     }
 
-    fn get_tx(&self) -> Sender<Vec<SampleType>> {
+    fn get_tx(&self) -> Sender<Samples> {
         self.sender.clone()
     }
 }
@@ -0,0 +1,69 @@
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::Sender;
+use wasm_timer::Instant;
+
+use crate::configuration::{AudioSampleRate, Configuration};
+use crate::dsp::generator::SignalGenerator;
+use crate::input::Source;
+use crate::types::Samples;
+
+pub struct Generator {
+    sender: Sender<Samples>,
+    sample_rate: AudioSampleRate,
+    last_time: Option<Instant>,
+    generator: SignalGenerator,
+}
+
+impl Generator {
+    pub fn new(sender: Sender<Samples>, config: &Configuration) -> Self {
+        let sample_rate = config.audio_sample_rate;
+        let generator = SignalGenerator::new(&config.generator, sample_rate.as_frequency());
+
+        Self {
+            sender,
+            sample_rate,
+            last_time: None,
+            generator,
+        }
+    }
+}
+
+impl Source for Generator {
+    fn run(&mut self, config: &Configuration) {
+        if config.audio_sample_rate != self.sample_rate
+            || config.generator != *self.generator.settings()
+        {
+            self.sample_rate = config.audio_sample_rate;
+            self.generator =
+                SignalGenerator::new(&config.generator, self.sample_rate.as_frequency());
+        }
+
+        self.last_time = if let Some(last_time) = self.last_time {
+            let now = Instant::now();
+
+            let elapsed = (now - last_time).as_secs_f32();
+            let new_samples = (elapsed * self.sample_rate.as_frequency().value()) as usize;
+
+            if new_samples > 0 {
+                let samples = self.generator.generate(new_samples);
+
+                if let Err(err) = self.sender.try_send(Samples::Real(samples)) {
+                    match err {
+                        TrySendError::Full(_) => println!("Waterfall processor falling behind"),
+                        TrySendError::Closed(_) => (),
+                    }
+                }
+
+                Some(now)
+            } else {
+                Some(last_time)
+            }
+        } else {
+            Some(Instant::now())
+        };
+    }
+
+    fn get_tx(&self) -> Sender<Samples> {
+        self.sender.clone()
+    }
+}
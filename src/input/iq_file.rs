@@ -0,0 +1,244 @@
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::Sender;
+use wasm_timer::Instant;
+
+use crate::configuration::{AudioSampleRate, Configuration};
+use crate::input::Source;
+use crate::types::{ComplexType, Samples};
+
+/// Sample formats for raw interleaved I/Q files.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum IqFormat {
+    // Little endian f32, as written by GNU Radio.
+    Cf32,
+    // Little endian i16.
+    Cs16,
+    // Offset binary u8, as written by rtl_sdr.
+    Cu8,
+}
+
+impl IqFormat {
+    /// Guess the format from the file extension. Anything unknown is
+    /// assumed to be `Cf32`.
+    pub fn from_path(path: &str) -> Self {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+
+        match extension.as_str() {
+            "cs16" => IqFormat::Cs16,
+            "cu8" => IqFormat::Cu8,
+            _ => IqFormat::Cf32,
+        }
+    }
+
+    /// Bytes per I/Q pair.
+    pub fn frame_len(&self) -> usize {
+        match self {
+            IqFormat::Cf32 => 8,
+            IqFormat::Cs16 => 4,
+            IqFormat::Cu8 => 2,
+        }
+    }
+
+    pub fn parse(&self, bytes: &[u8]) -> Vec<ComplexType> {
+        bytes
+            .chunks_exact(self.frame_len())
+            .map(|frame| {
+                let half = frame.len() / 2;
+                let (i, q) = frame.split_at(half);
+                ComplexType::new(self.parse_value(i), self.parse_value(q))
+            })
+            .collect()
+    }
+
+    fn parse_value(&self, bytes: &[u8]) -> f32 {
+        match self {
+            IqFormat::Cf32 => f32::from_le_bytes(bytes.try_into().unwrap()),
+            IqFormat::Cs16 => i16::from_le_bytes(bytes.try_into().unwrap()) as f32 / 32768.0,
+            IqFormat::Cu8 => (bytes[0] as f32 - 127.5) / 128.0,
+        }
+    }
+}
+
+/// Plays back a raw I/Q recording in real time, looping at the end.
+pub struct IqFile {
+    sender: Sender<Samples>,
+    sample_rate: AudioSampleRate,
+    path: String,
+    format: IqFormat,
+    last_time: Option<Instant>,
+    reader: Option<BufReader<File>>,
+    status: String,
+}
+
+impl IqFile {
+    pub fn new(sender: Sender<Samples>, config: &Configuration) -> Self {
+        let path = config.iq_file.clone();
+        let format = IqFormat::from_path(&path);
+
+        let (reader, status) = match File::open(&path) {
+            Ok(file) => (Some(BufReader::new(file)), format!("Playing {}", path)),
+            Err(err) => (None, format!("Unable to open {}: {}", path, err)),
+        };
+
+        Self {
+            sender,
+            sample_rate: config.audio_sample_rate,
+            path,
+            format,
+            last_time: None,
+            reader,
+            status,
+        }
+    }
+
+    fn read(&mut self, len: usize) -> Vec<ComplexType> {
+        let reader = match &mut self.reader {
+            Some(reader) => reader,
+            None => return Vec::new(),
+        };
+
+        let mut buffer = vec![0; len * self.format.frame_len()];
+        match fill(reader, &mut buffer, self.format.frame_len()) {
+            Ok(filled) => self.format.parse(&buffer[..filled]),
+            Err(err) => {
+                // Playback stops rather than trying again every frame.
+                self.status = format!("Error reading {}: {}", self.path, err);
+                self.reader = None;
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// Reads whole frames into `buffer`, going back to the start of the file at
+/// the end, and returns how many bytes it filled.
+fn fill<R: Read + Seek>(reader: &mut R, buffer: &mut [u8], frame_len: usize) -> io::Result<usize> {
+    let mut filled = 0;
+    // Where the file started over in `buffer`.
+    let mut rewound_at = None;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => {
+                // Part of a frame at the end would be joined to the start of
+                // the file, leaving everything after it misaligned.
+                filled -= filled % frame_len;
+                if rewound_at == Some(filled) {
+                    // Not even one whole frame in the file.
+                    break;
+                }
+                reader.seek(SeekFrom::Start(0))?;
+                rewound_at = Some(filled);
+            }
+            Ok(n) => filled += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(filled)
+}
+
+impl Source for IqFile {
+    fn run(&mut self, config: &Configuration) {
+        if config.iq_file != self.path {
+            *self = IqFile::new(self.sender.clone(), config);
+        }
+        self.sample_rate = config.audio_sample_rate;
+
+        self.last_time = if let Some(last_time) = self.last_time {
+            let now = Instant::now();
+
+            let elapsed = (now - last_time).as_secs_f32();
+            let new_samples = (elapsed * self.sample_rate.as_frequency().value()) as usize;
+
+            if new_samples > 0 {
+                let samples = self.read(new_samples);
+
+                if let Err(err) = self.sender.try_send(Samples::Complex(samples)) {
+                    match err {
+                        TrySendError::Full(_) => println!("Waterfall processor falling behind"),
+                        TrySendError::Closed(_) => (),
+                    }
+                }
+
+                Some(now)
+            } else {
+                Some(last_time)
+            }
+        } else {
+            Some(Instant::now())
+        };
+    }
+
+    fn get_tx(&self) -> Sender<Samples> {
+        self.sender.clone()
+    }
+
+    fn status(&self) -> Option<String> {
+        Some(self.status.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(IqFormat::from_path("capture.cf32"), IqFormat::Cf32);
+        assert_eq!(IqFormat::from_path("/tmp/CAPTURE.CS16"), IqFormat::Cs16);
+        assert_eq!(IqFormat::from_path("rtl.cu8"), IqFormat::Cu8);
+        assert_eq!(IqFormat::from_path("capture"), IqFormat::Cf32);
+    }
+
+    #[test]
+    fn test_parse() {
+        let bytes: Vec<u8> = [0.5_f32, -0.25]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        assert_eq!(
+            IqFormat::Cf32.parse(&bytes),
+            vec![ComplexType::new(0.5, -0.25)]
+        );
+
+        let bytes: Vec<u8> = [16384_i16, -32768]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        assert_eq!(
+            IqFormat::Cs16.parse(&bytes),
+            vec![ComplexType::new(0.5, -1.0)]
+        );
+
+        // A trailing partial frame is dropped.
+        assert_eq!(
+            IqFormat::Cu8.parse(&[255, 0, 127]),
+            vec![ComplexType::new(127.5 / 128.0, -127.5 / 128.0)]
+        );
+    }
+
+    #[test]
+    fn test_fill() {
+        // Two frames and half of another.
+        let mut reader = Cursor::new(vec![1, 2, 3, 4, 5]);
+        let mut buffer = vec![0; 10];
+        assert_eq!(fill(&mut reader, &mut buffer, 2).unwrap(), 10);
+        assert_eq!(buffer, vec![1, 2, 3, 4, 1, 2, 3, 4, 1, 2]);
+
+        // Nothing to loop over.
+        for bytes in [vec![], vec![1]] {
+            let mut reader = Cursor::new(bytes);
+            assert_eq!(fill(&mut reader, &mut buffer, 2).unwrap(), 0);
+        }
+    }
+}
@@ -1,21 +1,36 @@
 mod audio;
+mod channel_mixer;
 mod example;
+mod generator;
+mod iq_file;
+mod rtl_tcp;
 
 use tokio::sync::mpsc::Sender;
 
 use crate::configuration::Configuration;
-use crate::types::SampleType;
+use crate::types::Samples;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum InputSource {
     Audio,
     Example,
+    Generator,
+    IqFile,
+    RtlTcp,
 }
 
 pub trait Source {
     fn run(&mut self, config: &Configuration);
-    fn get_tx(&self) -> Sender<Vec<SampleType>>;
+    fn get_tx(&self) -> Sender<Samples>;
+
+    /// What the source is doing, or why it isn't, for the status bar.
+    fn status(&self) -> Option<String> {
+        None
+    }
 }
 
 pub use audio::Audio;
 pub use example::Example;
+pub use generator::Generator;
+pub use iq_file::{IqFile, IqFormat};
+pub use rtl_tcp::{Command, RtlTcp};
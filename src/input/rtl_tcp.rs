@@ -0,0 +1,354 @@
+use std::f32::consts::PI;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::Sender;
+
+use crate::configuration::{AudioSampleRate, Configuration, RtlTcpSettings};
+use crate::dsp::resampler::Resampler;
+use crate::input::{IqFormat, Source};
+use crate::types::{ComplexType, Samples};
+use crate::units::Frequency;
+
+// The lowest rate the RTL2832U supports.
+const MIN_DEVICE_RATE: f32 = 225_001.0;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Bytes per read, two per sample.
+const READ_LEN: usize = 16384;
+
+/// Commands understood by rtl_tcp. Each goes over the wire as a command byte
+/// followed by a big endian u32.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Command {
+    SetFrequency(u32),
+    SetSampleRate(u32),
+    // true for manual gain, false for the tuner's AGC.
+    SetGainMode(bool),
+    // Tenths of a dB
+    SetGain(u32),
+    // Parts per million
+    SetFrequencyCorrection(i32),
+}
+
+impl Command {
+    pub fn to_bytes(self) -> [u8; 5] {
+        let (command, param) = match self {
+            Command::SetFrequency(hz) => (0x01, hz),
+            Command::SetSampleRate(hz) => (0x02, hz),
+            Command::SetGainMode(manual) => (0x03, manual as u32),
+            Command::SetGain(tenths) => (0x04, tenths),
+            Command::SetFrequencyCorrection(ppm) => (0x05, ppm as u32),
+        };
+
+        let mut bytes = [command, 0, 0, 0, 0];
+        bytes[1..].copy_from_slice(&param.to_be_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: [u8; 5]) -> Option<Self> {
+        let param = u32::from_be_bytes(bytes[1..].try_into().unwrap());
+        match bytes[0] {
+            0x01 => Some(Command::SetFrequency(param)),
+            0x02 => Some(Command::SetSampleRate(param)),
+            0x03 => Some(Command::SetGainMode(param != 0)),
+            0x04 => Some(Command::SetGain(param)),
+            0x05 => Some(Command::SetFrequencyCorrection(param as i32)),
+            _ => None,
+        }
+    }
+}
+
+/// The smallest whole multiple of `sample_rate` the dongle can run at.
+fn device_sample_rate(sample_rate: AudioSampleRate) -> (u32, usize) {
+    let sample_rate = sample_rate.as_frequency().value();
+    let factor = (MIN_DEVICE_RATE / sample_rate).ceil();
+    ((factor * sample_rate) as u32, factor as usize)
+}
+
+/// The dongle is tuned this far above the center frequency, so its DC spike
+/// lands outside the band we keep.
+fn tuning_offset(device_rate: u32) -> f32 {
+    device_rate as f32 / 4.0
+}
+
+/// Shifts the stream down by the tuning offset, then resamples it to the
+/// audio rate. The resampler's filter is sized for the ratio, so nothing
+/// from outside the band folds into it.
+struct Decimator {
+    step: f32,
+    phase: f32,
+    i_resampler: Resampler,
+    q_resampler: Resampler,
+}
+
+impl Decimator {
+    fn new(sample_rate: AudioSampleRate) -> Self {
+        let (device_rate, _) = device_sample_rate(sample_rate);
+        let device_rate = Frequency::Hertz(device_rate as f32);
+
+        Self {
+            step: 2.0 * PI * tuning_offset(device_rate.value() as u32) / device_rate.value(),
+            phase: 0.0,
+            i_resampler: Resampler::new(device_rate, sample_rate.as_frequency()),
+            q_resampler: Resampler::new(device_rate, sample_rate.as_frequency()),
+        }
+    }
+
+    fn process(&mut self, samples: &[ComplexType]) -> Vec<ComplexType> {
+        let (i, q): (Vec<f32>, Vec<f32>) = samples
+            .iter()
+            .map(|sample| {
+                let shifted = sample * ComplexType::from_polar(1.0, self.phase);
+                self.phase = (self.phase + self.step) % (2.0 * PI);
+                (shifted.re, shifted.im)
+            })
+            .unzip();
+
+        // Both see the same samples, so they put out as many.
+        self.i_resampler
+            .process(&i)
+            .into_iter()
+            .zip(self.q_resampler.process(&q))
+            .map(|(i, q)| ComplexType::new(i, q))
+            .collect()
+    }
+}
+
+enum Event {
+    Connected(TcpStream),
+    Error(String),
+}
+
+fn connect(address: &str) -> io::Result<TcpStream> {
+    let address = address
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::from(io::ErrorKind::AddrNotAvailable))?;
+
+    let stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+    stream.set_nodelay(true)?;
+    Ok(stream)
+}
+
+/// Connects, then reads samples until the server or the `RtlTcp` hangs up.
+/// Runs on its own thread, so a slow lookup or server can't hold up the UI.
+fn worker(
+    address: String,
+    sender: Sender<Samples>,
+    mut decimator: Decimator,
+    events: mpsc::Sender<Event>,
+) {
+    let connection = connect(&address).and_then(|stream| Ok((stream.try_clone()?, stream)));
+    let mut reader = match connection {
+        Ok((reader, stream)) => {
+            if let Err(mpsc::SendError(Event::Connected(stream))) =
+                events.send(Event::Connected(stream))
+            {
+                // Nobody is waiting for it any more.
+                let _ = stream.shutdown(Shutdown::Both);
+                return;
+            }
+            reader
+        }
+        Err(err) => {
+            let error = format!("Unable to connect to {}: {}", address, err);
+            let _ = events.send(Event::Error(error));
+            return;
+        }
+    };
+
+    // "RTL0", then the tuner type and number of gain steps.
+    let mut header = [0; 12];
+    if let Err(err) = reader.read_exact(&mut header) {
+        let _ = events.send(Event::Error(err.to_string()));
+        return;
+    }
+    if &header[..4] != b"RTL0" {
+        let error = format!("{} is not an rtl_tcp server", address);
+        let _ = events.send(Event::Error(error));
+        return;
+    }
+
+    let mut buffer = vec![0; READ_LEN];
+    loop {
+        if let Err(err) = reader.read_exact(&mut buffer) {
+            let _ = events.send(Event::Error(err.to_string()));
+            return;
+        }
+        let samples = decimator.process(&IqFormat::Cu8.parse(&buffer));
+
+        if let Err(err) = sender.try_send(Samples::Complex(samples)) {
+            match err {
+                TrySendError::Full(_) => println!("Waterfall processor falling behind"),
+                TrySendError::Closed(_) => return,
+            }
+        }
+    }
+}
+
+/// Client for an rtl_tcp server. The connection is made, and the server's
+/// samples read, on a thread of their own; tuning follows
+/// `Configuration::center_frequency`.
+pub struct RtlTcp {
+    sender: Sender<Samples>,
+    sample_rate: AudioSampleRate,
+    settings: RtlTcpSettings,
+    center_frequency: f64,
+    stream: Option<TcpStream>,
+    events: mpsc::Receiver<Event>,
+    status: String,
+}
+
+impl RtlTcp {
+    pub fn new(sender: Sender<Samples>, config: &Configuration) -> Self {
+        let (event_tx, event_rx) = mpsc::channel();
+        let address = config.rtl_tcp.address.clone();
+        let worker_sender = sender.clone();
+        let decimator = Decimator::new(config.audio_sample_rate);
+        thread::spawn(move || worker(address, worker_sender, decimator, event_tx));
+
+        Self {
+            sender,
+            sample_rate: config.audio_sample_rate,
+            settings: config.rtl_tcp.clone(),
+            center_frequency: config.center_frequency,
+            stream: None,
+            events: event_rx,
+            status: format!("Connecting to {}", config.rtl_tcp.address),
+        }
+    }
+
+    fn send(&mut self, command: Command) {
+        if let Some(stream) = &mut self.stream {
+            if let Err(err) = stream.write_all(&command.to_bytes()) {
+                self.status = err.to_string();
+            }
+        }
+    }
+
+    fn tune(&mut self) {
+        let (device_rate, _) = device_sample_rate(self.sample_rate);
+        let frequency = self.center_frequency + tuning_offset(device_rate) as f64;
+        self.send(Command::SetFrequency(frequency.round() as u32));
+    }
+
+    fn set_gain(&mut self) {
+        self.send(Command::SetFrequencyCorrection(self.settings.ppm));
+        self.send(Command::SetGainMode(!self.settings.agc));
+        if !self.settings.agc {
+            self.send(Command::SetGain((self.settings.gain * 10.0) as u32));
+        }
+    }
+}
+
+impl Drop for RtlTcp {
+    fn drop(&mut self) {
+        // Also stops the reader thread.
+        if let Some(stream) = &self.stream {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+impl Source for RtlTcp {
+    fn run(&mut self, config: &Configuration) {
+        if config.audio_sample_rate != self.sample_rate
+            || config.rtl_tcp.address != self.settings.address
+        {
+            *self = RtlTcp::new(self.sender.clone(), config);
+            return;
+        }
+
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                Event::Connected(stream) => {
+                    self.status = format!("Connected to {}", self.settings.address);
+                    self.stream = Some(stream);
+
+                    let (device_rate, _) = device_sample_rate(self.sample_rate);
+                    self.send(Command::SetSampleRate(device_rate));
+                    self.tune();
+                    self.set_gain();
+                }
+                Event::Error(err) => self.status = err,
+            }
+        }
+
+        if config.center_frequency != self.center_frequency {
+            self.center_frequency = config.center_frequency;
+            self.tune();
+        }
+
+        if config.rtl_tcp != self.settings {
+            self.settings = config.rtl_tcp.clone();
+            self.set_gain();
+        }
+    }
+
+    fn get_tx(&self) -> Sender<Samples> {
+        self.sender.clone()
+    }
+
+    fn status(&self) -> Option<String> {
+        Some(self.status.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_bytes() {
+        let command = Command::SetFrequency(14_080_000);
+        assert_eq!(command.to_bytes(), [0x01, 0x00, 0xd6, 0xd8, 0x00]);
+        assert_eq!(Command::from_bytes(command.to_bytes()), Some(command));
+
+        let command = Command::SetFrequencyCorrection(-3);
+        assert_eq!(Command::from_bytes(command.to_bytes()), Some(command));
+    }
+
+    #[test]
+    fn test_decimator() {
+        let sample_rate = AudioSampleRate::F8000;
+        let (device_rate, _) = device_sample_rate(sample_rate);
+        let offset = tuning_offset(device_rate);
+        let level = |hz: f32| {
+            let input: Vec<ComplexType> = (0..device_rate as usize)
+                .map(|i| {
+                    let cycles = (hz as f64 * i as f64 / device_rate as f64).fract();
+                    ComplexType::from_polar(1.0, 2.0 * PI * cycles as f32)
+                })
+                .collect();
+            let output = Decimator::new(sample_rate).process(&input);
+            assert_eq!(output.len(), 8000);
+            let settled = &output[output.len() / 2..];
+            (settled.iter().map(|x| x.norm_sqr()).sum::<f32>() / settled.len() as f32).sqrt()
+        };
+
+        // The center frequency is the tuning offset below the dongle's.
+        assert!((level(1000.0 - offset) - 1.0).abs() < 0.01);
+        assert!((level(-1000.0 - offset) - 1.0).abs() < 0.01);
+        // Would fold onto -2 kHz.
+        assert!(level(6000.0 - offset) < 1e-3);
+        assert!(level(30000.0 - offset) < 1e-3);
+    }
+
+    #[test]
+    fn test_device_sample_rate() {
+        for sample_rate in [AudioSampleRate::F8000, AudioSampleRate::F96000] {
+            let (device_rate, factor) = device_sample_rate(sample_rate);
+            assert!((225_001..=300_000).contains(&device_rate));
+            assert_eq!(
+                device_rate as f32 / factor as f32,
+                sample_rate.as_frequency().value()
+            );
+        }
+    }
+}
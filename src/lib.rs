@@ -3,9 +3,18 @@ pub mod configuration;
 pub mod dsp;
 pub mod input;
 pub mod message;
+pub mod message_filter;
+pub mod message_log;
+pub mod message_parse;
 pub mod messages;
+pub mod monitor;
+pub mod output;
+pub mod rig;
 pub mod scope;
+pub mod spots;
 pub mod statistics;
+pub mod transmitter;
 pub mod types;
 pub mod ui;
 pub mod units;
+pub mod waterfall_labels;
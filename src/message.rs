@@ -3,10 +3,16 @@ use tokio::sync::mpsc;
 pub trait Message {
     fn payload(&self) -> String;
     fn mode(&self) -> String;
+    /// RF frequency of the tuner, in Hz, when the message was received.
+    fn frequency(&self) -> Option<f64>;
+    /// Signal to noise ratio in dB, in 2500 Hz, if the decoder measured it.
+    fn snr(&self) -> Option<f32>;
 }
 
 pub struct RttyMessage {
     message: String,
+    frequency: Option<f64>,
+    snr: Option<f32>,
 }
 
 impl Message for RttyMessage {
@@ -17,13 +23,44 @@ impl Message for RttyMessage {
     fn mode(&self) -> String {
         "RTTY".to_string()
     }
+
+    fn frequency(&self) -> Option<f64> {
+        self.frequency
+    }
+
+    fn snr(&self) -> Option<f32> {
+        self.snr
+    }
 }
 
 impl RttyMessage {
     pub fn new(message: String) -> Self {
-        Self { message }
+        Self {
+            message,
+            frequency: None,
+            snr: None,
+        }
+    }
+
+    pub fn with_frequency(mut self, frequency: f64) -> Self {
+        self.frequency = Some(frequency);
+        self
+    }
+
+    pub fn with_snr(mut self, snr: f32) -> Self {
+        self.snr = Some(snr);
+        self
     }
 }
 
-pub type MessageSender = mpsc::Sender<Box<dyn Message>>;
-pub type MessageReceiver = mpsc::Receiver<Box<dyn Message>>;
+/// What decoders send: text as soon as it's decoded, for following along,
+/// and each message once it's complete. The receiver works a slice at a
+/// time, so text lags the air by up to `rx::SLICE_DURATION`.
+pub enum DecoderEvent {
+    // The characters decoded since the last event.
+    Text(String),
+    Message(Box<dyn Message>),
+}
+
+pub type MessageSender = mpsc::Sender<DecoderEvent>;
+pub type MessageReceiver = mpsc::Receiver<DecoderEvent>;
@@ -0,0 +1,268 @@
+use regex::{Regex, RegexBuilder};
+
+use crate::configuration::MessageSettings;
+use crate::message_log::LogEntry;
+
+/// The message window's search, filters and highlight rules, ready to run
+/// against messages.
+pub struct MessageFilter {
+    search: Option<Regex>,
+    mode: Option<String>,
+    channel: Option<f64>,
+    frequency_range: Option<(f64, f64)>,
+    highlights: Vec<(Regex, [u8; 3])>,
+}
+
+impl MessageFilter {
+    /// Fails if the search or a highlight isn't a valid regular expression.
+    /// Our `callsign`, if we have one, is highlighted first.
+    pub fn new(settings: &MessageSettings, callsign: &str) -> Result<Self, regex::Error> {
+        let search = match settings.search.as_str() {
+            "" => None,
+            search => Some(pattern(search)?),
+        };
+
+        let mut highlights = Vec::new();
+        let callsign = callsign.trim();
+        if !callsign.is_empty() {
+            let callsign = format!(r"\b{}\b", regex::escape(callsign));
+            highlights.push((pattern(&callsign)?, settings.callsign_color));
+        }
+        for highlight in &settings.highlights {
+            if !highlight.pattern.is_empty() {
+                highlights.push((pattern(&highlight.pattern)?, highlight.color));
+            }
+        }
+
+        Ok(Self {
+            search,
+            mode: settings.mode.clone(),
+            channel: settings.channel,
+            frequency_range: settings.frequency_range,
+            highlights,
+        })
+    }
+
+    /// Whether the message should be shown. Messages without a frequency
+    /// never pass a channel or frequency filter.
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        if matches!(&self.mode, Some(mode) if *mode != entry.mode) {
+            return false;
+        }
+
+        let hz = entry.frequency;
+        if let Some(channel) = self.channel {
+            if hz.map(f64::round) != Some(channel) {
+                return false;
+            }
+        }
+        if let Some((lower, upper)) = self.frequency_range {
+            if !matches!(hz, Some(hz) if hz >= lower && hz <= upper) {
+                return false;
+            }
+        }
+
+        match &self.search {
+            Some(search) => search.is_match(&entry.payload),
+            None => true,
+        }
+    }
+
+    /// The color of the first highlight rule the message matches.
+    pub fn highlight(&self, entry: &LogEntry) -> Option<[u8; 3]> {
+        self.highlights
+            .iter()
+            .find(|(pattern, _)| pattern.is_match(&entry.payload))
+            .map(|(_, color)| *color)
+    }
+}
+
+/// Keeps the `MessageFilter` the settings and callsign were last built into,
+/// so that its regular expressions aren't compiled again every frame.
+#[derive(Default)]
+pub struct FilterCache {
+    built: Option<(MessageSettings, String, Result<MessageFilter, regex::Error>)>,
+}
+
+impl FilterCache {
+    /// The filter for `settings` and `callsign`, built only if either has
+    /// changed since the last call.
+    pub fn get(
+        &mut self,
+        settings: &MessageSettings,
+        callsign: &str,
+    ) -> &Result<MessageFilter, regex::Error> {
+        let current = matches!(
+            &self.built,
+            Some((built_settings, built_callsign, _))
+                if built_settings == settings && built_callsign == callsign
+        );
+        if !current {
+            let filter = MessageFilter::new(settings, callsign);
+            self.built = Some((settings.clone(), callsign.to_string(), filter));
+        }
+        &self.built.as_ref().unwrap().2
+    }
+}
+
+/// Every frequency messages were received on, to the nearest Hz and lowest
+/// first.
+pub fn channels<'a>(entries: impl IntoIterator<Item = &'a LogEntry>) -> Vec<f64> {
+    let mut channels: Vec<f64> = entries
+        .into_iter()
+        .filter_map(|entry| entry.frequency)
+        .map(f64::round)
+        .collect();
+    channels.sort_by(|a, b| a.total_cmp(b));
+    channels.dedup();
+    channels
+}
+
+/// Every mode messages were received in.
+pub fn modes<'a>(entries: impl IntoIterator<Item = &'a LogEntry>) -> Vec<String> {
+    let mut modes: Vec<String> = entries
+        .into_iter()
+        .map(|entry| entry.mode.clone())
+        .collect();
+    modes.sort();
+    modes.dedup();
+    modes
+}
+
+// Decoders tend to put out capitals, but people type in whatever.
+fn pattern(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern).case_insensitive(true).build()
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_timer::UNIX_EPOCH;
+
+    use super::*;
+    use crate::configuration::Highlight;
+
+    fn entry(mode: &str, hz: Option<f64>, payload: &str) -> LogEntry {
+        LogEntry {
+            time: UNIX_EPOCH,
+            mode: mode.to_string(),
+            frequency: hz,
+            snr: None,
+            payload: payload.to_string(),
+        }
+    }
+
+    fn shown(settings: &MessageSettings, entries: &[LogEntry]) -> Vec<String> {
+        let filter = MessageFilter::new(settings, "").unwrap();
+        entries
+            .iter()
+            .filter(|entry| filter.matches(entry))
+            .map(|entry| entry.payload.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_filters() {
+        let entries = [
+            entry("RTTY", Some(14085000.4), "CQ CQ DE W1AW"),
+            entry("RTTY", Some(7040000.0), "W1AW DE K1ABC"),
+            entry("PSK31", Some(14070000.0), "CQ DE DL1XYZ"),
+            entry("RTTY", None, "RYRYRY"),
+        ];
+
+        let settings = MessageSettings::default();
+        assert_eq!(shown(&settings, &entries).len(), 4);
+
+        let search = MessageSettings {
+            search: r"de \w+1abc".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(shown(&search, &entries), vec!["W1AW DE K1ABC"]);
+
+        let mode = MessageSettings {
+            mode: Some("PSK31".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(shown(&mode, &entries), vec!["CQ DE DL1XYZ"]);
+
+        let channel = MessageSettings {
+            channel: Some(14085000.0),
+            ..Default::default()
+        };
+        assert_eq!(shown(&channel, &entries), vec!["CQ CQ DE W1AW"]);
+
+        let range = MessageSettings {
+            frequency_range: Some((14e6, 14.35e6)),
+            search: "CQ".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            shown(&range, &entries),
+            vec!["CQ CQ DE W1AW", "CQ DE DL1XYZ"]
+        );
+
+        assert_eq!(channels(&entries), vec![7040000.0, 14070000.0, 14085000.0]);
+        assert_eq!(modes(&entries), vec!["PSK31", "RTTY"]);
+    }
+
+    #[test]
+    fn test_highlight() {
+        let settings = MessageSettings {
+            callsign_color: [1, 1, 1],
+            highlights: vec![
+                Highlight {
+                    pattern: r"\bCQ\b".to_string(),
+                    color: [2, 2, 2],
+                },
+                Highlight {
+                    pattern: String::new(),
+                    color: [3, 3, 3],
+                },
+            ],
+            ..Default::default()
+        };
+        let filter = MessageFilter::new(&settings, "k1abc").unwrap();
+        let highlight = |payload| filter.highlight(&entry("RTTY", None, payload));
+
+        assert_eq!(highlight("CQ K1ABC"), Some([1, 1, 1]));
+        assert_eq!(highlight("CQ CQ DE W1AW"), Some([2, 2, 2]));
+        assert_eq!(highlight("CQD DE W1AW K1ABCD"), None);
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let settings = MessageSettings {
+            search: "(CQ".to_string(),
+            ..Default::default()
+        };
+        assert!(MessageFilter::new(&settings, "").is_err());
+    }
+
+    #[test]
+    fn test_cache() {
+        let mut cache = FilterCache::default();
+        let mut settings = MessageSettings::default();
+        let cq = entry("RTTY", None, "CQ DE K1ABC");
+        let highlight = |cache: &mut FilterCache, settings: &MessageSettings, callsign| {
+            cache
+                .get(settings, callsign)
+                .as_ref()
+                .unwrap()
+                .highlight(&cq)
+        };
+
+        let callsign_color = settings.callsign_color;
+        assert_eq!(
+            highlight(&mut cache, &settings, ""),
+            Some([0x60, 0xff, 0x60])
+        );
+        assert_eq!(
+            highlight(&mut cache, &settings, "k1abc"),
+            Some(callsign_color)
+        );
+
+        settings.search = "(CQ".to_string();
+        assert!(cache.get(&settings, "k1abc").is_err());
+        settings.search.clear();
+        assert!(cache.get(&settings, "k1abc").is_ok());
+    }
+}
@@ -0,0 +1,475 @@
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::time::Duration;
+
+use wasm_timer::{SystemTime, UNIX_EPOCH};
+
+use crate::message::Message;
+use crate::message_parse::parse;
+
+/// A decoded message as it is kept and logged, with when it arrived.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogEntry {
+    pub time: SystemTime,
+    pub mode: String,
+    // RF, in Hz.
+    pub frequency: Option<f64>,
+    pub snr: Option<f32>,
+    pub payload: String,
+}
+
+impl Message for LogEntry {
+    fn payload(&self) -> String {
+        self.payload.clone()
+    }
+
+    fn mode(&self) -> String {
+        self.mode.clone()
+    }
+
+    fn frequency(&self) -> Option<f64> {
+        self.frequency
+    }
+
+    fn snr(&self) -> Option<f32> {
+        self.snr
+    }
+}
+
+impl LogEntry {
+    pub fn new(message: &dyn Message, time: SystemTime) -> Self {
+        Self {
+            time,
+            mode: message.mode(),
+            frequency: message.frequency(),
+            snr: message.snr(),
+            payload: message.payload(),
+        }
+    }
+
+    fn seconds(&self) -> f64 {
+        self.time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64()
+    }
+
+    /// One line of JSON, with the time in seconds since the Unix epoch, the
+    /// frequency in Hz and the SNR in dB.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"time\":{:.3},\"mode\":{},\"frequency\":{},\"snr\":{},\"payload\":{}}}",
+            self.seconds(),
+            json_string(&self.mode),
+            json_number(self.frequency),
+            json_number(self.snr.map(|snr| (snr * 10.).round() / 10.)),
+            json_string(&self.payload)
+        )
+    }
+
+    /// Reads back what `to_json` wrote. Logs from before the SNR was kept
+    /// have none.
+    pub fn from_json(line: &str) -> Option<Self> {
+        let mut time = None;
+        let mut mode = None;
+        let mut frequency = None;
+        let mut snr = None;
+        let mut payload = None;
+
+        let mut parser = Parser::new(line);
+        parser.expect('{')?;
+        loop {
+            let key = parser.string()?;
+            parser.expect(':')?;
+            match key.as_str() {
+                "time" => time = Some(parser.number()?),
+                "mode" => mode = Some(parser.string()?),
+                "frequency" => frequency = parser.nullable_number()?,
+                "snr" => snr = parser.nullable_number()?,
+                "payload" => payload = Some(parser.string()?),
+                _ => return None,
+            }
+            if parser.expect(',').is_none() {
+                break;
+            }
+        }
+        parser.expect('}')?;
+
+        // A corrupt time, negative or too far off to represent, makes the
+        // line unreadable like any other corruption.
+        let time = UNIX_EPOCH.checked_add(Duration::try_from_secs_f64(time?).ok()?)?;
+        Some(Self {
+            time,
+            mode: mode?,
+            frequency,
+            snr: snr.map(|snr| snr as f32),
+            payload: payload?,
+        })
+    }
+}
+
+/// Appends every message to a file of JSON lines.
+pub struct MessageLog {
+    path: String,
+    file: File,
+}
+
+impl MessageLog {
+    pub fn open(path: &str) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            path: path.to_string(),
+            file,
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn append(&mut self, entry: &LogEntry) -> io::Result<()> {
+        writeln!(self.file, "{}", entry.to_json())
+    }
+
+    /// Everything in the log at `path`. Lines that can't be read, such as one
+    /// cut short by a crash, are skipped.
+    pub fn read(path: &str) -> io::Result<Vec<LogEntry>> {
+        let mut entries = Vec::new();
+        for line in BufReader::new(File::open(path)?).lines() {
+            if let Some(entry) = LogEntry::from_json(&line?) {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Spreadsheet-friendly, one message to a row.
+pub fn write_csv(entries: &[LogEntry], mut writer: impl Write) -> io::Result<()> {
+    writeln!(writer, "time,mode,frequency_hz,snr_db,payload")?;
+    for entry in entries {
+        let frequency = match entry.frequency {
+            Some(frequency) => format!("{}", frequency),
+            None => String::new(),
+        };
+        let snr = match entry.snr {
+            Some(snr) => format!("{:.1}", snr),
+            None => String::new(),
+        };
+        writeln!(
+            writer,
+            "{},{},{},{},{}",
+            format_time(entry.time),
+            csv_field(&entry.mode),
+            frequency,
+            snr,
+            csv_field(&entry.payload)
+        )?;
+    }
+    Ok(())
+}
+
+/// Amateur Data Interchange Format, for logging software. Each record is
+/// for the message's sender, with their grid and report where the message
+/// gives them, and the message text goes in the comment. Messages that
+/// don't say who sent them can't be logged, and are left out.
+pub fn write_adif(entries: &[LogEntry], mut writer: impl Write) -> io::Result<()> {
+    writeln!(writer, "Decoded messages")?;
+    writeln!(
+        writer,
+        "{}{}<EOH>",
+        adif_field("ADIF_VER", "3.1.4"),
+        adif_field("PROGRAMID", env!("CARGO_PKG_NAME"))
+    )?;
+    for entry in entries {
+        let parsed = parse(&entry.payload);
+        let callsign = match parsed.sender {
+            Some(callsign) => callsign,
+            None => continue,
+        };
+        let (year, month, day, hour, minute, second) = utc(entry.time);
+        let mut record = adif_field("CALL", &callsign);
+        record += &adif_field("QSO_DATE", &format!("{:04}{:02}{:02}", year, month, day));
+        record += &adif_field("TIME_ON", &format!("{:02}{:02}{:02}", hour, minute, second));
+        record += &adif_field("MODE", &entry.mode);
+        if let Some(frequency) = entry.frequency {
+            // In MHz.
+            record += &adif_field("FREQ", &format!("{:.6}", frequency / 1e6));
+        }
+        if let Some(grid) = parsed.grids.first() {
+            record += &adif_field("GRIDSQUARE", grid);
+        }
+        if let Some(report) = parsed.reports.first() {
+            record += &adif_field("RST_RCVD", report);
+        }
+        record += &adif_field("COMMENT", &entry.payload);
+        writeln!(writer, "{}<EOR>", record)?;
+    }
+    Ok(())
+}
+
+/// ISO 8601, in UTC.
+pub fn format_time(time: SystemTime) -> String {
+    let (year, month, day, hour, minute, second) = utc(time);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, hour, minute, second
+    )
+}
+
+/// Year, month, day, hour, minute and second, from Howard Hinnant's
+/// `civil_from_days`.
+pub(crate) fn utc(time: SystemTime) -> (i64, u32, u32, u32, u32, u32) {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    let (days, seconds) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (
+        year,
+        month,
+        day,
+        (seconds / 3600) as u32,
+        (seconds / 60 % 60) as u32,
+        (seconds % 60) as u32,
+    )
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+// JSON has no NaN or infinity, so they're written as unknown.
+fn json_number<T: fmt::Display + Into<f64> + Copy>(n: Option<T>) -> String {
+    match n {
+        Some(n) if n.into().is_finite() => format!("{}", n),
+        _ => "null".to_string(),
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+// ADIF is ASCII, and its lengths count bytes.
+fn adif_field(name: &str, value: &str) -> String {
+    let value: String = value
+        .chars()
+        .map(|c| {
+            if c.is_ascii() && !c.is_ascii_control() {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect();
+    format!("<{}:{}>{} ", name, value.len(), value)
+}
+
+/// Just enough JSON for reading back log lines: a flat object of strings and
+/// numbers.
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str) -> Self {
+        Self {
+            chars: s.chars().peekable(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.chars.peek(), Some(c) if c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Option<()> {
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&expected) {
+            self.chars.next();
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.chars.next()? {
+                '"' => return Some(s),
+                '\\' => s.push(match self.chars.next()? {
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    'u' => {
+                        let hex: String = (0..4).filter_map(|_| self.chars.next()).collect();
+                        char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?
+                    }
+                    c => c,
+                }),
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Option<f64> {
+        self.skip_whitespace();
+        let mut s = String::new();
+        while let Some(c) = self.chars.peek() {
+            if c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E') {
+                s.push(*c);
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+        s.parse().ok()
+    }
+
+    // The outer option is whether it parsed.
+    fn nullable_number(&mut self) -> Option<Option<f64>> {
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&'n') {
+            for expected in "null".chars() {
+                if self.chars.next()? != expected {
+                    return None;
+                }
+            }
+            Some(None)
+        } else {
+            self.number().map(Some)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(seconds: u64, frequency: Option<f64>, payload: &str) -> LogEntry {
+        LogEntry {
+            time: UNIX_EPOCH + Duration::from_secs(seconds),
+            mode: "RTTY".to_string(),
+            frequency,
+            snr: frequency.map(|_| 12.5),
+            payload: payload.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        for entry in [
+            entry(1666180496, Some(14085000.0), "CQ CQ DE W1AW \"K\" \\ \u{7}"),
+            entry(1666180500, Some(144_090_001.0), "CQ DE W1AW"),
+            entry(0, None, ""),
+        ] {
+            let json = entry.to_json();
+            assert!(!json.contains('\n'));
+            assert_eq!(LogEntry::from_json(&json), Some(entry));
+        }
+        assert_eq!(LogEntry::from_json("{\"time\":1,\"mode\":\"RT"), None);
+
+        let old = LogEntry::from_json(
+            "{\"time\":0.000,\"mode\":\"RTTY\",\"frequency\":null,\"payload\":\"\"}",
+        );
+        assert_eq!(old, Some(entry(0, None, "")));
+
+        for time in ["-1", "1e300", "1e"] {
+            let corrupt = format!(
+                "{{\"time\":{},\"mode\":\"RTTY\",\"frequency\":null,\"payload\":\"\"}}",
+                time
+            );
+            assert_eq!(LogEntry::from_json(&corrupt), None, "{}", corrupt);
+        }
+
+        let unknown = LogEntry {
+            snr: Some(f32::NAN),
+            frequency: Some(f64::INFINITY),
+            ..entry(0, None, "")
+        };
+        let json = unknown.to_json();
+        assert!(json.contains("\"frequency\":null,\"snr\":null"), "{}", json);
+        assert_eq!(LogEntry::from_json(&json), Some(entry(0, None, "")));
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        // A leap day.
+        assert_eq!(
+            format_time(UNIX_EPOCH + Duration::from_secs(951827696)),
+            "2000-02-29T12:34:56Z"
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        let mut csv = Vec::new();
+        write_csv(&[entry(0, Some(7040000.0), "599, \"TU\"")], &mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "time,mode,frequency_hz,snr_db,payload\n\
+             1970-01-01T00:00:00Z,RTTY,7040000,12.5,\"599, \"\"TU\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_adif() {
+        let mut adif = Vec::new();
+        write_adif(
+            &[
+                entry(951827696, Some(14085000.0), "CQ DE W1AW"),
+                entry(951827700, None, "RYRYRYRY 599"),
+                entry(951827710, None, "K1ABC DE W1AW 5NN FN31 K"),
+            ],
+            &mut adif,
+        )
+        .unwrap();
+        let adif = String::from_utf8(adif).unwrap();
+        let (header, records) = adif.split_once("<EOH>").unwrap();
+        assert!(header.contains("<ADIF_VER:5>3.1.4"));
+        let records: Vec<&str> = records.trim().lines().collect();
+        assert_eq!(
+            records,
+            vec![
+                "<CALL:4>W1AW <QSO_DATE:8>20000229 <TIME_ON:6>123456 <MODE:4>RTTY \
+                 <FREQ:9>14.085000 <COMMENT:10>CQ DE W1AW <EOR>",
+                "<CALL:4>W1AW <QSO_DATE:8>20000229 <TIME_ON:6>123510 <MODE:4>RTTY \
+                 <GRIDSQUARE:4>FN31 <RST_RCVD:3>599 <COMMENT:24>K1ABC DE W1AW 5NN FN31 K <EOR>",
+            ]
+        );
+    }
+}
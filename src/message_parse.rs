@@ -0,0 +1,221 @@
+/// What a decoded message says about who was on the air.
+#[derive(Debug, Default, PartialEq)]
+pub struct Parsed {
+    // In the order they appear.
+    pub callsigns: Vec<String>,
+    pub grids: Vec<String>,
+    pub reports: Vec<String>,
+    // Whose transmission it was, going by the usual ways of writing CQs
+    // and exchanges.
+    pub sender: Option<String>,
+}
+
+/// Picks callsigns, grid locators and signal reports out of decoded text.
+/// Works for the free text of RTTY and PSK as well as for the structured
+/// messages of FT8 and the like.
+pub fn parse(text: &str) -> Parsed {
+    let text = text.to_uppercase();
+    let tokens: Vec<&str> = text
+        .split(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '/' | '+' | '-')))
+        .filter(|token| !token.is_empty())
+        .collect();
+
+    let mut parsed = Parsed::default();
+    // The callsign after DE, or the first after CQ.
+    let mut announced = None;
+    for (i, token) in tokens.iter().enumerate() {
+        if is_grid(token) {
+            parsed.grids.push(token.to_string());
+        } else if is_callsign(token) {
+            let previous = i.checked_sub(1).map(|i| tokens[i]);
+            let after_cq = tokens[..i]
+                .iter()
+                .rev()
+                .find(|t| **t == "CQ" || !is_cq_modifier(t))
+                == Some(&"CQ");
+            if announced.is_none() && (previous == Some("DE") || after_cq) {
+                announced = Some(token.to_string());
+            }
+            if !parsed.callsigns.iter().any(|c| c == token) {
+                parsed.callsigns.push(token.to_string());
+            }
+        } else if let Some(report) = report(token) {
+            parsed.reports.push(report);
+        }
+    }
+
+    // Otherwise only a structured exchange says who sent it. A callsign
+    // mentioned in passing could be anyone's.
+    parsed.sender = announced.or_else(|| exchange_sender(&tokens));
+    parsed
+}
+
+// As in FT8's "K1ABC W1AW -10": the callsign called, then the sender's, then
+// at most one of a grid, a report, RRR, RR73 or 73.
+fn exchange_sender(tokens: &[&str]) -> Option<String> {
+    let (called, sender, rest) = match tokens {
+        [called, sender, rest @ ..] => (called, sender, rest),
+        _ => return None,
+    };
+    let exchange = match rest {
+        [] => true,
+        [token] => {
+            is_grid(token) || report(token).is_some() || matches!(*token, "RRR" | "RR73" | "73")
+        }
+        _ => false,
+    };
+    (exchange && is_callsign(called) && is_callsign(sender)).then(|| sender.to_string())
+}
+
+// What can come between CQ and the callsign, as in CQ DX or CQ NA.
+fn is_cq_modifier(token: &str) -> bool {
+    token == "DX"
+        || token == "TEST"
+        || token == "POTA"
+        || (token.len() == 2 && token.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+/// Callsigns like W1AW, 9A1AA or 2E0ABC, with any portable prefix or
+/// suffix, as in DL/W1AW/P.
+pub fn is_callsign(token: &str) -> bool {
+    let parts: Vec<&str> = token.split('/').collect();
+    if parts.len() > 3 || parts.iter().any(|part| part.is_empty()) {
+        return false;
+    }
+    // The callsign proper is the longest part.
+    let base = parts.iter().max_by_key(|part| part.len()).unwrap();
+    is_base_callsign(base) && !is_grid(base)
+}
+
+// A prefix of one to three characters with a letter in its first two, a
+// digit, then a suffix of one to four letters.
+fn is_base_callsign(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    if !(3..=7).contains(&bytes.len())
+        || !bytes
+            .iter()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+    {
+        return false;
+    }
+    let digit = match bytes.iter().rposition(u8::is_ascii_digit) {
+        Some(digit) => digit,
+        None => return false,
+    };
+    let (prefix, suffix) = (&bytes[..digit], &bytes[digit + 1..]);
+    (1..=3).contains(&prefix.len())
+        && prefix.iter().take(2).any(u8::is_ascii_uppercase)
+        && (1..=4).contains(&suffix.len())
+}
+
+/// Maidenhead locators, to four or six characters, as in FN31 or FN31PR.
+pub fn is_grid(token: &str) -> bool {
+    let bytes = token.as_bytes();
+    // FT8's "roger, 73".
+    if token == "RR73" || !(bytes.len() == 4 || bytes.len() == 6) {
+        return false;
+    }
+    let field = |b: &u8| (b'A'..=b'R').contains(b);
+    let subsquare = |b: &u8| (b'A'..=b'X').contains(&b.to_ascii_uppercase());
+    bytes[..2].iter().all(field)
+        && bytes[2..4].iter().all(u8::is_ascii_digit)
+        && bytes[4..].iter().all(subsquare)
+}
+
+/// An RST report, such as 599 or the 5NN it's often sent as, or a report in
+/// dB, such as -10 or R+05.
+pub fn report(token: &str) -> Option<String> {
+    let bytes = token.as_bytes();
+    if bytes.len() == 3 && (b'1'..=b'5').contains(&bytes[0]) {
+        // Cut numbers: N for 9 and T for 0.
+        let rst: String = token
+            .chars()
+            .map(|c| match c {
+                'N' => '9',
+                'T' => '0',
+                c => c,
+            })
+            .collect();
+        if rst[1..].chars().all(|c| ('1'..='9').contains(&c)) {
+            return Some(rst);
+        }
+    }
+
+    let db = token.strip_prefix('R').unwrap_or(token);
+    let bytes = db.as_bytes();
+    if bytes.len() == 3
+        && matches!(bytes[0], b'+' | b'-')
+        && bytes[1..].iter().all(u8::is_ascii_digit)
+    {
+        return Some(db.to_string());
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_callsigns() {
+        for callsign in [
+            "W1AW",
+            "K1ABC",
+            "9A1AA",
+            "2E0ABC",
+            "3DA0XX",
+            "DL/W1AW/P",
+            "VE3ABC/QRP",
+        ] {
+            assert!(is_callsign(callsign), "{}", callsign);
+        }
+        for word in [
+            "CQ", "599", "5NN", "FN31", "FN31PR", "RR73", "73", "W1", "TEST", "1AW",
+        ] {
+            assert!(!is_callsign(word), "{}", word);
+        }
+    }
+
+    #[test]
+    fn test_reports() {
+        assert_eq!(report("599"), Some("599".to_string()));
+        assert_eq!(report("5NN"), Some("599".to_string()));
+        assert_eq!(report("-10"), Some("-10".to_string()));
+        assert_eq!(report("R+05"), Some("+05".to_string()));
+        assert_eq!(report("600"), None);
+        assert_eq!(report("RST"), None);
+    }
+
+    #[test]
+    fn test_parse() {
+        let parsed = parse("cq cq de w1aw w1aw fn31pr k");
+        assert_eq!(parsed.callsigns, vec!["W1AW"]);
+        assert_eq!(parsed.grids, vec!["FN31PR"]);
+        assert_eq!(parsed.sender.as_deref(), Some("W1AW"));
+
+        let parsed = parse("K1ABC DE W1AW UR 5NN 5NN BK");
+        assert_eq!(parsed.callsigns, vec!["K1ABC", "W1AW"]);
+        assert_eq!(parsed.reports, vec!["599", "599"]);
+        assert_eq!(parsed.sender.as_deref(), Some("W1AW"));
+
+        assert_eq!(parse("CQ DX DL1XYZ JO62").sender.as_deref(), Some("DL1XYZ"));
+        assert_eq!(parse("K1ABC W1AW R-10").sender.as_deref(), Some("W1AW"));
+        assert_eq!(parse("K1ABC W1AW RR73").sender.as_deref(), Some("W1AW"));
+        assert_eq!(parse("THE QUICK BROWN FOX"), Parsed::default());
+    }
+
+    #[test]
+    fn test_no_sender() {
+        for text in [
+            "TNX QSO K1ABC 73",
+            "K1ABC",
+            "K1ABC W1AW UR 599 TU",
+            "QRZ? K1ABC W1AW",
+            "73 DE",
+        ] {
+            assert_eq!(parse(text).sender, None, "{}", text);
+        }
+        // The callsign is still picked out.
+        assert_eq!(parse("TNX QSO K1ABC 73").callsigns, vec!["K1ABC"]);
+    }
+}
@@ -1,22 +1,198 @@
-use crate::configuration::Configuration;
-use crate::message::{Message, MessageReceiver};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use wasm_timer::SystemTime;
+
+use crate::configuration::{Configuration, LogSettings};
+use crate::message::{DecoderEvent, MessageReceiver};
+use crate::message_filter::FilterCache;
+use crate::message_log::{write_adif, write_csv, LogEntry, MessageLog};
+
+// How many messages are kept in memory. The log on disk has the rest.
+const MAX_MESSAGES: usize = 1000;
+// How much of the streamed text is kept, in bytes.
+const MAX_TEXT: usize = 64 * 1024;
+const MAX_LINE: usize = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Adif,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Adif => "adi",
+        }
+    }
+}
 
 pub struct MessageCollector {
     rx: MessageReceiver,
-    pub data: Vec<Box<dyn Message>>,
+    pub data: VecDeque<LogEntry>,
+    // Everything decoded, as it came in.
+    pub text: String,
+    received: u64,
+    log: Option<MessageLog>,
+    // The last session's messages are brought back once, at the start.
+    restored: bool,
+    pub export: Option<ExportFormat>,
+    pub status: Option<String>,
+    // What the message window shows of `data`.
+    pub filter: FilterCache,
 }
 
 impl MessageCollector {
     pub fn new(rx: MessageReceiver) -> Self {
         Self {
             rx,
-            data: Vec::new(),
+            data: VecDeque::new(),
+            text: String::new(),
+            received: 0,
+            log: None,
+            restored: false,
+            export: None,
+            status: None,
+            filter: FilterCache::default(),
+        }
+    }
+
+    pub fn run(&mut self, config: &mut Configuration) {
+        // There is no file system in the browser.
+        #[cfg(not(target_arch = "wasm32"))]
+        self.update_log(&mut config.log);
+
+        while let Ok(event) = self.rx.try_recv() {
+            match event {
+                DecoderEvent::Text(text) => self.push_text(&text),
+                DecoderEvent::Message(message) => {
+                    let entry = LogEntry::new(message.as_ref(), SystemTime::now());
+                    if let Some(log) = &mut self.log {
+                        if let Err(err) = log.append(&entry) {
+                            self.status = Some(format!("{}: {}", log.path(), err));
+                        }
+                    }
+                    self.push(entry);
+                    self.received += 1;
+                }
+            }
+        }
+
+        if let Some(format) = self.export.take() {
+            self.status = Some(match self.export(format, &config.log) {
+                Ok(path) => format!("Wrote {}", path),
+                Err(err) => err.to_string(),
+            });
+        }
+    }
+
+    /// How many messages have come from the decoders. Those brought back
+    /// from the log don't count.
+    pub fn received(&self) -> u64 {
+        self.received
+    }
+
+    fn push(&mut self, entry: LogEntry) {
+        self.data.push_back(entry);
+        while self.data.len() > MAX_MESSAGES {
+            self.data.pop_front();
+        }
+    }
+
+    // Drops the oldest text, up to the end of a line if there's one close by.
+    fn push_text(&mut self, text: &str) {
+        self.text.push_str(text);
+        if self.text.len() <= MAX_TEXT {
+            return;
+        }
+        let mut cut = self.text.len() - MAX_TEXT;
+        while !self.text.is_char_boundary(cut) {
+            cut += 1;
         }
+        if let Some(i) = self.text[cut..].find('\n').filter(|i| *i < MAX_LINE) {
+            cut += i + 1;
+        }
+        self.text.drain(..cut);
     }
 
-    pub fn run(&mut self, _config: &mut Configuration) {
-        while let Ok(data) = self.rx.try_recv() {
-            self.data.push(data);
+    // Opens, or closes, the log to match the settings.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn update_log(&mut self, settings: &mut LogSettings) {
+        if !settings.enabled {
+            self.log = None;
+            return;
+        }
+        if matches!(&self.log, Some(log) if log.path() == settings.path) {
+            return;
         }
+
+        if !self.restored {
+            self.restored = true;
+            match MessageLog::read(&settings.path) {
+                Ok(entries) => {
+                    let received = std::mem::take(&mut self.data);
+                    entries
+                        .into_iter()
+                        .chain(received)
+                        .for_each(|e| self.push(e));
+                }
+                Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+                Err(err) => self.status = Some(format!("{}: {}", settings.path, err)),
+            }
+        }
+
+        match MessageLog::open(&settings.path) {
+            Ok(log) => self.log = Some(log),
+            Err(err) => {
+                self.status = Some(format!("{}: {}", settings.path, err));
+                settings.enabled = false;
+            }
+        }
+    }
+
+    // Everything in the log, if there is one, or else what is in memory.
+    fn export(&self, format: ExportFormat, settings: &LogSettings) -> io::Result<String> {
+        let entries = match &self.log {
+            Some(log) => MessageLog::read(log.path())?,
+            None => self.data.iter().cloned().collect(),
+        };
+
+        let path = format!("{}.{}", settings.export_path, format.extension());
+        let mut writer = BufWriter::new(File::create(&path)?);
+        match format {
+            ExportFormat::Csv => write_csv(&entries, &mut writer)?,
+            ExportFormat::Adif => write_adif(&entries, &mut writer)?,
+        }
+        writer.flush()?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    #[test]
+    fn test_text_is_trimmed() {
+        let (_tx, rx) = mpsc::channel(1);
+        let mut collector = MessageCollector::new(rx);
+
+        let line = format!("{}\n", "RY".repeat(50));
+        for _ in 0..1000 {
+            collector.push_text(&line);
+        }
+        assert!(collector.text.len() <= MAX_TEXT);
+        assert!(collector.text.starts_with("RYRY"));
+        assert!(collector.text.ends_with("RY\n"));
+
+        // Never part of a character.
+        collector.push_text(&"\u{e9}".repeat(MAX_TEXT));
+        assert!(collector.text.len() <= MAX_TEXT);
+        assert!(collector.text.chars().all(|c| c == '\u{e9}'));
     }
 }
@@ -0,0 +1,92 @@
+use tokio::sync::mpsc;
+
+use crate::configuration::Configuration;
+use crate::dsp::resampler::Resampler;
+use crate::dsp::rx::SLICE_DURATION;
+use crate::output::AudioOutput;
+use crate::types::SampleType;
+use crate::units::Frequency;
+
+// Audio arrives a receiver slice at a time, so playback starts a slice
+// behind, and this much more for the receiver and UI to keep up.
+const MARGIN_SECONDS: f32 = 0.25;
+
+/// Plays the receiver's monitor audio on the default output device.
+pub struct Monitor {
+    monitor_rx: mpsc::Receiver<Vec<SampleType>>,
+    output: Option<AudioOutput>,
+    // From the input rate, which it was built for, to the device's rate.
+    resampler: Option<(Frequency, Resampler)>,
+    pub status: Option<String>,
+}
+
+impl Monitor {
+    pub fn new(monitor_rx: mpsc::Receiver<Vec<SampleType>>) -> Self {
+        Self {
+            monitor_rx,
+            output: None,
+            resampler: None,
+            status: None,
+        }
+    }
+
+    pub fn run(&mut self, config: &mut Configuration) {
+        if !config.monitor.enabled {
+            // Releases the device.
+            self.output = None;
+            while self.monitor_rx.try_recv().is_ok() {}
+            return;
+        }
+
+        if self.output.is_none() {
+            match AudioOutput::new() {
+                Ok(output) => {
+                    self.output = Some(output);
+                    self.status = None;
+                }
+                Err(err) => {
+                    self.status = Some(err);
+                    config.monitor.enabled = false;
+                    return;
+                }
+            }
+        }
+        let output = self.output.as_ref().unwrap();
+        let output_rate = output.sample_rate();
+        let input_rate = config.audio_sample_rate.as_frequency();
+
+        match &self.resampler {
+            Some((rate, _)) if *rate == input_rate => (),
+            _ => self.resampler = Some((input_rate, Resampler::new(input_rate, output_rate))),
+        }
+        let (_, resampler) = self.resampler.as_mut().unwrap();
+
+        let gain = if config.monitor.mute {
+            0.0
+        } else {
+            config.monitor.volume
+        };
+        let seconds = |seconds: f32| (seconds * output_rate.value()) as usize;
+        let buffer = seconds(SLICE_DURATION + MARGIN_SECONDS);
+        // Beyond a slice more than that the input is running faster than the
+        // output device, and slices are dropped to catch up.
+        let max_queued = buffer + seconds(SLICE_DURATION);
+
+        while let Ok(samples) = self.monitor_rx.try_recv() {
+            let samples: Vec<SampleType> = resampler
+                .process(&samples)
+                .into_iter()
+                .map(|sample| gain * sample)
+                .collect();
+
+            let queued = output.queued();
+            if queued > max_queued {
+                continue;
+            }
+            if queued == 0 {
+                output.play(vec![0.0; buffer]);
+            }
+            output.play(samples);
+        }
+    }
+}
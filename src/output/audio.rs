@@ -0,0 +1,87 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{Device, Sample, Stream, StreamConfig};
+
+use crate::types::SampleType;
+use crate::units::Frequency;
+
+type Queue = Arc<Mutex<VecDeque<SampleType>>>;
+
+/// Plays mono samples on the default output device, on every channel.
+pub struct AudioOutput {
+    queue: Queue,
+    sample_rate: Frequency,
+    _stream: Stream,
+}
+
+fn build_stream<T: Sample>(
+    device: &Device,
+    config: &StreamConfig,
+    queue: Queue,
+) -> Result<Stream, cpal::BuildStreamError> {
+    let channels = config.channels as usize;
+
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _: &_| {
+            let mut queue = queue.lock().unwrap();
+            for frame in data.chunks_mut(channels) {
+                let value = queue.pop_front().unwrap_or(0.0);
+                frame.iter_mut().for_each(|s| *s = Sample::from(&value));
+            }
+        },
+        |err| println!("Audio output error: {}", err),
+    )
+}
+
+impl AudioOutput {
+    pub fn new() -> Result<Self, String> {
+        let host = cpal::default_host();
+        let device = host
+            .default_output_device()
+            .ok_or_else(|| "No output device".to_string())?;
+
+        println!("Using output device {}", device.name().unwrap());
+
+        let supported = device
+            .default_output_config()
+            .map_err(|err| err.to_string())?;
+        let sample_rate = Frequency::Hertz(supported.sample_rate().0 as f32);
+        let config: StreamConfig = supported.config();
+
+        let queue: Queue = Default::default();
+        let stream = match supported.sample_format() {
+            cpal::SampleFormat::F32 => build_stream::<f32>(&device, &config, queue.clone()),
+            cpal::SampleFormat::I16 => build_stream::<i16>(&device, &config, queue.clone()),
+            cpal::SampleFormat::U16 => build_stream::<u16>(&device, &config, queue.clone()),
+        }
+        .map_err(|err| err.to_string())?;
+
+        stream.play().map_err(|err| err.to_string())?;
+
+        Ok(Self {
+            queue,
+            sample_rate,
+            _stream: stream,
+        })
+    }
+
+    pub fn sample_rate(&self) -> Frequency {
+        self.sample_rate
+    }
+
+    pub fn play(&self, samples: Vec<SampleType>) {
+        self.queue.lock().unwrap().extend(samples);
+    }
+
+    /// Samples waiting to be played.
+    pub fn queued(&self) -> usize {
+        self.queue.lock().unwrap().len()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        !self.queue.lock().unwrap().is_empty()
+    }
+}
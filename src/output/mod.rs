@@ -0,0 +1,5 @@
+mod audio;
+mod wav;
+
+pub use audio::AudioOutput;
+pub use wav::write_wav;
@@ -0,0 +1,27 @@
+use std::path::Path;
+
+use hound::{SampleFormat, WavSpec, WavWriter};
+
+use crate::types::SampleType;
+use crate::units::Frequency;
+
+/// Write mono 16-bit PCM.
+pub fn write_wav(
+    path: impl AsRef<Path>,
+    samples: &[SampleType],
+    sample_rate: Frequency,
+) -> Result<(), hound::Error> {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: sample_rate.value() as u32,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+
+    let mut writer = WavWriter::create(path, spec)?;
+    for sample in samples {
+        let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        writer.write_sample(sample)?;
+    }
+    writer.finalize()
+}
@@ -0,0 +1,180 @@
+mod rigctld;
+
+use std::io;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+use crate::configuration::{Configuration, Sideband};
+use crate::units::format_hertz;
+
+pub use rigctld::Rigctld;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// What the radio last reported.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RigState {
+    pub frequency: f64,
+    pub mode: String,
+    pub passband: f32,
+}
+
+impl RigState {
+    /// The sideband audio is received on, if the mode has one.
+    pub fn sideband(&self) -> Option<Sideband> {
+        if self.mode.ends_with("USB") {
+            Some(Sideband::Usb)
+        } else if self.mode.ends_with("LSB") {
+            Some(Sideband::Lsb)
+        } else {
+            None
+        }
+    }
+}
+
+enum Request {
+    SetFrequency(f64),
+    SetSideband(Sideband),
+}
+
+// How many requests had been handled, and the radio's state after them.
+type Report = (u64, Result<RigState, String>);
+
+fn poll(rig: &mut Rigctld) -> io::Result<RigState> {
+    let frequency = rig.frequency()?;
+    let (mode, passband) = rig.mode()?;
+    Ok(RigState {
+        frequency,
+        mode,
+        passband,
+    })
+}
+
+/// Talks to the radio on its own thread, so a slow radio can't hold up the
+/// UI. Stops when the `RigControl` hangs up.
+fn worker(address: String, requests: Receiver<Request>, reports: Sender<Report>) {
+    let mut rig = match Rigctld::connect(&address) {
+        Ok(rig) => rig,
+        Err(err) => {
+            let error = format!("Unable to connect to {}: {}", address, err);
+            let _ = reports.send((0, Err(error)));
+            return;
+        }
+    };
+
+    let mut handled = 0;
+    let mut mode = String::new();
+    let mut result = Ok(());
+    loop {
+        let state = result.and_then(|_| poll(&mut rig));
+        if let Ok(state) = &state {
+            mode = state.mode.clone();
+        }
+        if reports
+            .send((handled, state.map_err(|err| err.to_string())))
+            .is_err()
+        {
+            return;
+        }
+
+        result = match requests.recv_timeout(POLL_INTERVAL) {
+            Ok(request) => {
+                handled += 1;
+                match request {
+                    Request::SetFrequency(hz) => rig.set_frequency(hz),
+                    Request::SetSideband(sideband) => {
+                        // Stay in the data variant of the mode if we're in it.
+                        let prefix = if mode.starts_with("PKT") { "PKT" } else { "" };
+                        rig.set_mode(&format!("{}{}", prefix, sideband), 0.0)
+                    }
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => Ok(()),
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+    }
+}
+
+/// Keeps `Configuration::dial_frequency` and `sideband` in step with a radio
+/// controlled through rigctld. Changes from either side are passed on to the
+/// other.
+#[derive(Default)]
+pub struct RigControl {
+    address: Option<String>,
+    requests: Option<Sender<Request>>,
+    reports: Option<Receiver<Report>>,
+    sent: u64,
+    synced: Option<(f64, Sideband)>,
+    pub status: Option<String>,
+}
+
+impl RigControl {
+    fn connect(&mut self, address: Option<String>) {
+        *self = Self::default();
+
+        if let Some(address) = &address {
+            let (request_tx, request_rx) = mpsc::channel();
+            let (report_tx, report_rx) = mpsc::channel();
+            let worker_address = address.clone();
+            thread::spawn(move || worker(worker_address, request_rx, report_tx));
+
+            self.requests = Some(request_tx);
+            self.reports = Some(report_rx);
+            self.status = Some(format!("Connecting to {}", address));
+        }
+        self.address = address;
+    }
+
+    fn send(&mut self, request: Request) {
+        if let Some(requests) = &self.requests {
+            if requests.send(request).is_ok() {
+                self.sent += 1;
+            }
+        }
+    }
+
+    pub fn run(&mut self, config: &mut Configuration) {
+        let address = if config.rig.enabled {
+            Some(config.rig.address.clone())
+        } else {
+            None
+        };
+        if address != self.address {
+            self.connect(address);
+        }
+
+        // Edits made since the last sync go to the radio.
+        if let Some((frequency, sideband)) = self.synced {
+            if config.dial_frequency != frequency {
+                self.send(Request::SetFrequency(config.dial_frequency));
+            }
+            if config.sideband != sideband {
+                self.send(Request::SetSideband(config.sideband));
+            }
+            self.synced = Some((config.dial_frequency, config.sideband));
+        }
+
+        let reports = match &self.reports {
+            Some(reports) => reports,
+            None => return,
+        };
+        while let Ok((handled, state)) = reports.try_recv() {
+            match state {
+                // Reports from before our latest requests are stale.
+                Ok(state) if handled == self.sent => {
+                    config.dial_frequency = state.frequency;
+                    if let Some(sideband) = state.sideband() {
+                        config.sideband = sideband;
+                    }
+                    config.rig.passband = state.passband;
+
+                    self.synced = Some((config.dial_frequency, config.sideband));
+                    self.status = Some(format!("{} {}", format_hertz(state.frequency), state.mode));
+                }
+                Ok(_) => (),
+                Err(err) => self.status = Some(err),
+            }
+        }
+    }
+}
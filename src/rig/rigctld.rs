@@ -0,0 +1,87 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Client for Hamlib's rigctld, which takes one command per line and answers
+/// either with values, one per line, or with "RPRT <code>".
+pub struct Rigctld {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Rigctld {
+    pub fn connect(address: &str) -> io::Result<Self> {
+        let address = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::AddrNotAvailable))?;
+
+        let writer = TcpStream::connect_timeout(&address, TIMEOUT)?;
+        writer.set_read_timeout(Some(TIMEOUT))?;
+        writer.set_nodelay(true)?;
+        let reader = BufReader::new(writer.try_clone()?);
+
+        Ok(Self { reader, writer })
+    }
+
+    /// Send `command` and read `lines` lines of reply.
+    fn command(&mut self, command: &str, lines: usize) -> io::Result<Vec<String>> {
+        self.writer.write_all(format!("{}\n", command).as_bytes())?;
+
+        let mut reply = Vec::with_capacity(lines);
+        while reply.len() < lines {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            let line = line.trim_end().to_string();
+
+            if let Some(code) = line.strip_prefix("RPRT ") {
+                if code != "0" {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("rigctld: {} failed with {}", command, code),
+                    ));
+                }
+                // Set commands only ever answer with a report.
+                break;
+            }
+
+            reply.push(line);
+        }
+        Ok(reply)
+    }
+
+    fn parse<T: FromStr>(reply: &[String], index: usize) -> io::Result<T> {
+        let value = reply.get(index).map(String::as_str).unwrap_or_default();
+        value
+            .parse()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, value.to_string()))
+    }
+
+    /// VFO frequency in Hz.
+    pub fn frequency(&mut self) -> io::Result<f64> {
+        let reply = self.command("f", 1)?;
+        Self::parse(&reply, 0)
+    }
+
+    pub fn set_frequency(&mut self, hz: f64) -> io::Result<()> {
+        self.command(&format!("F {:.0}", hz), 1).map(|_| ())
+    }
+
+    /// Mode, such as "USB" or "PKTLSB", and passband in Hz.
+    pub fn mode(&mut self) -> io::Result<(String, f32)> {
+        let reply = self.command("m", 2)?;
+        let passband = Self::parse(&reply, 1)?;
+        Ok((reply[0].clone(), passband))
+    }
+
+    /// A passband of 0 leaves the choice to the radio.
+    pub fn set_mode(&mut self, mode: &str, passband: f32) -> io::Result<()> {
+        self.command(&format!("M {} {:.0}", mode, passband), 1)
+            .map(|_| ())
+    }
+}
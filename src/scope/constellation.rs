@@ -0,0 +1,92 @@
+use std::collections::VecDeque;
+
+use crate::types::ComplexType;
+
+// How many symbols are shown at once, and measured over.
+const MAX_POINTS: usize = 500;
+
+/// How far recovered BPSK symbols are from where they should be.
+#[derive(Clone, Copy, Debug)]
+pub struct ConstellationStats {
+    // RMS, in degrees.
+    pub phase_error: f32,
+    // Error vector magnitude, in percent of the ideal.
+    pub evm: f32,
+    // Modulation error ratio, in dB.
+    pub mer: f32,
+}
+
+/// The most recent PSK symbols, as points in the I/Q plane.
+#[derive(Default)]
+pub struct Constellation {
+    points: VecDeque<ComplexType>,
+}
+
+impl Constellation {
+    pub fn push(&mut self, i: &[f32], q: &[f32]) {
+        self.points
+            .extend(i.iter().zip(q).map(|(i, q)| ComplexType::new(*i, *q)));
+        while self.points.len() > MAX_POINTS {
+            self.points.pop_front();
+        }
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = &ComplexType> {
+        self.points.iter()
+    }
+
+    /// Each point is measured against whichever of the two ideal points it
+    /// is nearer, at the average amplitude.
+    pub fn stats(&self) -> Option<ConstellationStats> {
+        if self.points.is_empty() {
+            return None;
+        }
+        let n = self.points.len() as f32;
+        let amplitude = self.points.iter().map(|p| p.norm()).sum::<f32>() / n;
+        if amplitude == 0.0 {
+            return None;
+        }
+
+        let error_power = self
+            .points
+            .iter()
+            .map(|p| (p - ComplexType::new(amplitude.copysign(p.re), 0.0)).norm_sqr())
+            .sum::<f32>()
+            / n;
+        let phase_power = self
+            .points
+            .iter()
+            .map(|p| p.im.atan2(p.re.abs()).powi(2))
+            .sum::<f32>()
+            / n;
+
+        Some(ConstellationStats {
+            phase_error: phase_power.sqrt().to_degrees(),
+            evm: 100.0 * error_power.sqrt() / amplitude,
+            mer: 10.0 * (amplitude * amplitude / error_power).log10(),
+        })
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let mut constellation = Constellation::default();
+        // Both points 0.1 off in quadrature.
+        constellation.push(&[1.0, -1.0], &[0.1, -0.1]);
+
+        let stats = constellation.stats().unwrap();
+        let amplitude = (1.01f32).sqrt();
+        let error = (amplitude - 1.0).powi(2) + 0.01;
+        assert!((stats.evm - 100.0 * error.sqrt() / amplitude).abs() < 1e-3);
+        assert!((stats.mer + 20.0 * (stats.evm / 100.0).log10()).abs() < 1e-3);
+        assert!((stats.phase_error - 0.1f32.atan().to_degrees()).abs() < 1e-3);
+    }
+}
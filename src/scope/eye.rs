@@ -0,0 +1,125 @@
+use std::collections::VecDeque;
+
+use crate::types::SampleType;
+use crate::units::Frequency;
+
+// How many symbols are overlaid at once.
+const MAX_SEGMENTS: usize = 200;
+
+/// The discriminator cut into pieces around each point the decoder sampled a
+/// bit at, one symbol either side, for overlaying as an eye diagram.
+pub struct Eye {
+    pub sample_rate: Frequency,
+    // Samples either side of each sampling point.
+    pub half_width: usize,
+    segments: VecDeque<Vec<SampleType>>,
+    // Held back until there is a symbol either side of each sampling point.
+    discriminator: Vec<SampleType>,
+    bits: Vec<SampleType>,
+    // Sampling points before this have been cut out already.
+    next: usize,
+}
+
+impl Eye {
+    pub fn new(sample_rate: Frequency, baud: Frequency) -> Self {
+        Self {
+            sample_rate,
+            half_width: (sample_rate.value() / baud.value()).round() as usize,
+            segments: VecDeque::new(),
+            discriminator: Vec::new(),
+            bits: Vec::new(),
+            next: 0,
+        }
+    }
+
+    /// Takes the discriminator and the bit samples, which are non-zero where
+    /// a bit was sampled, over the same stretch of time.
+    pub fn push(&mut self, discriminator: &[SampleType], bits: &[SampleType]) {
+        self.discriminator.extend(discriminator);
+        self.bits.extend(bits);
+
+        let width = self.half_width;
+        let len = self.discriminator.len().min(self.bits.len());
+        for i in self.next.max(width)..len.saturating_sub(width) {
+            if self.bits[i] != 0.0 {
+                self.segments
+                    .push_back(self.discriminator[i - width..=i + width].to_vec());
+            }
+        }
+        while self.segments.len() > MAX_SEGMENTS {
+            self.segments.pop_front();
+        }
+
+        self.next = self.next.max(len.saturating_sub(width));
+        let drop = len.saturating_sub(2 * width);
+        self.discriminator.drain(..drop);
+        self.bits.drain(..drop);
+        self.next -= drop;
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = &Vec<SampleType>> {
+        self.segments.iter()
+    }
+
+    /// The gap between the lowest mark and highest space at the sampling
+    /// point, over the gap between their means. 1 for a clean eye, and 0 or
+    /// less once it has closed.
+    pub fn opening(&self) -> Option<f32> {
+        let centres = self.segments.iter().map(|segment| segment[self.half_width]);
+        let marks: Vec<f32> = centres.clone().filter(|x| *x > 0.0).collect();
+        let spaces: Vec<f32> = centres.filter(|x| *x <= 0.0).collect();
+        if marks.is_empty() || spaces.is_empty() {
+            return None;
+        }
+
+        let mean = |xs: &[f32]| xs.iter().sum::<f32>() / xs.len() as f32;
+        let lowest_mark = marks.iter().copied().fold(f32::INFINITY, f32::min);
+        let highest_space = spaces.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        Some((lowest_mark - highest_space) / (mean(&marks) - mean(&spaces)))
+    }
+
+    pub fn clear(&mut self) {
+        self.segments.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Alternating bits, `period` samples each, sampled in the middle.
+    fn dotting(period: usize, symbols: usize) -> (Vec<f32>, Vec<f32>) {
+        let mut discriminator = Vec::new();
+        let mut bits = Vec::new();
+        for symbol in 0..symbols {
+            let level = if symbol % 2 == 0 { 1.0 } else { -1.0 };
+            for i in 0..period {
+                discriminator.push(level);
+                bits.push(if i == period / 2 { level } else { 0.0 });
+            }
+        }
+        (discriminator, bits)
+    }
+
+    #[test]
+    fn test_eye() {
+        let period = 10;
+        let (discriminator, bits) = dotting(period, 30);
+
+        let mut whole = Eye::new(Frequency::Hertz(100.0), Frequency::Hertz(10.0));
+        whole.push(&discriminator, &bits);
+        // Bar the first and last, which lack a symbol on one side.
+        assert_eq!(whole.segments().count(), 28);
+        assert!(whole
+            .segments()
+            .all(|segment| segment.len() == 2 * period + 1));
+        assert_eq!(whole.opening(), Some(1.0));
+
+        // Block boundaries make no difference.
+        let mut blocks = Eye::new(Frequency::Hertz(100.0), Frequency::Hertz(10.0));
+        for (discriminator, bits) in discriminator.chunks(7).zip(bits.chunks(7)) {
+            blocks.push(discriminator, bits);
+        }
+        assert!(whole.segments().eq(blocks.segments()));
+    }
+}
@@ -0,0 +1,261 @@
+mod constellation;
+mod eye;
+
+use tokio::sync::mpsc;
+
+use crate::configuration::{
+    Configuration, Probe, ScopeMode, ScopeView, TriggerMode, TriggerSettings,
+};
+use crate::dsp::decode::Rtty;
+use crate::types::SampleType;
+use crate::units::Frequency;
+
+pub use constellation::{Constellation, ConstellationStats};
+pub use eye::Eye;
+
+fn trigger_position(data: &[SampleType], settings: &TriggerSettings) -> Option<usize> {
+    let TriggerSettings { mode, level, .. } = settings;
+    let lower = |d| d < level;
+    let higher = |d| d > level;
+    match mode {
+        TriggerMode::Rising => {
+            let below = data.iter().position(lower)?;
+            Some(below + data[below..].iter().position(higher)?)
+        }
+        TriggerMode::Falling => {
+            let above = data.iter().position(higher)?;
+            Some(above + data[above..].iter().position(lower)?)
+        }
+        TriggerMode::Auto => Some(0),
+    }
+}
+
+/// One probe's worth of samples from the receive chain.
+#[derive(Clone, Debug)]
+pub struct Trace {
+    pub probe: Probe,
+    pub sample_rate: Frequency,
+    pub samples: Vec<SampleType>,
+    // Time of the first sample, in seconds from the trigger.
+    pub start: f32,
+}
+
+impl Trace {
+    pub fn new(probe: Probe, sample_rate: Frequency, samples: Vec<SampleType>) -> Self {
+        Self {
+            probe,
+            sample_rate,
+            samples,
+            start: 0.0,
+        }
+    }
+
+    fn seconds(&self) -> f32 {
+        self.samples.len() as f32 / self.sample_rate.value()
+    }
+
+    /// Drops everything before `from` seconds in, and counts time from
+    /// `trigger` seconds in.
+    fn crop(&self, from: f32, trigger: f32) -> Self {
+        let start = ((from * self.sample_rate.value()).round() as usize).min(self.samples.len());
+        Self {
+            samples: self.samples[start..].to_vec(),
+            start: from - trigger,
+            ..*self
+        }
+    }
+
+    /// This, following on from `history` if it is from the same probe.
+    fn after(&self, history: Option<&Trace>) -> Self {
+        let mut samples = Vec::new();
+        if let Some(history) = history {
+            if history.probe == self.probe && history.sample_rate == self.sample_rate {
+                samples.extend(&history.samples);
+            }
+        }
+        samples.extend(&self.samples);
+        Self::new(self.probe, self.sample_rate, samples)
+    }
+}
+
+pub struct Scope {
+    plot_rx: mpsc::Receiver<Vec<Trace>>,
+    traces: Vec<Trace>,
+    // The last block received, for the time before a trigger near the
+    // start of the next.
+    history: Vec<Trace>,
+    eye: Option<Eye>,
+    constellation: Constellation,
+}
+
+impl Scope {
+    pub fn new(plot_rx: mpsc::Receiver<Vec<Trace>>) -> Self {
+        Self {
+            plot_rx,
+            traces: Vec::new(),
+            history: Vec::new(),
+            eye: None,
+            constellation: Constellation::default(),
+        }
+    }
+
+    pub fn run(&mut self, config: &mut Configuration) {
+        let mut blocks = Vec::new();
+        while let Ok(traces) = self.plot_rx.try_recv() {
+            if !traces.is_empty() {
+                blocks.push(traces);
+            }
+        }
+        if blocks.is_empty() {
+            return;
+        }
+
+        if config.scope.mode != ScopeMode::Stop {
+            let captured = match config.scope.view {
+                ScopeView::Time => self.trigger(&blocks, config),
+                ScopeView::Eye => {
+                    self.update_eye(&blocks);
+                    true
+                }
+                ScopeView::Constellation => {
+                    self.update_constellation(&blocks);
+                    true
+                }
+            };
+            if captured && config.scope.mode == ScopeMode::Single {
+                config.scope.mode = ScopeMode::Stop;
+            }
+        }
+        self.history = blocks.pop().unwrap();
+    }
+
+    // Looks for a trigger in the newest block, and whether there was one.
+    fn trigger(&mut self, blocks: &[Vec<Trace>], config: &Configuration) -> bool {
+        let new_traces = &blocks[blocks.len() - 1];
+        let history = match blocks.len() {
+            1 => &self.history,
+            n => &blocks[n - 2],
+        };
+        let traces: Vec<Trace> = new_traces
+            .iter()
+            .enumerate()
+            .map(|(i, trace)| trace.after(history.get(i)))
+            .collect();
+
+        // Only new samples are triggered on, as long as there is enough
+        // before them.
+        let first = &traces[0];
+        let history_seconds = first.seconds() - new_traces[0].seconds();
+        let pre_trigger = config.scope.pre_trigger();
+        let search_from = (history_seconds.max(pre_trigger) * first.sample_rate.value()) as usize;
+        let search_from = search_from.min(first.samples.len());
+
+        let position = match trigger_position(&first.samples[search_from..], &config.scope.trigger)
+        {
+            Some(position) => position,
+            None => return false,
+        };
+
+        // Traces can be at different rates, so they are lined up in time.
+        let trigger = (search_from + position) as f32 / first.sample_rate.value();
+        let from = (trigger - pre_trigger).max(0.0);
+        self.traces = traces
+            .iter()
+            .map(|trace| trace.crop(from, trigger))
+            .collect();
+        true
+    }
+
+    // Every block counts towards the eye, not just the newest.
+    fn update_eye(&mut self, blocks: &[Vec<Trace>]) {
+        for traces in blocks {
+            let find = |probe| traces.iter().find(|trace| trace.probe == probe);
+            let (discriminator, bits) = match (find(Probe::Discriminator), find(Probe::BitSamples))
+            {
+                (Some(discriminator), Some(bits)) => (discriminator, bits),
+                _ => continue,
+            };
+
+            match &self.eye {
+                Some(eye) if eye.sample_rate == discriminator.sample_rate => (),
+                _ => self.eye = Some(Eye::new(discriminator.sample_rate, Rtty::BAUD)),
+            }
+            self.eye
+                .as_mut()
+                .unwrap()
+                .push(&discriminator.samples, &bits.samples);
+        }
+    }
+
+    fn update_constellation(&mut self, blocks: &[Vec<Trace>]) {
+        for traces in blocks {
+            let find = |probe| traces.iter().find(|trace| trace.probe == probe);
+            if let (Some(i), Some(q)) = (find(Probe::PskI), find(Probe::PskQ)) {
+                self.constellation.push(&i.samples, &q.samples);
+            }
+        }
+    }
+
+    pub fn traces(&self) -> &[Trace] {
+        &self.traces
+    }
+
+    pub fn eye(&self) -> Option<&Eye> {
+        self.eye.as_ref()
+    }
+
+    pub fn constellation(&self) -> &Constellation {
+        &self.constellation
+    }
+
+    pub fn clear_constellation(&mut self) {
+        self.constellation.clear();
+    }
+
+    pub fn clear_eye(&mut self) {
+        if let Some(eye) = &mut self.eye {
+            eye.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configuration::AxisMode;
+
+    #[test]
+    fn test_pre_trigger() {
+        let mut config = Configuration::default();
+        config.scope.x_mode = AxisMode::Divisions;
+        config.scope.timebase = 10.0;
+        config.scope.trigger.position = 0.1;
+        config.scope.trigger.mode = TriggerMode::Rising;
+        config.scope.trigger.level = 0.5;
+
+        let (plot_tx, plot_rx) = mpsc::channel(2);
+        let mut scope = Scope::new(plot_rx);
+        let sample_rate = Frequency::Hertz(1000.0);
+        let send = |samples: Vec<SampleType>| {
+            plot_tx
+                .try_send(vec![Trace::new(Probe::Channel, sample_rate, samples)])
+                .unwrap();
+        };
+
+        send(vec![0.0; 200]);
+        scope.run(&mut config);
+        assert!(scope.traces().is_empty());
+
+        // An edge so close to the start of a block that the time before it
+        // comes from the one before.
+        let mut samples = vec![1.0; 200];
+        samples[..5].fill(0.0);
+        send(samples);
+        scope.run(&mut config);
+
+        let trace = &scope.traces()[0];
+        assert!((trace.start + 0.01).abs() < 1e-6, "{}", trace.start);
+        assert_eq!(trace.samples[9], 0.0);
+        assert_eq!(trace.samples[10], 1.0);
+    }
+}
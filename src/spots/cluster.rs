@@ -0,0 +1,57 @@
+use crate::message_log::utc;
+use crate::spots::Spot;
+
+/// A spot as a DX cluster announces it, such as
+///
+/// `DX de K1ABC:     14085.0  W1AW         RTTY +12 dB FN31PR             1234Z`
+pub fn cluster_line(spot: &Spot, spotter: &str) -> String {
+    let mut comment = spot.mode.clone();
+    if let Some(snr) = spot.snr {
+        comment += &format!(" {:+.0} dB", snr);
+    }
+    if let Some(grid) = &spot.grid {
+        comment += &format!(" {}", grid);
+    }
+    if let Some(report) = &spot.report {
+        comment += &format!(" {}", report);
+    }
+    let comment: String = comment.chars().take(30).collect();
+
+    let (_, _, _, hour, minute, _) = utc(spot.time);
+    format!(
+        "DX de {:<10}{:>8.1}  {:<13}{:<30} {:02}{:02}Z",
+        format!("{}:", spotter),
+        // In kHz.
+        spot.frequency / 1e3,
+        spot.callsign,
+        comment,
+        hour,
+        minute
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use wasm_timer::UNIX_EPOCH;
+
+    use super::*;
+
+    #[test]
+    fn test_cluster_line() {
+        let spot = Spot {
+            time: UNIX_EPOCH + Duration::from_secs(951827696),
+            callsign: "W1AW".to_string(),
+            frequency: 14085000.0,
+            mode: "RTTY".to_string(),
+            snr: Some(12.4),
+            grid: Some("FN31PR".to_string()),
+            report: None,
+        };
+        assert_eq!(
+            cluster_line(&spot, "K1ABC"),
+            "DX de K1ABC:     14085.0  W1AW         RTTY +12 dB FN31PR             1234Z"
+        );
+    }
+}
@@ -0,0 +1,281 @@
+mod cluster;
+mod psk_reporter;
+
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::OpenOptions;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{self, Write};
+#[cfg(not(target_arch = "wasm32"))]
+use std::net::UdpSocket;
+use std::time::Duration;
+
+use wasm_timer::{Instant, SystemTime};
+
+use crate::configuration::Configuration;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::configuration::{SpotSettings, StationSettings};
+use crate::message_log::LogEntry;
+use crate::message_parse::parse;
+use crate::messages::MessageCollector;
+
+pub use cluster::cluster_line;
+pub use psk_reporter::{datagram, Receiver};
+
+// A station calling for a while is spotted once.
+const REPEAT_INTERVAL: Duration = Duration::from_secs(5 * 60);
+// Spots are sent to PSK Reporter in batches, at most this often.
+#[cfg(not(target_arch = "wasm32"))]
+const REPORT_INTERVAL: Duration = Duration::from_secs(30);
+// Keeps datagrams well under the usual MTU.
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_BATCH: usize = 20;
+
+/// Someone heard on the air, as worked out from a decoded message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spot {
+    pub time: SystemTime,
+    pub callsign: String,
+    // RF, in Hz.
+    pub frequency: f64,
+    pub mode: String,
+    pub snr: Option<f32>,
+    pub grid: Option<String>,
+    pub report: Option<String>,
+}
+
+impl Spot {
+    /// The message's sender, if it names one and has a frequency to spot
+    /// it on.
+    pub fn from_entry(entry: &LogEntry) -> Option<Self> {
+        let frequency = entry.frequency?;
+        let parsed = parse(&entry.payload);
+        Some(Self {
+            time: entry.time,
+            callsign: parsed.sender?,
+            frequency,
+            mode: entry.mode.clone(),
+            snr: entry.snr,
+            grid: parsed.grids.into_iter().next(),
+            report: parsed.reports.into_iter().next(),
+        })
+    }
+}
+
+/// Spots the senders of decoded messages, as DX cluster lines appended to a
+/// file and as PSK Reporter datagrams sent to a local collector.
+pub struct Spotter {
+    // What had been received as of the last run.
+    received: u64,
+    // When each callsign was last spotted, in each mode.
+    spotted: HashMap<(String, String), Instant>,
+    pending: Vec<Spot>,
+    #[cfg(not(target_arch = "wasm32"))]
+    last_report: Instant,
+    #[cfg(not(target_arch = "wasm32"))]
+    socket: Option<UdpSocket>,
+    // Spots sent so far, and an id for this session, as IPFIX wants.
+    #[cfg(not(target_arch = "wasm32"))]
+    sequence: u32,
+    #[cfg(not(target_arch = "wasm32"))]
+    domain: u32,
+    pub status: Option<String>,
+}
+
+impl Default for Spotter {
+    fn default() -> Self {
+        Self {
+            received: 0,
+            spotted: HashMap::new(),
+            pending: Vec::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            last_report: Instant::now(),
+            #[cfg(not(target_arch = "wasm32"))]
+            socket: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            sequence: 0,
+            #[cfg(not(target_arch = "wasm32"))]
+            domain: rand::random(),
+            status: None,
+        }
+    }
+}
+
+impl Spotter {
+    pub fn run(&mut self, messages: &MessageCollector, config: &Configuration) {
+        let new = messages.received().saturating_sub(self.received) as usize;
+        self.received = messages.received();
+        if !config.spots.enabled {
+            self.pending.clear();
+            return;
+        }
+
+        let now = Instant::now();
+        self.spotted
+            .retain(|_, time| now.duration_since(*time) < REPEAT_INTERVAL);
+        let new = new.min(messages.data.len());
+        // Without our callsign there's nobody to say who heard it, and
+        // without a dial or center frequency it would be at an audio frequency.
+        // Stations heard meanwhile aren't held against later spots.
+        if config.station.callsign.trim().is_empty() {
+            if new > 0 {
+                self.status = Some("Set a callsign to report spots".to_string());
+            }
+            return;
+        }
+        if !config.has_rf_frequency() {
+            if new > 0 {
+                self.status = Some("Set the dial or center frequency to report spots".to_string());
+            }
+            return;
+        }
+
+        let spots: Vec<Spot> = messages
+            .data
+            .iter()
+            .skip(messages.data.len() - new)
+            .filter_map(Spot::from_entry)
+            .filter(|spot| spot.callsign != config.station.callsign.to_uppercase())
+            .collect();
+        for spot in spots {
+            let key = (spot.callsign.clone(), spot.mode.clone());
+            if self.spotted.insert(key, now).is_none() {
+                self.spot(spot, config);
+            }
+        }
+
+        // There is no file system or UDP in the browser.
+        #[cfg(not(target_arch = "wasm32"))]
+        if config.spots.psk_reporter
+            && (self.pending.len() >= MAX_BATCH
+                || (!self.pending.is_empty()
+                    && now.duration_since(self.last_report) >= REPORT_INTERVAL))
+        {
+            self.last_report = now;
+            if let Err(err) = self.report(&config.spots, &config.station) {
+                self.status = Some(format!("{}: {}", config.spots.host, err));
+            }
+        }
+    }
+
+    fn spot(&mut self, spot: Spot, config: &Configuration) {
+        self.status = Some(format!("Spotted {}", spot.callsign));
+        #[cfg(not(target_arch = "wasm32"))]
+        if !config.spots.cluster_path.is_empty() {
+            let callsign = config.station.callsign.trim().to_uppercase();
+            let line = cluster_line(&spot, &callsign);
+            if let Err(err) = append(&config.spots.cluster_path, &line) {
+                self.status = Some(format!("{}: {}", config.spots.cluster_path, err));
+            }
+        }
+        if config.spots.psk_reporter {
+            self.pending.push(spot);
+        }
+    }
+
+    // Sends what's pending, in as many datagrams as it takes.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn report(&mut self, settings: &SpotSettings, station: &StationSettings) -> io::Result<()> {
+        if self.socket.is_none() {
+            self.socket = Some(UdpSocket::bind("0.0.0.0:0")?);
+        }
+        let socket = self.socket.as_ref().unwrap();
+
+        let callsign = station.callsign.trim().to_uppercase();
+        let receiver = Receiver {
+            callsign: &callsign,
+            locator: station.locator.trim(),
+            software: concat!("waterfall-rs ", env!("CARGO_PKG_VERSION")),
+        };
+        let export_time = SystemTime::now()
+            .duration_since(wasm_timer::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as u32;
+        let pending = std::mem::take(&mut self.pending);
+        for spots in pending.chunks(MAX_BATCH) {
+            let datagram = datagram(&receiver, spots, export_time, self.sequence, self.domain);
+            socket.send_to(&datagram, &settings.host)?;
+            self.sequence = self.sequence.wrapping_add(spots.len() as u32);
+        }
+        self.status = Some(format!("Reported {} spots", pending.len()));
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn append(path: &str, line: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+    use wasm_timer::UNIX_EPOCH;
+
+    use super::*;
+    use crate::message::{DecoderEvent, RttyMessage};
+
+    #[test]
+    fn test_from_entry() {
+        let entry = LogEntry {
+            time: UNIX_EPOCH,
+            mode: "RTTY".to_string(),
+            frequency: Some(14085000.0),
+            snr: Some(12.0),
+            payload: "K1ABC DE W1AW UR RST 5NN QTH FN31PR".to_string(),
+        };
+        let spot = Spot::from_entry(&entry).unwrap();
+        assert_eq!(spot.callsign, "W1AW");
+        assert_eq!(spot.grid.as_deref(), Some("FN31PR"));
+        assert_eq!(spot.report.as_deref(), Some("599"));
+        assert_eq!(spot.snr, Some(12.0));
+
+        let unknown = LogEntry {
+            frequency: None,
+            ..entry.clone()
+        };
+        assert_eq!(Spot::from_entry(&unknown), None);
+        let nobody = LogEntry {
+            payload: "RYRYRYRY".to_string(),
+            ..entry
+        };
+        assert_eq!(Spot::from_entry(&nobody), None);
+    }
+
+    #[test]
+    fn test_spotter_waits_for_station() {
+        let mut config = Configuration::default();
+        config.spots.enabled = true;
+        config.spots.cluster_path.clear();
+        let (tx, rx) = mpsc::channel(16);
+        let mut messages = MessageCollector::new(rx);
+        let mut spotter = Spotter::default();
+        let mut receive = |config: &mut Configuration, spotter: &mut Spotter| {
+            let message = RttyMessage::new("CQ CQ DE W1AW W1AW K".to_string())
+                .with_frequency(config.display_frequency(config.tuner.carrier()));
+            tx.try_send(DecoderEvent::Message(Box::new(message)))
+                .unwrap();
+            messages.run(config);
+            spotter.run(&messages, config);
+        };
+
+        receive(&mut config, &mut spotter);
+        assert_eq!(
+            spotter.status.as_deref(),
+            Some("Set a callsign to report spots")
+        );
+
+        config.station.callsign = "K1ABC".to_string();
+        receive(&mut config, &mut spotter);
+        assert_eq!(
+            spotter.status.as_deref(),
+            Some("Set the dial or center frequency to report spots")
+        );
+
+        // Heard before, but never spotted.
+        config.dial_frequency = 14_080_000.0;
+        receive(&mut config, &mut spotter);
+        assert_eq!(spotter.status.as_deref(), Some("Spotted W1AW"));
+    }
+}
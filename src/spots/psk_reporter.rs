@@ -0,0 +1,201 @@
+//! Reception reports in the IPFIX format PSK Reporter takes over UDP, as
+//! described at <https://pskreporter.info/pskdev.html>.
+
+use wasm_timer::UNIX_EPOCH;
+
+use crate::spots::Spot;
+
+const VERSION: u16 = 10;
+const TEMPLATE_SET: u16 = 2;
+const OPTIONS_TEMPLATE_SET: u16 = 3;
+const RECEIVER_TEMPLATE: u16 = 0x9992;
+const SENDER_TEMPLATE: u16 = 0x9993;
+// PSK Reporter's own fields are numbered under its enterprise number.
+const ENTERPRISE: u32 = 30351;
+const VARIABLE: u16 = 0xffff;
+
+// Our spots were picked out of decoded text by software.
+const AUTOMATIC: u8 = 1;
+
+/// Who heard the spots.
+pub struct Receiver<'a> {
+    pub callsign: &'a str,
+    pub locator: &'a str,
+    pub software: &'a str,
+}
+
+/// One datagram reporting `spots`. The templates go in every datagram so
+/// that a collector can make sense of any of them. `sequence` counts the
+/// spots sent before these, and `domain` stays the same for a session.
+pub fn datagram(
+    receiver: &Receiver,
+    spots: &[Spot],
+    export_time: u32,
+    sequence: u32,
+    domain: u32,
+) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend(receiver_template());
+    body.extend(sender_template());
+
+    let mut record = Vec::new();
+    string(&mut record, receiver.callsign);
+    string(&mut record, receiver.locator);
+    string(&mut record, receiver.software);
+    body.extend(set(RECEIVER_TEMPLATE, &record));
+
+    if !spots.is_empty() {
+        let mut records = Vec::new();
+        for spot in spots {
+            string(&mut records, &spot.callsign);
+            records.extend((spot.frequency.round() as u32).to_be_bytes());
+            let snr = spot.snr.unwrap_or(0.0).round().clamp(-128.0, 127.0) as i8;
+            records.extend(snr.to_be_bytes());
+            string(&mut records, &spot.mode);
+            string(&mut records, spot.grid.as_deref().unwrap_or(""));
+            records.push(AUTOMATIC);
+            records.extend(seconds(spot).to_be_bytes());
+        }
+        body.extend(set(SENDER_TEMPLATE, &records));
+    }
+
+    let mut datagram = Vec::with_capacity(16 + body.len());
+    datagram.extend(VERSION.to_be_bytes());
+    datagram.extend(((16 + body.len()) as u16).to_be_bytes());
+    datagram.extend(export_time.to_be_bytes());
+    datagram.extend(sequence.to_be_bytes());
+    datagram.extend(domain.to_be_bytes());
+    datagram.extend(body);
+    datagram
+}
+
+// Callsign, locator and software, all of them strings.
+fn receiver_template() -> Vec<u8> {
+    let mut template = Vec::new();
+    template.extend(RECEIVER_TEMPLATE.to_be_bytes());
+    template.extend(3_u16.to_be_bytes());
+    // No scope fields.
+    template.extend(0_u16.to_be_bytes());
+    for field in [0x8002, 0x8004, 0x8008] {
+        template.extend(enterprise_field(field, VARIABLE));
+    }
+    set(OPTIONS_TEMPLATE_SET, &template)
+}
+
+// Callsign, frequency, SNR, mode, locator, information source and time.
+fn sender_template() -> Vec<u8> {
+    let mut template = Vec::new();
+    template.extend(SENDER_TEMPLATE.to_be_bytes());
+    template.extend(7_u16.to_be_bytes());
+    template.extend(enterprise_field(0x8001, VARIABLE));
+    template.extend(enterprise_field(0x8005, 4));
+    template.extend(enterprise_field(0x8006, 1));
+    template.extend(enterprise_field(0x800a, VARIABLE));
+    template.extend(enterprise_field(0x8003, VARIABLE));
+    template.extend(enterprise_field(0x800b, 1));
+    // IPFIX's own flowStartSeconds.
+    template.extend(150_u16.to_be_bytes());
+    template.extend(4_u16.to_be_bytes());
+    set(TEMPLATE_SET, &template)
+}
+
+fn enterprise_field(id: u16, length: u16) -> Vec<u8> {
+    let mut field = Vec::new();
+    field.extend(id.to_be_bytes());
+    field.extend(length.to_be_bytes());
+    field.extend(ENTERPRISE.to_be_bytes());
+    field
+}
+
+// Padded to a multiple of four bytes.
+fn set(id: u16, contents: &[u8]) -> Vec<u8> {
+    let padding = (4 - contents.len() % 4) % 4;
+    let mut set = Vec::new();
+    set.extend(id.to_be_bytes());
+    set.extend(((4 + contents.len() + padding) as u16).to_be_bytes());
+    set.extend(contents);
+    set.extend(vec![0; padding]);
+    set
+}
+
+// Prefixed with its length, in one byte.
+fn string(record: &mut Vec<u8>, s: &str) {
+    let bytes = &s.as_bytes()[..s.len().min(254)];
+    record.push(bytes.len() as u8);
+    record.extend(bytes);
+}
+
+fn seconds(spot: &Spot) -> u32 {
+    spot.time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_datagram() {
+        let receiver = Receiver {
+            callsign: "K1ABC",
+            locator: "FN42",
+            software: "waterfall-rs",
+        };
+        let spot = Spot {
+            time: UNIX_EPOCH + Duration::from_secs(0x12345678),
+            callsign: "W1AW".to_string(),
+            frequency: 14085000.0,
+            mode: "RTTY".to_string(),
+            snr: Some(-7.4),
+            grid: Some("FN31PR".to_string()),
+            report: None,
+        };
+        let datagram = datagram(&receiver, &[spot], 1, 2, 3);
+
+        assert_eq!(datagram.len() % 4, 0);
+        assert_eq!(&datagram[..2], &[0x00, 0x0a]);
+        assert_eq!(
+            u16::from_be_bytes([datagram[2], datagram[3]]) as usize,
+            datagram.len()
+        );
+        assert_eq!(&datagram[4..16], &[0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3]);
+
+        // As PSK Reporter's documentation gives it.
+        let receiver_template = [
+            0x00, 0x03, 0x00, 0x24, 0x99, 0x92, 0x00, 0x03, 0x00, 0x00, //
+            0x80, 0x02, 0xff, 0xff, 0x00, 0x00, 0x76, 0x8f, //
+            0x80, 0x04, 0xff, 0xff, 0x00, 0x00, 0x76, 0x8f, //
+            0x80, 0x08, 0xff, 0xff, 0x00, 0x00, 0x76, 0x8f, //
+            0x00, 0x00,
+        ];
+        assert_eq!(&datagram[16..52], &receiver_template);
+
+        let sender_template = &datagram[52..52 + 60];
+        assert_eq!(
+            &sender_template[..8],
+            &[0x00, 0x02, 0x00, 0x3c, 0x99, 0x93, 0x00, 0x07]
+        );
+
+        let records = &datagram[112..];
+        assert_eq!(&records[..4], &[0x99, 0x92, 0x00, 0x1c]);
+        assert_eq!(&records[4..10], b"\x05K1ABC");
+
+        let sender = &records[0x1c..];
+        assert_eq!(&sender[..2], &[0x99, 0x93]);
+        let record = [
+            b"\x04W1AW".as_slice(),
+            &14085000_u32.to_be_bytes(),
+            &[(-7_i8) as u8],
+            b"\x04RTTY",
+            b"\x06FN31PR",
+            &[AUTOMATIC],
+            &0x12345678_u32.to_be_bytes(),
+        ]
+        .concat();
+        assert_eq!(&sender[4..4 + record.len()], record.as_slice());
+    }
+}
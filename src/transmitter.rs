@@ -0,0 +1,78 @@
+use crate::configuration::Configuration;
+use crate::dsp::encode::RttyEncoder;
+use crate::output::{write_wav, AudioOutput};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransmitRequest {
+    Play,
+    SaveWav,
+}
+
+/// Turns the text typed into the messages window into RTTY audio, either on
+/// the default output device or in a WAV file.
+pub struct Transmitter {
+    pub text: String,
+    pub wav_path: String,
+    pub request: Option<TransmitRequest>,
+    pub status: Option<String>,
+    output: Option<AudioOutput>,
+}
+
+impl Default for Transmitter {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            wav_path: "transmit.wav".to_owned(),
+            request: None,
+            status: None,
+            output: None,
+        }
+    }
+}
+
+impl Transmitter {
+    pub fn run(&mut self, config: &Configuration) {
+        let request = match self.request.take() {
+            Some(request) => request,
+            None => return,
+        };
+
+        // The decoder emits a message on a line break.
+        let text = format!("{}\r\n", self.text);
+
+        match request {
+            TransmitRequest::Play => {
+                if self.output.is_none() {
+                    match AudioOutput::new() {
+                        Ok(output) => self.output = Some(output),
+                        Err(err) => {
+                            self.status = Some(err);
+                            return;
+                        }
+                    }
+                }
+
+                if let Some(output) = &self.output {
+                    let mut encoder = RttyEncoder::from_tuner(&config.tuner, output.sample_rate());
+                    output.play(encoder.encode(&text));
+                    self.text.clear();
+                    self.status = None;
+                }
+            }
+            TransmitRequest::SaveWav => {
+                let sample_rate = config.audio_sample_rate.as_frequency();
+                let mut encoder = RttyEncoder::from_tuner(&config.tuner, sample_rate);
+                let samples = encoder.encode(&text);
+
+                self.status = Some(match write_wav(&self.wav_path, &samples, sample_rate) {
+                    Ok(()) => format!("Wrote {}", self.wav_path),
+                    Err(err) => err.to_string(),
+                });
+            }
+        }
+    }
+
+    pub fn is_transmitting(&self) -> bool {
+        matches!(&self.output, Some(output) if output.is_playing())
+    }
+}
@@ -1,2 +1,11 @@
+use rustfft::num_complex::Complex;
+
 pub type SampleType = f32;
+pub type ComplexType = Complex<SampleType>;
 pub const PLOT_DEPTH: usize = 256;
+
+/// A block of input. Audio is real, SDR front ends deliver I/Q.
+pub enum Samples {
+    Real(Vec<SampleType>),
+    Complex(Vec<ComplexType>),
+}
@@ -1,7 +1,7 @@
 use egui::*;
 
 use crate::configuration::Configuration;
-use crate::units::Frequency;
+use crate::units::{format_hertz, Frequency};
 
 pub struct FilterCutoffLower<'a> {
     f: &'a mut f32,
@@ -178,6 +178,9 @@ impl<'a> Carrier<'a> {
                     *self.f = hz.value();
                 }
             }
+
+            let rf = self.config.display_frequency(Frequency::Hertz(*self.f));
+            response.on_hover_text(format_hertz(rf));
         });
     }
 }
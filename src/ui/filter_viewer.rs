@@ -0,0 +1,112 @@
+use egui::plot::{Legend, Line, Plot, Value, Values};
+
+use crate::configuration::Configuration;
+use crate::dsp::response::FilterTaps;
+
+const POINTS: usize = 512;
+const PLOT_HEIGHT: f32 = 150.0;
+
+/// Magnitude, phase and group delay of the filters in front of the decoder,
+/// with the input spectrum behind the magnitude.
+pub struct FilterViewer<'a> {
+    config: &'a mut Configuration,
+    filters: &'a [FilterTaps],
+    spectrum: &'a [f32],
+}
+
+impl<'a> FilterViewer<'a> {
+    pub fn new(
+        config: &'a mut Configuration,
+        filters: &'a [FilterTaps],
+        spectrum: &'a [f32],
+    ) -> Self {
+        Self {
+            config,
+            filters,
+            spectrum,
+        }
+    }
+
+    /// The spectrum, where it is within the filter's band.
+    fn spectrum_line(&self, nyquist: f32) -> Line {
+        let sample_rate = self.config.audio_sample_rate.as_frequency().value();
+        let bin_hz = sample_rate / (2 * (self.spectrum.len().max(2) - 1)) as f32;
+        let values = self
+            .spectrum
+            .iter()
+            .enumerate()
+            .map(|(i, db)| Value::new(i as f32 * bin_hz, *db))
+            .take_while(|value| value.x <= nyquist as f64);
+        Line::new(Values::from_values_iter(values)).name("Spectrum")
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        let settings = &mut self.config.filter_response;
+        egui::ComboBox::from_label("Filter")
+            .selected_text(settings.filter.clone())
+            .show_ui(ui, |ui| {
+                for filter in self.filters {
+                    ui.selectable_value(&mut settings.filter, filter.name.clone(), &filter.name);
+                }
+            });
+        ui.checkbox(&mut settings.spectrum, "Show spectrum");
+
+        let filter = match self
+            .filters
+            .iter()
+            .find(|filter| filter.name == self.config.filter_response.filter)
+        {
+            Some(filter) => filter,
+            None => {
+                ui.label("This filter is not in use.");
+                return;
+            }
+        };
+        ui.label(format!("{} at {}", filter.kernel, filter.sample_rate));
+
+        let response = filter.response(POINTS);
+        let line = |ys: &[f32], scale: f32| {
+            let values = response
+                .frequency
+                .iter()
+                .zip(ys)
+                .map(|(x, y)| Value::new(*x, y * scale));
+            Line::new(Values::from_values_iter(values))
+        };
+
+        // The input's spectrum, cut off at the filter's Nyquist frequency.
+        // The waterfall only keeps it for real input, so there's none to
+        // show for complex input.
+        let spectrum = if self.config.filter_response.spectrum && !self.spectrum.is_empty() {
+            Some(self.spectrum_line(filter.sample_rate.value() / 2.0))
+        } else {
+            None
+        };
+
+        ui.label("Magnitude (dB)");
+        Plot::new("Filter magnitude")
+            .height(PLOT_HEIGHT)
+            .include_y(0.0)
+            .include_y(-100.0)
+            .legend(Legend::default())
+            .show(ui, |plot_ui| {
+                if let Some(spectrum) = spectrum {
+                    plot_ui.line(spectrum);
+                }
+                plot_ui.line(line(&response.magnitude, 1.0).name(&filter.name));
+            });
+
+        ui.label("Phase (rad)");
+        Plot::new("Filter phase")
+            .height(PLOT_HEIGHT)
+            .show(ui, |plot_ui| plot_ui.line(line(&response.phase, 1.0)));
+
+        ui.label("Group delay (ms)");
+        Plot::new("Filter group delay")
+            .height(PLOT_HEIGHT)
+            .include_y(0.0)
+            .show(ui, |plot_ui| {
+                plot_ui.line(line(&response.group_delay, 1000.0))
+            });
+    }
+}
@@ -1,23 +1,320 @@
-use crate::messages::MessageCollector;
+use egui::{Color32, RichText, Sense};
+
+use crate::configuration::{Configuration, Highlight, MessageSettings};
+use crate::message_filter::{channels, modes};
+use crate::message_log::{format_time, LogEntry};
+use crate::messages::{ExportFormat, MessageCollector};
+use crate::transmitter::{TransmitRequest, Transmitter};
+use crate::units::format_hertz;
+
+const COLUMNS: [&str; 5] = ["Time", "Mode", "Frequency", "SNR", "Text"];
 
 pub struct Messages<'a> {
-    data: &'a MessageCollector,
+    data: &'a mut MessageCollector,
+    transmitter: &'a mut Transmitter,
+    config: &'a mut Configuration,
 }
 
 impl<'a> Messages<'a> {
-    pub fn new(data: &'a MessageCollector) -> Self {
-        Self { data }
+    pub fn new(
+        data: &'a mut MessageCollector,
+        transmitter: &'a mut Transmitter,
+        config: &'a mut Configuration,
+    ) -> Self {
+        Self {
+            data,
+            transmitter,
+            config,
+        }
     }
 
     pub fn ui(&mut self, ui: &mut egui::Ui) {
-        egui::ScrollArea::vertical()
-            .show(ui, |ui| {
-                if self.data.data.len() == 0 {
-                    ui.label("Received messages will appear here.");
+        self.filter_ui(ui);
+        self.table_ui(ui);
+
+        // There is no output device or file system in the browser.
+        #[cfg(not(target_arch = "wasm32"))]
+        self.transmit_ui(ui);
+        #[cfg(not(target_arch = "wasm32"))]
+        self.export_ui(ui);
+    }
+
+    fn filter_ui(&mut self, ui: &mut egui::Ui) {
+        let settings = &mut self.config.messages;
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut settings.search)
+                    .hint_text("Search (regular expression)"),
+            );
+            if ui.button("✖").on_hover_text("Clear the search").clicked() {
+                settings.search.clear();
+            }
+        });
+
+        egui::CollapsingHeader::new("Filters").show(ui, |ui| {
+            let modes = modes(&self.data.data);
+            egui::ComboBox::from_label("Mode")
+                .selected_text(settings.mode.as_deref().unwrap_or("All"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut settings.mode, None, "All");
+                    for mode in modes {
+                        ui.selectable_value(&mut settings.mode, Some(mode.clone()), mode);
+                    }
+                });
+
+            let channels = channels(&self.data.data);
+            let channel_text = |channel: Option<f64>| match channel {
+                Some(hz) => format_hertz(hz),
+                None => "All".to_string(),
+            };
+            egui::ComboBox::from_label("Channel")
+                .selected_text(channel_text(settings.channel))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut settings.channel, None, "All");
+                    for channel in channels.iter().copied() {
+                        ui.selectable_value(
+                            &mut settings.channel,
+                            Some(channel),
+                            channel_text(Some(channel)),
+                        );
+                    }
+                });
+
+            ui.horizontal(|ui| {
+                let mut enabled = settings.frequency_range.is_some();
+                if ui.checkbox(&mut enabled, "Between").changed() {
+                    settings.frequency_range = match (enabled, channels.first(), channels.last()) {
+                        (false, _, _) => None,
+                        (true, Some(lower), Some(upper)) => Some((*lower, *upper)),
+                        (true, _, _) => Some((0.0, 30e6)),
+                    };
                 }
-                for message in self.data.data.iter() {
-                    ui.label(format!("{}: {}", message.mode(), message.payload()));
+                if let Some((lower, upper)) = &mut settings.frequency_range {
+                    ui.add(
+                        egui::DragValue::new(lower)
+                            .speed(100.0)
+                            .clamp_range(0.0..=*upper)
+                            .suffix(" Hz"),
+                    );
+                    ui.label("and");
+                    ui.add(
+                        egui::DragValue::new(upper)
+                            .speed(100.0)
+                            .clamp_range(*lower..=f64::MAX)
+                            .suffix(" Hz"),
+                    );
                 }
             });
+        });
+
+        egui::CollapsingHeader::new("Highlights").show(ui, |ui| {
+            highlights_ui(ui, settings, &mut self.config.station.callsign);
+        });
+    }
+
+    fn table_ui(&mut self, ui: &mut egui::Ui) {
+        let settings = &mut self.config.messages;
+        let filter = match self
+            .data
+            .filter
+            .get(settings, &self.config.station.callsign)
+        {
+            Ok(filter) => Some(filter),
+            Err(err) => {
+                ui.colored_label(Color32::RED, err.to_string());
+                None
+            }
+        };
+        let shown: Vec<&LogEntry> = self
+            .data
+            .data
+            .iter()
+            .filter(|entry| match &filter {
+                Some(filter) => filter.matches(entry),
+                None => true,
+            })
+            .collect();
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut settings.follow, "Follow")
+                .on_hover_text("Keep the newest message in view");
+            ui.label(format!("{} of {}", shown.len(), self.data.data.len()));
+            if ui
+                .button("Copy")
+                .on_hover_text("Copy the messages shown")
+                .clicked()
+            {
+                let rows: Vec<String> = shown.iter().map(|entry| row_text(entry)).collect();
+                ui.output().copied_text = rows.join("\n");
+            }
+        });
+
+        if self.data.data.is_empty() {
+            ui.label("Received messages will appear here.");
+            return;
+        }
+        if shown.is_empty() {
+            ui.label("No messages match.");
+            return;
+        }
+
+        let scroll_area = egui::ScrollArea::vertical();
+        let scroll_area = if settings.follow {
+            scroll_area.stick_to_bottom()
+        } else {
+            scroll_area
+        };
+        // Only the rows in view are laid out, which takes every row being the
+        // same height, so line breaks in the text are shown as spaces.
+        let row_height = ui.spacing().interact_size.y;
+        scroll_area.show_rows(ui, row_height, shown.len() + 1, |ui, rows| {
+            egui::Grid::new("messages")
+                .num_columns(COLUMNS.len())
+                .min_row_height(row_height)
+                .striped(true)
+                .show(ui, |ui| {
+                    for row in rows {
+                        // The header is the first row.
+                        let entry = match row.checked_sub(1) {
+                            Some(i) => shown[i],
+                            None => {
+                                for column in COLUMNS {
+                                    ui.strong(column);
+                                }
+                                ui.end_row();
+                                continue;
+                            }
+                        };
+
+                        let color = filter.and_then(|filter| filter.highlight(entry));
+                        let cell = |text: String| match color {
+                            Some([r, g, b]) => {
+                                RichText::new(text).color(Color32::from_rgb(r, g, b))
+                            }
+                            None => RichText::new(text),
+                        };
+
+                        ui.label(cell(format_time(entry.time)));
+                        ui.label(cell(entry.mode.clone()));
+                        ui.label(cell(frequency_text(entry)));
+                        ui.label(cell(snr_text(entry)));
+                        let text = entry.payload.lines().collect::<Vec<_>>().join(" ");
+                        ui.add(
+                            egui::Label::new(cell(text))
+                                .wrap(false)
+                                .sense(Sense::click()),
+                        )
+                        .on_hover_text("Right click to copy")
+                        .context_menu(|ui| {
+                            if ui.button("Copy text").clicked() {
+                                ui.output().copied_text = entry.payload.clone();
+                                ui.close_menu();
+                            }
+                            if ui.button("Copy row").clicked() {
+                                ui.output().copied_text = row_text(entry);
+                                ui.close_menu();
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_ui(&mut self, ui: &mut egui::Ui) {
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.config.log.export_path);
+            if ui.button("Export CSV").clicked() {
+                self.data.export = Some(ExportFormat::Csv);
+            }
+            if ui.button("Export ADIF").clicked() {
+                self.data.export = Some(ExportFormat::Adif);
+            }
+        });
+        if let Some(status) = &self.data.status {
+            ui.label(status);
+        }
     }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn transmit_ui(&mut self, ui: &mut egui::Ui) {
+        ui.separator();
+        ui.horizontal(|ui| {
+            let response = ui.text_edit_singleline(&mut self.transmitter.text);
+            let enter = response.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+
+            let transmitting = self.transmitter.is_transmitting();
+            let button = ui.add_enabled(!transmitting, egui::Button::new("Transmit"));
+            if (button.clicked() || enter) && !transmitting {
+                self.transmitter.request = Some(TransmitRequest::Play);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.transmitter.wav_path);
+            if ui.button("Save WAV").clicked() {
+                self.transmitter.request = Some(TransmitRequest::SaveWav);
+            }
+        });
+        if let Some(status) = &self.transmitter.status {
+            ui.label(status);
+        }
+    }
+}
+
+fn highlights_ui(ui: &mut egui::Ui, settings: &mut MessageSettings, callsign: &mut String) {
+    ui.horizontal(|ui| {
+        ui.color_edit_button_srgb(&mut settings.callsign_color);
+        ui.add(egui::TextEdit::singleline(callsign).hint_text("Your callsign"));
+    });
+
+    let mut removed = None;
+    for (i, highlight) in settings.highlights.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.color_edit_button_srgb(&mut highlight.color);
+            ui.add(
+                egui::TextEdit::singleline(&mut highlight.pattern).hint_text("Regular expression"),
+            );
+            if ui.button("✖").clicked() {
+                removed = Some(i);
+            }
+        });
+    }
+    if let Some(i) = removed {
+        settings.highlights.remove(i);
+    }
+
+    if ui.button("Add").clicked() {
+        settings.highlights.push(Highlight {
+            pattern: String::new(),
+            color: [0xff, 0xff, 0x60],
+        });
+    }
+}
+
+fn frequency_text(entry: &LogEntry) -> String {
+    match entry.frequency {
+        Some(frequency) => format_hertz(frequency),
+        None => String::new(),
+    }
+}
+
+fn snr_text(entry: &LogEntry) -> String {
+    match entry.snr {
+        Some(snr) => format!("{:+.0} dB", snr),
+        None => String::new(),
+    }
+}
+
+// Tab separated, so it pastes into a spreadsheet.
+fn row_text(entry: &LogEntry) -> String {
+    [
+        format_time(entry.time),
+        entry.mode.clone(),
+        frequency_text(entry),
+        snr_text(entry),
+        entry.payload.clone(),
+    ]
+    .join("\t")
 }
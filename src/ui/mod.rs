@@ -1,18 +1,22 @@
 mod about;
 mod bump;
 mod filter_cutoff;
+mod filter_viewer;
 mod messages;
 mod scope_viewer;
 mod settings;
+mod text_view;
 mod toolbar;
 mod waterfall_plot;
 mod waterfall_ticks;
 mod windows;
 
 pub use about::About;
+pub use filter_viewer::FilterViewer;
 pub use messages::Messages;
 pub use scope_viewer::ScopeViewer;
 pub use settings::Settings;
+pub use text_view::TextView;
 pub use toolbar::Toolbar;
 pub use waterfall_plot::WaterfallPlot;
 pub use waterfall_ticks::WaterfallTicks;
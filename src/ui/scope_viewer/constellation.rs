@@ -0,0 +1,67 @@
+use egui::plot::{HLine, LineStyle, MarkerShape, Plot, Points, VLine, Value, Values};
+use egui::Color32;
+
+use crate::scope::Scope;
+
+const POINT_COLOR: Color32 = Color32::from_rgb(0x60, 0xff, 0x60);
+const REFERENCE_COLOR: Color32 = Color32::from_rgb(0xff, 0x80, 0x00);
+
+/// Recovered PSK symbols, I against Q, with where they ought to land.
+pub struct ConstellationPlot<'a> {
+    scope: &'a mut Scope,
+}
+
+impl<'a> ConstellationPlot<'a> {
+    pub fn new(scope: &'a mut Scope) -> Self {
+        Self { scope }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        let constellation = self.scope.constellation();
+        let points = Points::new(Values::from_values_iter(
+            constellation
+                .points()
+                .map(|point| Value::new(point.re, point.im)),
+        ))
+        .color(POINT_COLOR)
+        .radius(2.0);
+        let reference = Points::new(Values::from_values(vec![
+            Value::new(-1.0, 0.0),
+            Value::new(1.0, 0.0),
+        ]))
+        .shape(MarkerShape::Cross)
+        .color(REFERENCE_COLOR)
+        .radius(6.0);
+
+        Plot::new("Constellation")
+            .height(300.0)
+            .data_aspect(1.0)
+            .include_x(-1.5)
+            .include_x(1.5)
+            .include_y(-1.5)
+            .include_y(1.5)
+            .show(ui, |plot_ui| {
+                plot_ui.vline(VLine::new(0.0).style(LineStyle::dotted_dense()));
+                plot_ui.hline(HLine::new(0.0).style(LineStyle::dotted_dense()));
+                plot_ui.points(points);
+                plot_ui.points(reference);
+            });
+
+        let label = match constellation.stats() {
+            Some(stats) => format!(
+                "{} symbols   Phase error {:.1}\u{b0}   EVM {:.1}%   MER {:.1} dB",
+                constellation.points().count(),
+                stats.phase_error,
+                stats.evm,
+                stats.mer
+            ),
+            None => "Waiting for symbols".to_string(),
+        };
+        ui.horizontal(|ui| {
+            ui.label(label);
+            if ui.button("Clear").clicked() {
+                self.scope.clear_constellation();
+            }
+        });
+    }
+}
@@ -0,0 +1,86 @@
+use egui::plot::{HLine, Line, LineStyle, Plot, VLine, Value, Values};
+use egui::Color32;
+
+use crate::scope::Scope;
+use crate::units::Frequency;
+
+// Faint, so that where segments pile up stands out.
+const SEGMENT_COLOR: Color32 = Color32::from_rgba_premultiplied(40, 40, 0, 40);
+const MARKER_COLOR: Color32 = Color32::from_rgb(0xff, 0x80, 0x00);
+
+/// Overlaid symbols from the discriminator, in ms from where the decoder
+/// sampled each bit.
+pub struct EyePlot<'a> {
+    scope: &'a mut Scope,
+}
+
+impl<'a> EyePlot<'a> {
+    pub fn new(scope: &'a mut Scope) -> Self {
+        Self { scope }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        let eye = match self.scope.eye() {
+            Some(eye) => eye,
+            None => {
+                ui.label("Waiting for the decoder");
+                return;
+            }
+        };
+
+        let ms_per_sample = 1000.0 / eye.sample_rate.value() as f64;
+        let half_width = eye.half_width as f64;
+        let lines: Vec<Line> =
+            eye.segments()
+                .map(|segment| {
+                    let values = segment.iter().enumerate().map(|(i, y)| {
+                        Value::new((i as f64 - half_width) * ms_per_sample, *y as f64)
+                    });
+                    Line::new(Values::from_values_iter(values)).color(SEGMENT_COLOR)
+                })
+                .collect();
+        // Bits change halfway between sampling points, if the timing is right.
+        let edge = half_width * ms_per_sample / 2.0;
+
+        Plot::new("Eye")
+            .height(300.0)
+            .include_x(-2.0 * edge)
+            .include_x(2.0 * edge)
+            .show(ui, |plot_ui| {
+                for line in lines {
+                    plot_ui.line(line);
+                }
+                plot_ui.vline(VLine::new(0.0).color(MARKER_COLOR).name("Sampling point"));
+                for x in [-edge, edge] {
+                    plot_ui.vline(
+                        VLine::new(x)
+                            .color(MARKER_COLOR)
+                            .style(LineStyle::dashed_dense()),
+                    );
+                }
+                plot_ui.hline(
+                    HLine::new(0.0)
+                        .color(MARKER_COLOR)
+                        .style(LineStyle::dotted_dense()),
+                );
+            });
+
+        let baud = Frequency::Hertz(eye.sample_rate.value() / eye.half_width as f32);
+        let opening = match eye.opening() {
+            Some(opening) => format!("{:.2}", opening),
+            None => "-".to_string(),
+        };
+        let label = format!(
+            "{} symbols at {}   Eye opening {}",
+            eye.segments().count(),
+            baud,
+            opening
+        );
+        ui.horizontal(|ui| {
+            ui.label(label);
+            if ui.button("Clear").clicked() {
+                self.scope.clear_eye();
+            }
+        });
+    }
+}
@@ -1,39 +1,189 @@
 mod buttons;
+mod constellation;
+mod eye;
 mod settings;
 
-use egui::plot::{Line, Plot, Values};
+use egui::plot::{uniform_grid_spacer, HLine, Legend, Line, LineStyle, Plot, VLine, Value, Values};
+use egui::{Color32, Vec2};
 
-use crate::configuration::Configuration;
-use crate::types::SampleType;
+use crate::configuration::{AxisMode, Configuration, Probe, ScopeSettings, ScopeView};
+use crate::scope::{Scope, Trace};
+use crate::units::Frequency;
 use buttons::Buttons;
+use constellation::ConstellationPlot;
+use eye::EyePlot;
 use settings::Settings;
 
+const CURSOR_COLOR: Color32 = Color32::from_rgb(0xff, 0x80, 0x00);
+
 pub struct ScopeViewer<'a> {
-    plot_data: &'a [SampleType],
+    scope: &'a mut Scope,
     config: &'a mut Configuration,
 }
 
 impl<'a> ScopeViewer<'a> {
-    pub fn new(config: &'a mut Configuration, plot_data: &'a [SampleType]) -> Self {
-        Self { config, plot_data }
+    pub fn new(config: &'a mut Configuration, scope: &'a mut Scope) -> Self {
+        Self { config, scope }
     }
 
     pub fn ui(&mut self, ui: &mut egui::Ui) {
         let mut buttons = Buttons::new(self.config);
         buttons.ui(ui);
 
-        let line = Line::new(Values::from_ys_f32(self.plot_data));
-        Plot::new("Scope")
-            // .view_aspect(1.732)
-            .center_y_axis(true)
-            // .include_y(1.0)
-            // .include_y(-1.0)
-            // .include_x(450.0)
-            .height(300.0)
-            // .width(400.0)
-            .show(ui, |plot_ui| plot_ui.line(line));
+        match self.config.scope.view {
+            ScopeView::Time => self.time_ui(ui),
+            ScopeView::Eye => EyePlot::new(self.scope).ui(ui),
+            ScopeView::Constellation => ConstellationPlot::new(self.scope).ui(ui),
+        }
 
         let mut settings = Settings::new(self.config);
         settings.ui(ui);
     }
+
+    fn time_ui(&mut self, ui: &mut egui::Ui) {
+        let traces = self.scope.traces();
+        // Traces can be at different rates, so they share a time axis, in
+        // ms from the trigger.
+        let lines: Vec<Line> = traces
+            .iter()
+            .map(|trace| {
+                let start = trace.start as f64 * 1000.0;
+                let ms_per_sample = 1000.0 / trace.sample_rate.value() as f64;
+                let values = trace
+                    .samples
+                    .iter()
+                    .enumerate()
+                    .map(|(i, y)| Value::new(start + i as f64 * ms_per_sample, *y as f64));
+                Line::new(Values::from_values_iter(values))
+                    .name(trace.probe.name())
+                    .color(color(trace.probe))
+            })
+            .collect();
+
+        let scope = &self.config.scope;
+        let (x_min, x_max) = scope
+            .x_bounds()
+            .or_else(|| time_extent(traces))
+            .unwrap_or((0.0, scope.timebase * ScopeSettings::X_DIVISIONS as f32));
+        let (y_min, y_max) = scope.y_bounds().unwrap_or_else(|| {
+            let peak = level_peak(traces).max(1e-6);
+            (-peak, peak)
+        });
+        // Set limits are kept to exactly, so divisions line up with the grid.
+        let margin = |bounds: Option<(f32, f32)>| if bounds.is_some() { 0.0 } else { 0.05 };
+
+        let mut plot = Plot::new("Scope")
+            .include_x(x_min)
+            .include_x(x_max)
+            .include_y(y_min)
+            .include_y(y_max)
+            .set_margin_fraction(Vec2::new(
+                margin(scope.x_bounds()),
+                margin(scope.y_bounds()),
+            ))
+            .height(300.0)
+            .legend(Legend::default());
+        if scope.x_mode == AxisMode::Divisions {
+            let division = scope.timebase as f64;
+            plot = plot.x_grid_spacer(uniform_grid_spacer(move |_| {
+                [division / 5.0, division, 5.0 * division]
+            }));
+        }
+        if scope.y_mode == AxisMode::Divisions {
+            let division = scope.scale as f64;
+            plot = plot.y_grid_spacer(uniform_grid_spacer(move |_| {
+                [division / 5.0, division, 4.0 * division]
+            }));
+        }
+
+        let cursors = &mut self.config.scope.cursors;
+        plot.show(ui, |plot_ui| {
+            for line in lines {
+                plot_ui.line(line);
+            }
+
+            if cursors.enabled {
+                // A click moves whichever cursor is closer in time.
+                if plot_ui.plot_clicked() {
+                    if let Some(pointer) = plot_ui.pointer_coordinate() {
+                        let (x, y) = (pointer.x as f32, pointer.y as f32);
+                        let i = if (cursors.times[0] - x).abs() <= (cursors.times[1] - x).abs() {
+                            0
+                        } else {
+                            1
+                        };
+                        cursors.times[i] = x;
+                        cursors.levels[i] = y;
+                    }
+                }
+                for time in cursors.times {
+                    plot_ui.vline(
+                        VLine::new(time)
+                            .color(CURSOR_COLOR)
+                            .style(LineStyle::dashed_dense()),
+                    );
+                }
+                for level in cursors.levels {
+                    plot_ui.hline(
+                        HLine::new(level)
+                            .color(CURSOR_COLOR)
+                            .style(LineStyle::dashed_dense()),
+                    );
+                }
+            }
+        });
+
+        if cursors.enabled {
+            let dt = (cursors.times[1] - cursors.times[0]).abs();
+            let dv = (cursors.levels[1] - cursors.levels[0]).abs();
+            let frequency = if dt > 0.0 {
+                format!("{}", Frequency::Hertz(1000.0 / dt))
+            } else {
+                "-".to_string()
+            };
+            ui.label(format!(
+                "\u{394}t {:.3} ms ({})   \u{394}V {:.4}",
+                dt, frequency, dv
+            ));
+        }
+    }
+}
+
+// Each probe keeps its colour, whichever others are shown with it.
+fn color(probe: Probe) -> Color32 {
+    const COLORS: [Color32; 11] = [
+        Color32::LIGHT_GRAY,
+        Color32::LIGHT_BLUE,
+        Color32::LIGHT_GREEN,
+        Color32::RED,
+        Color32::BLUE,
+        Color32::LIGHT_RED,
+        Color32::from_rgb(0x60, 0x90, 0xff),
+        Color32::YELLOW,
+        Color32::WHITE,
+        Color32::GREEN,
+        Color32::from_rgb(0xc0, 0x60, 0xff),
+    ];
+    let index = Probe::ALL.iter().position(|p| *p == probe).unwrap();
+    COLORS[index]
+}
+
+// First and last sample times, in ms from the trigger.
+fn time_extent(traces: &[Trace]) -> Option<(f32, f32)> {
+    traces
+        .iter()
+        .filter(|trace| !trace.samples.is_empty())
+        .map(|trace| {
+            let start = 1000.0 * trace.start;
+            let end = start + 1000.0 * trace.samples.len() as f32 / trace.sample_rate.value();
+            (start, end)
+        })
+        .reduce(|(a, b), (c, d)| (a.min(c), b.max(d)))
+}
+
+fn level_peak(traces: &[Trace]) -> f32 {
+    traces
+        .iter()
+        .flat_map(|trace| &trace.samples)
+        .fold(0.0, |peak, x| x.abs().max(peak))
 }
@@ -1,4 +1,4 @@
-use crate::configuration::{AxisMode, Configuration, TriggerMode};
+use crate::configuration::{AxisMode, Configuration, Probe, ScopeView, TriggerMode};
 use egui::*;
 
 pub struct Settings<'a> {
@@ -12,16 +12,75 @@ impl<'a> Settings<'a> {
 
     pub fn ui(&mut self, ui: &mut egui::Ui) {
         ScrollArea::vertical().show(ui, |ui| {
-            egui::ComboBox::from_label("X-Axis Mode")
-                .selected_text(format!("{:?}", self.config.scope.x_mode))
+            egui::ComboBox::from_label("View")
+                .selected_text(format!("{:?}", self.config.scope.view))
                 .show_ui(ui, |ui| {
-                    ui.selectable_value(&mut self.config.scope.x_mode, AxisMode::Fit, "Fit");
+                    ui.selectable_value(&mut self.config.scope.view, ScopeView::Time, "Time");
+                    ui.selectable_value(&mut self.config.scope.view, ScopeView::Eye, "Eye");
+                    ui.selectable_value(
+                        &mut self.config.scope.view,
+                        ScopeView::Constellation,
+                        "Constellation",
+                    );
                 });
+            if self.config.scope.view != ScopeView::Time {
+                // The rest only applies to traces against time.
+                return;
+            }
+            egui::ComboBox::from_label("X-Axis Mode")
+                .selected_text(format!("{:?}", self.config.scope.x_mode))
+                .show_ui(ui, |ui| axis_modes(ui, &mut self.config.scope.x_mode));
+            match self.config.scope.x_mode {
+                AxisMode::Fit => (),
+                AxisMode::Divisions => {
+                    ui.add(
+                        egui::Slider::new(&mut self.config.scope.timebase, 0.1..=1000.0)
+                            .logarithmic(true)
+                            .text("Timebase (ms/div)"),
+                    );
+                }
+                AxisMode::Manual => {
+                    let (min, max) = &mut self.config.scope.x_range;
+                    range(ui, "x-range", min, max, 0.1, "Time (ms)");
+                }
+            }
             egui::ComboBox::from_label("Y-Axis Mode")
                 .selected_text(format!("{:?}", self.config.scope.y_mode))
-                .show_ui(ui, |ui| {
-                    ui.selectable_value(&mut self.config.scope.y_mode, AxisMode::Fit, "Fit");
-                });
+                .show_ui(ui, |ui| axis_modes(ui, &mut self.config.scope.y_mode));
+            match self.config.scope.y_mode {
+                AxisMode::Fit => (),
+                AxisMode::Divisions => {
+                    ui.add(
+                        egui::Slider::new(&mut self.config.scope.scale, 0.0001..=10.0)
+                            .logarithmic(true)
+                            .text("Scale (/div)"),
+                    );
+                }
+                AxisMode::Manual => {
+                    let (min, max) = &mut self.config.scope.y_range;
+                    range(ui, "y-range", min, max, 0.001, "Level");
+                }
+            }
+            ui.separator();
+            ui.vertical_centered(|ui| {
+                ui.heading("Probes");
+            });
+            for probe in Probe::ALL {
+                let mut shown = self.config.scope.probes.contains(&probe);
+                if ui.checkbox(&mut shown, probe.name()).changed() {
+                    // Kept in chain order, so the trigger is on the earliest.
+                    self.config.scope.probes = Probe::ALL
+                        .into_iter()
+                        .filter(|p| {
+                            if *p == probe {
+                                shown
+                            } else {
+                                self.config.scope.probes.contains(p)
+                            }
+                        })
+                        .collect();
+                }
+            }
             ui.separator();
             ui.vertical_centered(|ui| {
                 ui.heading("Trigger");
@@ -55,6 +114,49 @@ impl<'a> Settings<'a> {
                     ui.end_row();
                 });
             }
+            ui.add(
+                egui::Slider::new(&mut self.config.scope.trigger.position, 0.0..=1.0)
+                    .text("Position"),
+            );
+            ui.separator();
+            ui.vertical_centered(|ui| {
+                ui.heading("Cursors");
+            });
+            let cursors = &mut self.config.scope.cursors;
+            ui.checkbox(&mut cursors.enabled, "Show (click the plot to move)");
+            if cursors.enabled {
+                egui::Grid::new("cursors").show(ui, |ui| {
+                    for i in 0..2 {
+                        ui.add(egui::DragValue::new(&mut cursors.times[i]).speed(0.1));
+                        ui.add(egui::DragValue::new(&mut cursors.levels[i]).speed(0.001));
+                        ui.label(format!("Cursor {} (ms, level)", i + 1));
+                        ui.end_row();
+                    }
+                });
+            }
         });
     }
 }
+
+fn axis_modes(ui: &mut egui::Ui, mode: &mut AxisMode) {
+    ui.selectable_value(mode, AxisMode::Fit, "Fit");
+    ui.selectable_value(mode, AxisMode::Divisions, "Divisions");
+    ui.selectable_value(mode, AxisMode::Manual, "Manual");
+}
+
+fn range(ui: &mut egui::Ui, id: &str, min: &mut f32, max: &mut f32, speed: f64, label: &str) {
+    egui::Grid::new(id).show(ui, |ui| {
+        ui.add(
+            egui::DragValue::new(min)
+                .speed(speed)
+                .clamp_range(f32::MIN..=*max),
+        );
+        ui.add(
+            egui::DragValue::new(max)
+                .speed(speed)
+                .clamp_range(*min..=f32::MAX),
+        );
+        ui.label(label);
+        ui.end_row();
+    });
+}
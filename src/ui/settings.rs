@@ -1,9 +1,12 @@
 use egui::*;
 
 use crate::configuration::DecoderType;
-use crate::configuration::{AudioSampleRate, Configuration};
-use crate::input::InputSource;
+use crate::configuration::{
+    AudioSampleRate, ChannelMode, Configuration, MonitorSource, Sideband, Waveform,
+};
+use crate::input::{InputSource, IqFormat};
 use crate::ui::bump::Bump;
+use crate::units::format_hertz;
 
 pub struct Settings<'a> {
     config: &'a mut Configuration,
@@ -33,6 +36,23 @@ impl<'a> Settings<'a> {
                         InputSource::Example,
                         "Example",
                     );
+                    ui.selectable_value(
+                        &mut self.config.input_source,
+                        InputSource::Generator,
+                        "Generator",
+                    );
+                    #[cfg(not(target_arch = "wasm32"))]
+                    ui.selectable_value(
+                        &mut self.config.input_source,
+                        InputSource::IqFile,
+                        "I/Q File",
+                    );
+                    #[cfg(not(target_arch = "wasm32"))]
+                    ui.selectable_value(
+                        &mut self.config.input_source,
+                        InputSource::RtlTcp,
+                        "rtl_tcp",
+                    );
                 });
 
             if self.config.input_source == InputSource::Audio {
@@ -52,6 +72,72 @@ impl<'a> Settings<'a> {
                             );
                         });
                     });
+                egui::ComboBox::from_label("Channel")
+                    .selected_text(format!("{}", self.config.input_channel))
+                    .show_ui(ui, |ui| {
+                        for mode in [
+                            ChannelMode::Left,
+                            ChannelMode::Right,
+                            ChannelMode::Sum,
+                            ChannelMode::Iq,
+                        ] {
+                            ui.selectable_value(
+                                &mut self.config.input_channel,
+                                mode,
+                                mode.to_string(),
+                            );
+                        }
+                    });
+            }
+
+            if self.config.input_source == InputSource::Generator {
+                self.generator_ui(ui);
+            }
+
+            if self.config.input_source == InputSource::IqFile {
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.config.iq_file);
+                    ui.label(format!("{:?}", IqFormat::from_path(&self.config.iq_file)));
+                });
+            }
+
+            if self.config.input_source == InputSource::RtlTcp {
+                let rtl_tcp = &mut self.config.rtl_tcp;
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut rtl_tcp.address);
+                    ui.label("Server");
+                });
+                ui.checkbox(&mut rtl_tcp.agc, "AGC");
+                if !rtl_tcp.agc {
+                    ui.add(egui::Slider::new(&mut rtl_tcp.gain, 0.0..=50.0).text("Gain (dB)"));
+                }
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut rtl_tcp.ppm).clamp_range(-200..=200));
+                    ui.label("Correction (ppm)");
+                });
+            }
+
+            if self.config.is_complex() {
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut self.config.center_frequency).speed(100.0));
+                    ui.label("Center Frequency (Hz)");
+                });
+            } else {
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut self.config.dial_frequency).speed(100.0));
+                    ui.label("Dial Frequency (Hz)");
+                });
+                egui::ComboBox::from_label("Sideband")
+                    .selected_text(self.config.sideband.to_string())
+                    .show_ui(ui, |ui| {
+                        for sideband in [Sideband::Usb, Sideband::Lsb] {
+                            ui.selectable_value(
+                                &mut self.config.sideband,
+                                sideband,
+                                sideband.to_string(),
+                            );
+                        }
+                    });
             }
 
             egui::ComboBox::from_label("Sample Rate")
@@ -89,6 +175,10 @@ impl<'a> Settings<'a> {
                     );
                 });
 
+            // rigctld is reached over TCP, which the browser doesn't allow.
+            #[cfg(not(target_arch = "wasm32"))]
+            self.rig_ui(ui);
+
             ui.separator();
             ui.vertical_centered(|ui| {
                 ui.heading("Waterfall");
@@ -121,12 +211,17 @@ impl<'a> Settings<'a> {
             );
             ui.add(egui::Slider::new(&mut self.config.zoom, 1.0..=5.0).text("Zoom"));
             ui.add(egui::Slider::new(&mut self.config.scroll, 0.0..=1.0).text("Scroll"));
+            self.labels_ui(ui);
 
             ui.separator();
             ui.vertical_centered(|ui| {
                 ui.heading("Tuner");
             });
             Bump::new(&mut self.config.tuner.carrier, "Carrier".to_string()).ui(ui);
+            ui.label(format!(
+                "Carrier at {}",
+                format_hertz(self.config.display_frequency(self.config.tuner.carrier()))
+            ));
             Bump::new(&mut self.config.tuner.upper, "Bandpass Upper".to_string()).ui(ui);
             Bump::new(&mut self.config.tuner.lower, "Bandpass Lower".to_string()).ui(ui);
 
@@ -135,6 +230,209 @@ impl<'a> Settings<'a> {
                 .show_ui(ui, |ui| {
                     ui.selectable_value(&mut self.config.tuner.decoder, DecoderType::Rtty, "RTTY");
                 });
+
+            self.noise_ui(ui);
+            self.monitor_ui(ui);
+            // There is no file system in the browser.
+            #[cfg(not(target_arch = "wasm32"))]
+            self.log_ui(ui);
+            self.station_ui(ui);
+            // Spots are written to a file and sent over UDP.
+            #[cfg(not(target_arch = "wasm32"))]
+            self.spots_ui(ui);
+        });
+    }
+
+    fn labels_ui(&mut self, ui: &mut egui::Ui) {
+        let labels = &mut self.config.labels;
+        ui.checkbox(&mut labels.enabled, "Label decoded messages");
+        ui.add_enabled_ui(labels.enabled, |ui| {
+            ui.horizontal(|ui| {
+                for decoder in DecoderType::ALL {
+                    let mut labelled = labels.modes.contains(&decoder);
+                    if ui.checkbox(&mut labelled, decoder.mode()).changed() {
+                        labels.modes.retain(|mode| *mode != decoder);
+                        if labelled {
+                            labels.modes.push(decoder);
+                        }
+                    }
+                }
+            });
+        });
+    }
+
+    fn monitor_ui(&mut self, ui: &mut egui::Ui) {
+        let monitor = &mut self.config.monitor;
+
+        ui.separator();
+        ui.vertical_centered(|ui| {
+            ui.heading("Monitor");
+        });
+        ui.checkbox(&mut monitor.enabled, "Play audio");
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut monitor.source, MonitorSource::Channel, "Channel");
+            ui.selectable_value(&mut monitor.source, MonitorSource::Raw, "Raw input");
+        });
+        ui.checkbox(&mut monitor.shift, "Shift the carrier to a pitch");
+        if monitor.shift {
+            Bump::new(&mut monitor.pitch, "Pitch (Hz)".to_string()).ui(ui);
+        }
+        ui.horizontal(|ui| {
+            ui.add(egui::Slider::new(&mut monitor.volume, 0.0..=1.0).text("Volume"));
+            ui.checkbox(&mut monitor.mute, "Mute");
         });
     }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn log_ui(&mut self, ui: &mut egui::Ui) {
+        let log = &mut self.config.log;
+
+        ui.separator();
+        ui.vertical_centered(|ui| {
+            ui.heading("Log");
+        });
+        ui.checkbox(&mut log.enabled, "Log messages to a file");
+        // The file is reopened when the path changes, so it is only edited
+        // while the log is closed.
+        ui.add_enabled(!log.enabled, egui::TextEdit::singleline(&mut log.path));
+    }
+
+    fn station_ui(&mut self, ui: &mut egui::Ui) {
+        let station = &mut self.config.station;
+
+        ui.separator();
+        ui.vertical_centered(|ui| {
+            ui.heading("Station");
+        });
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut station.callsign);
+            ui.label("Callsign");
+        });
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut station.locator);
+            ui.label("Locator");
+        });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn spots_ui(&mut self, ui: &mut egui::Ui) {
+        let spots = &mut self.config.spots;
+
+        ui.separator();
+        ui.vertical_centered(|ui| {
+            ui.heading("Spots");
+        });
+        ui.checkbox(&mut spots.enabled, "Spot the stations we decode");
+        ui.add_enabled_ui(spots.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut spots.cluster_path);
+                ui.label("Cluster file");
+            });
+            ui.checkbox(&mut spots.psk_reporter, "Send PSK Reporter datagrams");
+            ui.horizontal(|ui| {
+                ui.add_enabled(
+                    spots.psk_reporter,
+                    egui::TextEdit::singleline(&mut spots.host),
+                );
+                ui.label("Host");
+            });
+        });
+    }
+
+    fn noise_ui(&mut self, ui: &mut egui::Ui) {
+        let noise = &mut self.config.noise;
+
+        ui.separator();
+        ui.vertical_centered(|ui| {
+            ui.heading("Noise");
+        });
+        ui.checkbox(&mut noise.noise_reduction, "Noise reduction");
+        ui.checkbox(&mut noise.notch, "Automatic notch");
+        ui.checkbox(&mut noise.blanker, "Noise blanker");
+        if noise.blanker {
+            ui.add(
+                egui::Slider::new(&mut noise.blanker_threshold, 2.0..=50.0)
+                    .logarithmic(true)
+                    .text("Blanker threshold"),
+            );
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn rig_ui(&mut self, ui: &mut egui::Ui) {
+        let rig = &mut self.config.rig;
+
+        ui.separator();
+        ui.vertical_centered(|ui| {
+            ui.heading("Rig Control");
+        });
+        ui.checkbox(&mut rig.enabled, "Connect to rigctld");
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut rig.address);
+            ui.label("Address");
+        });
+        ui.checkbox(&mut rig.qsy, "QSY on clicks outside the passband");
+    }
+
+    fn generator_ui(&mut self, ui: &mut egui::Ui) {
+        let generator = &mut self.config.generator;
+
+        egui::ComboBox::from_label("Waveform")
+            .selected_text(format!("{:?}", generator.waveform))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut generator.waveform, Waveform::Tone, "Tone");
+                ui.selectable_value(&mut generator.waveform, Waveform::Multitone, "Multitone");
+                ui.selectable_value(&mut generator.waveform, Waveform::Sweep, "Sweep");
+                ui.selectable_value(&mut generator.waveform, Waveform::WhiteNoise, "White Noise");
+                ui.selectable_value(&mut generator.waveform, Waveform::PinkNoise, "Pink Noise");
+                ui.selectable_value(&mut generator.waveform, Waveform::Rtty, "RTTY");
+                ui.selectable_value(&mut generator.waveform, Waveform::Cw, "CW");
+                ui.selectable_value(&mut generator.waveform, Waveform::Psk, "PSK");
+            });
+
+        match generator.waveform {
+            Waveform::WhiteNoise | Waveform::PinkNoise => (),
+            Waveform::Rtty => {
+                Bump::new(&mut generator.frequency, "Space (Hz)".to_string()).ui(ui);
+                Bump::new(&mut generator.shift, "Shift (Hz)".to_string()).ui(ui);
+            }
+            Waveform::Sweep => {
+                Bump::new(&mut generator.frequency, "Start (Hz)".to_string()).ui(ui);
+                Bump::new(&mut generator.sweep_stop, "Stop (Hz)".to_string()).ui(ui);
+                ui.add(
+                    egui::Slider::new(&mut generator.sweep_period, 0.1..=60.0).text("Period (s)"),
+                );
+            }
+            Waveform::Multitone => {
+                Bump::new(&mut generator.frequency, "Frequency (Hz)".to_string()).ui(ui);
+                Bump::new(&mut generator.spacing, "Spacing (Hz)".to_string()).ui(ui);
+                ui.add(egui::Slider::new(&mut generator.tones, 1..=16).text("Tones"));
+            }
+            _ => {
+                Bump::new(&mut generator.frequency, "Frequency (Hz)".to_string()).ui(ui);
+            }
+        }
+
+        if generator.waveform.is_keyed() {
+            let label = if generator.waveform == Waveform::Cw {
+                format!("Baud ({:.0} WPM)", 1.2 * generator.baud)
+            } else {
+                "Baud".to_string()
+            };
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut generator.baud)
+                        .speed(0.01)
+                        .clamp_range(1.0..=300.0),
+                );
+                ui.label(label);
+            });
+            ui.add(egui::TextEdit::multiline(&mut generator.text).desired_rows(2));
+        }
+
+        ui.checkbox(&mut generator.noise, "Add noise");
+        if generator.noise {
+            ui.add(egui::Slider::new(&mut generator.snr_db, -30.0..=50.0).text("SNR (dB)"));
+        }
+    }
 }
@@ -0,0 +1,38 @@
+use egui::RichText;
+
+use crate::messages::MessageCollector;
+
+/// Decoded text as it arrives, the way a teleprinter would print it.
+pub struct TextView<'a> {
+    data: &'a mut MessageCollector,
+}
+
+impl<'a> TextView<'a> {
+    pub fn new(data: &'a mut MessageCollector) -> Self {
+        Self { data }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("Copy").clicked() {
+                ui.output().copied_text = self.data.text.clone();
+            }
+            if ui.button("Clear").clicked() {
+                self.data.text.clear();
+            }
+        });
+        ui.separator();
+
+        // Follows new text, unless scrolled back to read.
+        egui::ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .stick_to_bottom()
+            .show(ui, |ui| {
+                if self.data.text.is_empty() {
+                    ui.weak("Decoded text will appear here as it arrives.");
+                } else {
+                    ui.add(egui::Label::new(RichText::new(&self.data.text).monospace()).wrap(true));
+                }
+            });
+    }
+}
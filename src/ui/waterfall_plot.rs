@@ -3,15 +3,28 @@ use egui_extras::image::RetainedImage;
 
 use crate::configuration::Configuration;
 use crate::ui::WaterfallTicks;
+use crate::units::format_hertz;
+use crate::waterfall_labels::WaterfallLabels;
+
+const LABEL_BACKGROUND: Color32 = Color32::from_rgba_premultiplied(0, 0, 0, 160);
 
 pub struct WaterfallPlot<'a> {
     image: &'a Option<RetainedImage>,
     config: &'a mut Configuration,
+    labels: &'a WaterfallLabels,
 }
 
 impl<'a> WaterfallPlot<'a> {
-    pub fn new(image: &'a Option<RetainedImage>, config: &'a mut Configuration) -> Self {
-        Self { image, config }
+    pub fn new(
+        image: &'a Option<RetainedImage>,
+        config: &'a mut Configuration,
+        labels: &'a WaterfallLabels,
+    ) -> Self {
+        Self {
+            image,
+            config,
+            labels,
+        }
     }
 
     pub fn ui(&mut self, ui: &mut egui::Ui) {
@@ -22,15 +35,39 @@ impl<'a> WaterfallPlot<'a> {
                 .frame(Frame::none().fill(ui.style().visuals.faint_bg_color))
                 .show_inside(ui, |ui| {
                     let size = ui.available_size();
-                    let response = image.show_size(ui, size);
+                    let response = image.show_size(ui, size).interact(Sense::click());
+                    self.labels_ui(ui, response.rect);
+                    if response.clicked() {
+                        if let Some(pos) = response.interact_pointer_pos() {
+                            let f = self.config.zoomed_interval_to_hz(pos.x / size.x);
+                            self.config.click_to_tune(f);
+                        }
+                    }
                     if response.hovered() {
                         if let Some(pos) = response.hover_pos() {
                             let interval_pos = pos.x / size.x;
-                            let hover_freq = self.config.zoomed_interval_to_hz(interval_pos);
-                            response.on_hover_text_at_pointer(hover_freq.to_string());
+                            let hover_freq = self
+                                .config
+                                .display_frequency(self.config.zoomed_interval_to_hz(interval_pos));
+                            response.on_hover_text_at_pointer(format_hertz(hover_freq));
                         }
                     }
                 });
         }
     }
+
+    /// Each label sits just above where its message came in, starting at
+    /// its frequency.
+    fn labels_ui(&self, ui: &mut egui::Ui, rect: Rect) {
+        let painter = ui.painter_at(rect);
+        for (label, x, y) in self.labels.visible(self.config) {
+            let pos = rect.left_top() + vec2(x * rect.width(), y * rect.height());
+            let galley =
+                painter.layout_no_wrap(label.text.clone(), FontId::monospace(12.0), Color32::WHITE);
+            let text_rect =
+                Align2::LEFT_BOTTOM.anchor_rect(Rect::from_min_size(pos, galley.size()));
+            painter.rect_filled(text_rect.expand(2.0), 2.0, LABEL_BACKGROUND);
+            painter.galley(text_rect.min, galley);
+        }
+    }
 }
@@ -2,7 +2,7 @@ use egui::*;
 
 use crate::configuration::Configuration;
 use crate::ui::filter_cutoff::{Carrier, FilterConnection, FilterCutoffLower, FilterCutoffUpper};
-use crate::units::Frequency;
+use crate::units::{format_hertz, Frequency};
 
 pub struct WaterfallTicks<'a> {
     config: &'a mut Configuration,
@@ -71,8 +71,10 @@ impl<'a> WaterfallTicks<'a> {
                 if response.hovered() {
                     if let Some(pos) = response.hover_pos() {
                         let interval_pos = pos.x / size.x;
-                        let hover_freq = self.config.zoomed_interval_to_hz(interval_pos);
-                        response.on_hover_text_at_pointer(hover_freq.to_string());
+                        let hover_freq = self
+                            .config
+                            .display_frequency(self.config.zoomed_interval_to_hz(interval_pos));
+                        response.on_hover_text_at_pointer(format_hertz(hover_freq));
                     }
                 }
 
@@ -87,59 +89,54 @@ impl<'a> WaterfallTicks<'a> {
                     200.0_f32.min(rect.width()),
                 );
 
-                for i in 0..(10.0 * rect.width() / pixel_width + 1.0) as usize {
-                    let x = rect.left() + (i as f32) * pixel_width / 10.0;
+                // Ticks sit on round RF frequencies. In LSB these run right
+                // to left.
+                let edges = [
+                    self.config.display_frequency(Frequency::Hertz(start_hz)),
+                    self.config
+                        .display_frequency(Frequency::Hertz(start_hz + displayed_bandwidth)),
+                ];
+                let low = edges[0].min(edges[1]);
+                let high = edges[0].max(edges[1]);
+
+                let minor = f_width as f64 / 10.0;
+                let first = (low / minor).ceil() as i64;
+                let last = (high / minor).floor() as i64;
+
+                for i in first..=last {
+                    let frequency = i as f64 * minor;
+                    let audio = self.config.baseband_frequency(frequency).value();
+                    let x = rect.left() + (audio - start_hz) / displayed_bandwidth * rect.width();
+
+                    let length = if i % 10 == 0 {
+                        15.0
+                    } else if i % 5 == 0 {
+                        10.0
+                    } else {
+                        5.0
+                    };
                     painter.line_segment(
                         [
                             Pos2 { x, y: rect.top() },
                             Pos2 {
                                 x,
-                                y: rect.top() + 5.0,
+                                y: rect.top() + length,
                             },
                         ],
                         stroke,
                     );
-                }
-
-                for i in 0..(2.0 * rect.width() / pixel_width + 1.0) as usize {
-                    let x = rect.left() + (i as f32) * pixel_width / 2.0;
-                    painter.line_segment(
-                        [
-                            Pos2 { x, y: rect.top() },
-                            Pos2 {
-                                x,
-                                y: rect.top() + 10.0,
-                            },
-                        ],
-                        stroke,
-                    );
-                }
-
-                for i in 0..(rect.width() / pixel_width + 1.0) as usize {
-                    let x = rect.left() + (i as f32) * pixel_width;
-                    painter.line_segment(
-                        [
-                            Pos2 { x, y: rect.top() },
-                            Pos2 {
-                                x,
-                                y: rect.top() + 15.0,
-                            },
-                        ],
-                        stroke,
-                    );
-                }
-
-                for i in 0..(rect.width() / pixel_width + 1.0) as usize {
-                    let x = rect.left() + (i as f32) * pixel_width;
 
-                    let mut align: Align2 = Align2::CENTER_TOP;
-                    if i == 0 {
-                        align = Align2::LEFT_TOP;
-                    } else if i == (rect.width() / pixel_width) as usize {
-                        align = Align2::RIGHT_TOP;
+                    if i % 10 != 0 {
+                        continue;
                     }
 
-                    let frequency = Frequency::Hertz(i as f32 * f_width + start_hz);
+                    let align = if x - rect.left() < pixel_width / 2.0 {
+                        Align2::LEFT_TOP
+                    } else if rect.right() - x < pixel_width / 2.0 {
+                        Align2::RIGHT_TOP
+                    } else {
+                        Align2::CENTER_TOP
+                    };
 
                     painter.text(
                         Pos2 {
@@ -147,7 +144,7 @@ impl<'a> WaterfallTicks<'a> {
                             y: rect.top() + 18.0,
                         },
                         align,
-                        format!("{}", frequency),
+                        format_hertz(frequency),
                         FontId::proportional(14.0),
                         color,
                     );
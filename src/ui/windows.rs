@@ -2,6 +2,8 @@
 pub struct Windows {
     pub settings: bool,
     pub scope: bool,
+    pub filters: bool,
     pub messages: bool,
+    pub text: bool,
     pub about: bool,
 }
@@ -12,14 +12,20 @@ pub enum Frequency {
 
 impl fmt::Display for Frequency {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let prefix_number = (self.value().log10() / 3.0).floor() as u32;
+        write!(f, "{}", format_hertz(self.value() as f64))
+    }
+}
 
-        match prefix_number {
-            0 => write!(f, "{:.2} Hz", self.value()),
-            1 => write!(f, "{:.2} kHz", self.value() / 10_u32.pow(3) as f32),
-            2 => write!(f, "{:.2} MHz", self.value() / 10_u32.pow(6) as f32),
-            _ => write!(f, "{:.2} GHz", self.value() / 10_u32.pow(9) as f32),
-        }
+/// Written the way a `Frequency` is. RF frequencies are kept as f64 hertz,
+/// since an f32 can't hold them to the hertz above 16.7 MHz.
+pub fn format_hertz(hz: f64) -> String {
+    let prefix_number = (hz.abs().log10() / 3.0).floor() as u32;
+
+    match prefix_number {
+        0 => format!("{:.2} Hz", hz),
+        1 => format!("{:.2} kHz", hz / 10_u32.pow(3) as f64),
+        2 => format!("{:.4} MHz", hz / 10_u32.pow(6) as f64),
+        _ => format!("{:.2} GHz", hz / 10_u32.pow(9) as f64),
     }
 }
 
@@ -1,5 +1,5 @@
 mod frequency;
 mod time;
 
-pub use frequency::Frequency;
+pub use frequency::{format_hertz, Frequency};
 pub use time::Time;
@@ -0,0 +1,144 @@
+use std::collections::VecDeque;
+
+use crate::configuration::Configuration;
+use crate::messages::MessageCollector;
+use crate::types::PLOT_DEPTH;
+
+// Longer text is cut short, so that labels don't hide the waterfall.
+const MAX_CHARS: usize = 24;
+
+/// A decoded message, pinned to the waterfall row it arrived with.
+pub struct WaterfallLabel {
+    pub row: u64,
+    pub mode: String,
+    // RF, in Hz.
+    pub frequency: f64,
+    pub text: String,
+}
+
+/// Where on the waterfall each message was received, for as long as that
+/// part of the waterfall is on screen.
+#[derive(Default)]
+pub struct WaterfallLabels {
+    labels: VecDeque<WaterfallLabel>,
+    // What had been received, and the waterfall's row count, as of the
+    // last update.
+    received: u64,
+    rows: u64,
+}
+
+impl WaterfallLabels {
+    /// Labels the messages received since the last update at `rows`, the
+    /// waterfall's current row count.
+    pub fn update(&mut self, messages: &MessageCollector, rows: u64) {
+        // The waterfall started over.
+        if rows < self.rows {
+            self.labels.clear();
+        }
+        self.rows = rows;
+
+        let new = messages.received().saturating_sub(self.received) as usize;
+        self.received = messages.received();
+        let new = new.min(messages.data.len());
+        for entry in messages.data.iter().skip(messages.data.len() - new) {
+            if let Some(frequency) = entry.frequency {
+                self.labels.push_back(WaterfallLabel {
+                    row: rows,
+                    mode: entry.mode.clone(),
+                    frequency,
+                    text: shorten(&entry.payload),
+                });
+            }
+        }
+
+        while matches!(self.labels.front(), Some(label) if rows - label.row >= PLOT_DEPTH as u64) {
+            self.labels.pop_front();
+        }
+    }
+
+    /// The labels to show, each with where it goes: across the displayed
+    /// span and down the waterfall, both from 0 to 1.
+    pub fn visible<'a>(
+        &'a self,
+        config: &'a Configuration,
+    ) -> impl Iterator<Item = (&'a WaterfallLabel, f32, f32)> {
+        self.labels
+            .iter()
+            .filter(|label| config.labels.labels(&label.mode))
+            .filter_map(|label| {
+                let hz = config.baseband_frequency(label.frequency).value();
+                let x = (hz - config.start_hz()) / config.displayed_bandwidth();
+                // The newest row is at the bottom.
+                let y = 1.0 - (self.rows - label.row) as f32 / PLOT_DEPTH as f32;
+                (0.0..=1.0).contains(&x).then_some((label, x, y))
+            })
+    }
+}
+
+fn shorten(payload: &str) -> String {
+    let text = payload.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() > MAX_CHARS {
+        text.chars()
+            .take(MAX_CHARS - 1)
+            .chain(Some('\u{2026}'))
+            .collect()
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::configuration::DecoderType;
+    use crate::message::{DecoderEvent, RttyMessage};
+
+    fn receive(tx: &mpsc::Sender<DecoderEvent>, hz: f64, payload: &str) {
+        let message = RttyMessage::new(payload.to_string()).with_frequency(hz);
+        tx.try_send(DecoderEvent::Message(Box::new(message)))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_labels() {
+        let mut config = Configuration::default();
+        let (tx, rx) = mpsc::channel(16);
+        let mut messages = MessageCollector::new(rx);
+        let mut labels = WaterfallLabels::default();
+
+        receive(&tx, 1000.0, "CQ CQ\r\nDE W1AW W1AW W1AW PSE K");
+        messages.run(&mut config);
+        labels.update(&messages, 10);
+
+        receive(&tx, 2000.0, "W1AW DE K1ABC");
+        messages.run(&mut config);
+        labels.update(&messages, 10 + PLOT_DEPTH as u64 / 2);
+
+        let visible: Vec<_> = labels.visible(&config).collect();
+        assert_eq!(visible.len(), 2);
+        let (label, x, y) = visible[0];
+        assert_eq!(label.text, "CQ CQ DE W1AW W1AW W1AW\u{2026}");
+        assert!((x - 1000.0 / config.displayed_bandwidth()).abs() < 1e-6);
+        assert_eq!(y, 0.5);
+        let (label, _, y) = visible[1];
+        assert_eq!(label.text, "W1AW DE K1ABC");
+        assert_eq!(y, 1.0);
+
+        config
+            .labels
+            .modes
+            .retain(|mode| *mode != DecoderType::Rtty);
+        assert_eq!(labels.visible(&config).count(), 0);
+        config.labels = Default::default();
+
+        // Scrolled off the top.
+        labels.update(&messages, 10 + PLOT_DEPTH as u64);
+        assert_eq!(labels.visible(&config).count(), 1);
+
+        // The waterfall was cleared.
+        labels.update(&messages, 0);
+        assert_eq!(labels.visible(&config).count(), 0);
+    }
+}
@@ -0,0 +1,198 @@
+// Shared by several test crates, none of which use everything.
+#![allow(dead_code)]
+
+use std::f32::consts::PI;
+use std::path::Path;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use statrs::distribution::Normal;
+use tokio::sync::mpsc;
+
+use waterfall_rs::configuration::Configuration;
+use waterfall_rs::dsp::encode::{rtty_keying, RttyEncoder};
+use waterfall_rs::dsp::rx::Rx;
+use waterfall_rs::message::{DecoderEvent, Message};
+use waterfall_rs::statistics::Statistics;
+use waterfall_rs::types::{ComplexType, SampleType};
+use waterfall_rs::units::Frequency;
+
+pub const MARK_HZ: f32 = 1100.0;
+pub const SPACE_HZ: f32 = 930.0;
+pub const BAUD: f32 = 45.45;
+
+/// SNR is quoted in this bandwidth, as is customary for HF digital modes.
+pub const SNR_BANDWIDTH_HZ: f32 = 2500.0;
+
+pub fn read_wav(path: impl AsRef<Path>) -> (Vec<SampleType>, f32) {
+    let mut reader = hound::WavReader::open(path).unwrap();
+    let sample_rate = reader.spec().sample_rate as f32;
+    let samples = reader
+        .samples::<i16>()
+        .map(|s| s.unwrap() as f32 / 32768.0)
+        .collect();
+
+    (samples, sample_rate)
+}
+
+/// AFSK at the tones `Rtty` listens for.
+pub fn rtty_afsk(text: &str, sample_rate: f32) -> Vec<SampleType> {
+    rtty_afsk_at(text, sample_rate, SPACE_HZ)
+}
+
+/// AFSK with the space tone at `space_hz`.
+pub fn rtty_afsk_at(text: &str, sample_rate: f32, space_hz: f32) -> Vec<SampleType> {
+    let mut encoder = RttyEncoder::new(
+        Frequency::Hertz(sample_rate),
+        Frequency::Hertz(space_hz),
+        Frequency::Hertz(MARK_HZ - SPACE_HZ),
+        Frequency::Hertz(BAUD),
+    );
+    encoder.encode(text)
+}
+
+/// Complex baseband AFSK with the space tone at `space_hz`, which may be
+/// negative. Starts and ends with a second of mark.
+pub fn iq_afsk(text: &str, sample_rate: f32, space_hz: f32) -> Vec<ComplexType> {
+    let idle = [(true, BAUD)];
+    let keying: Vec<_> = idle
+        .into_iter()
+        .chain(rtty_keying(text))
+        .chain(idle)
+        .collect();
+
+    let mut phase = 0.0;
+    let mut samples = Vec::new();
+    for (mark, symbols) in keying {
+        let f = if mark {
+            space_hz + MARK_HZ - SPACE_HZ
+        } else {
+            space_hz
+        };
+        let len = (symbols * sample_rate / BAUD).round() as usize;
+        for _ in 0..len {
+            samples.push(ComplexType::from_polar(0.5, phase));
+            phase = (phase + 2.0 * PI * f / sample_rate) % (2.0 * PI);
+        }
+    }
+    samples
+}
+
+/// Add white Gaussian noise so the SNR, measured in `SNR_BANDWIDTH_HZ`, is
+/// `snr_db`. Seeded so that runs are reproducible.
+pub fn add_awgn(samples: &mut [SampleType], snr_db: f32, sample_rate: f32, seed: u64) {
+    use rand::distributions::Distribution;
+
+    let signal_power = samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32;
+    let noise_power = signal_power / 10_f32.powf(snr_db / 10.0);
+    let variance = noise_power * (sample_rate / 2.0) / SNR_BANDWIDTH_HZ;
+
+    let normal = Normal::new(0.0, variance.sqrt() as f64).unwrap();
+    let mut rng = StdRng::seed_from_u64(seed);
+    samples
+        .iter_mut()
+        .for_each(|s| *s += normal.sample(&mut rng) as f32);
+}
+
+/// Run samples through `Rx`, the way `Processor` does, and collect every
+/// payload it emits.
+pub fn decode(samples: &[SampleType], config: &Configuration) -> Vec<String> {
+    decode_messages(samples, config)
+        .iter()
+        .map(|message| message.payload())
+        .collect()
+}
+
+/// Like `decode`, keeping everything else about each message.
+pub fn decode_messages(samples: &[SampleType], config: &Configuration) -> Vec<Box<dyn Message>> {
+    decode_events(samples, config)
+        .into_iter()
+        .filter_map(|event| match event {
+            DecoderEvent::Message(message) => Some(message),
+            DecoderEvent::Text(_) => None,
+        })
+        .collect()
+}
+
+/// Everything `Rx` streamed out as it decoded.
+pub fn decode_text(samples: &[SampleType], config: &Configuration) -> String {
+    decode_text_events(samples, config).concat()
+}
+
+/// The text `Rx` streamed out, as it was sent.
+pub fn decode_text_events(samples: &[SampleType], config: &Configuration) -> Vec<String> {
+    decode_events(samples, config)
+        .into_iter()
+        .filter_map(|event| match event {
+            DecoderEvent::Text(text) => Some(text),
+            DecoderEvent::Message(_) => None,
+        })
+        .collect()
+}
+
+fn decode_events(samples: &[SampleType], config: &Configuration) -> Vec<DecoderEvent> {
+    let (message_tx, mut message_rx) = mpsc::channel(1024);
+    let mut rx = Rx::new(config).with_message_sender(message_tx);
+    let mut stats = Statistics::default();
+
+    // Trailing silence flushes the aggregator and the message timeout.
+    let sample_rate = config.audio_sample_rate.as_frequency().value() as usize;
+    let mut samples = samples.to_vec();
+    samples.extend(vec![0.0; sample_rate]);
+
+    for chunk in samples.chunks(1024) {
+        let mut chunk = chunk.to_vec();
+        rx.clean(&mut chunk, config);
+        rx.run(chunk, config, &mut stats);
+    }
+
+    let mut events = Vec::new();
+    while let Ok(event) = message_rx.try_recv() {
+        events.push(event);
+    }
+    events
+}
+
+/// Like `decode`, for I/Q input.
+pub fn decode_iq(samples: &[ComplexType], config: &Configuration) -> Vec<String> {
+    let (message_tx, mut message_rx) = mpsc::channel(1024);
+    let mut rx = Rx::new(config).with_message_sender(message_tx);
+    let mut stats = Statistics::default();
+
+    let sample_rate = config.audio_sample_rate.as_frequency().value() as usize;
+    let mut samples = samples.to_vec();
+    samples.extend(vec![ComplexType::default(); sample_rate]);
+
+    for chunk in samples.chunks(1024) {
+        let mut chunk = chunk.to_vec();
+        rx.clean_complex(&mut chunk, config);
+        rx.run_complex(&chunk, config, &mut stats);
+    }
+
+    let mut payloads = Vec::new();
+    while let Ok(event) = message_rx.try_recv() {
+        if let DecoderEvent::Message(message) = event {
+            payloads.push(message.payload());
+        }
+    }
+    payloads
+}
+
+/// Levenshtein distance between `expected` and `actual`, divided by the
+/// length of `expected`.
+pub fn character_error_rate(expected: &str, actual: &str) -> f32 {
+    let expected: Vec<char> = expected.chars().collect();
+    let actual: Vec<char> = actual.chars().collect();
+
+    let mut previous: Vec<usize> = (0..=actual.len()).collect();
+    for (i, e) in expected.iter().enumerate() {
+        let mut current = vec![i + 1; actual.len() + 1];
+        for (j, a) in actual.iter().enumerate() {
+            let substitution = previous[j] + usize::from(e != a);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[actual.len()] as f32 / expected.len() as f32
+}
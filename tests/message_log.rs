@@ -0,0 +1,96 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tokio::sync::mpsc;
+
+use waterfall_rs::configuration::{Configuration, LogSettings};
+use waterfall_rs::message::{DecoderEvent, Message, RttyMessage};
+use waterfall_rs::messages::{ExportFormat, MessageCollector};
+
+/// A fresh directory for one test's files.
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("waterfall-rs-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn config(dir: &Path) -> Configuration {
+    Configuration {
+        log: LogSettings {
+            enabled: true,
+            path: dir.join("messages.jsonl").to_str().unwrap().to_owned(),
+            export_path: dir.join("export").to_str().unwrap().to_owned(),
+        },
+        ..Default::default()
+    }
+}
+
+/// Runs a collector through one session, in which `payloads` arrive.
+fn session(config: &mut Configuration, payloads: &[&str]) -> MessageCollector {
+    let (message_tx, message_rx) = mpsc::channel(16);
+    let mut collector = MessageCollector::new(message_rx);
+    collector.run(config);
+    for payload in payloads {
+        let message = RttyMessage::new(payload.to_string()).with_frequency(14_085_000.0);
+        message_tx
+            .try_send(DecoderEvent::Message(Box::new(message)))
+            .unwrap();
+    }
+    collector.run(config);
+    collector
+}
+
+#[test]
+fn test_log_survives_restart() {
+    let dir = scratch("restart");
+    let mut config = config(&dir);
+
+    session(&mut config, &["CQ CQ DE W1AW", "W1AW DE K1ABC"]);
+    let collector = session(&mut config, &["K1ABC DE W1AW 599"]);
+
+    let payloads: Vec<String> = collector.data.iter().map(|m| m.payload()).collect();
+    assert_eq!(
+        payloads,
+        vec!["CQ CQ DE W1AW", "W1AW DE K1ABC", "K1ABC DE W1AW 599"]
+    );
+    assert!(collector.status.is_none(), "{:?}", collector.status);
+}
+
+#[test]
+fn test_export() {
+    let dir = scratch("export");
+    let mut config = config(&dir);
+    let mut collector = session(&mut config, &["CQ CQ DE W1AW", "599, TU"]);
+
+    collector.export = Some(ExportFormat::Csv);
+    collector.run(&mut config);
+    let csv = fs::read_to_string(dir.join("export.csv")).unwrap();
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows.len(), 3);
+    assert!(
+        rows[2].ends_with(",RTTY,14085000,,\"599, TU\""),
+        "{}",
+        rows[2]
+    );
+
+    collector.export = Some(ExportFormat::Adif);
+    collector.run(&mut config);
+    let adif = fs::read_to_string(dir.join("export.adi")).unwrap();
+    // "599, TU" doesn't say who sent it.
+    assert_eq!(adif.matches("<EOR>").count(), 1);
+    assert!(adif.contains("<CALL:4>W1AW "));
+    assert!(adif.contains("<MODE:4>RTTY <FREQ:9>14.085000 <COMMENT:13>CQ CQ DE W1AW"));
+}
+
+#[test]
+fn test_logging_off() {
+    let dir = scratch("off");
+    let mut config = config(&dir);
+    config.log.enabled = false;
+
+    let collector = session(&mut config, &["CQ CQ DE W1AW"]);
+
+    assert_eq!(collector.data.len(), 1);
+    assert!(!dir.join("messages.jsonl").exists());
+}
@@ -0,0 +1,149 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use waterfall_rs::configuration::{Configuration, Sideband};
+use waterfall_rs::rig::{RigControl, RigState, Rigctld};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const MODES: [&str; 5] = ["USB", "LSB", "PKTUSB", "PKTLSB", "CW"];
+
+/// Stands in for rigctld, for as many clients as come along.
+fn serve(state: RigState) -> (String, Arc<Mutex<RigState>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let state = Arc::new(Mutex::new(state));
+
+    let rig = state.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let rig = rig.clone();
+            thread::spawn(move || {
+                let reader = BufReader::new(stream.try_clone().unwrap());
+                for line in reader.lines() {
+                    let line = line.unwrap();
+                    let words: Vec<&str> = line.split_whitespace().collect();
+                    let mut rig = rig.lock().unwrap();
+                    let reply = match words.as_slice() {
+                        ["f"] => format!("{}\n", rig.frequency),
+                        ["m"] => format!("{}\n{}\n", rig.mode, rig.passband),
+                        ["F", hz] => {
+                            rig.frequency = hz.parse().unwrap();
+                            "RPRT 0\n".to_string()
+                        }
+                        ["M", mode, passband] if MODES.contains(mode) => {
+                            rig.mode = mode.to_string();
+                            if *passband != "0" {
+                                rig.passband = passband.parse().unwrap();
+                            }
+                            "RPRT 0\n".to_string()
+                        }
+                        _ => "RPRT -1\n".to_string(),
+                    };
+                    if stream.write_all(reply.as_bytes()).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    (address, state)
+}
+
+fn run_until(
+    rig: &mut RigControl,
+    config: &mut Configuration,
+    done: impl Fn(&Configuration) -> bool,
+) {
+    let deadline = Instant::now() + TIMEOUT;
+    while !done(config) {
+        assert!(Instant::now() < deadline, "status {:?}", rig.status);
+        rig.run(config);
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn test_rigctld() {
+    let (address, _) = serve(RigState {
+        frequency: 14_074_000.0,
+        mode: "USB".to_string(),
+        passband: 2400.0,
+    });
+    let mut rig = Rigctld::connect(&address).unwrap();
+
+    assert_eq!(rig.frequency().unwrap(), 14_074_000.0);
+    rig.set_frequency(7_040_000.0).unwrap();
+    assert_eq!(rig.frequency().unwrap(), 7_040_000.0);
+
+    rig.set_mode("PKTLSB", 3000.0).unwrap();
+    assert_eq!(rig.mode().unwrap(), ("PKTLSB".to_string(), 3000.0));
+    assert!(rig.set_mode("WSPR", 0.0).is_err());
+
+    // Still in step after an error.
+    assert_eq!(rig.frequency().unwrap(), 7_040_000.0);
+
+    // Finer than an f32 can hold up here.
+    rig.set_frequency(50_313_001.0).unwrap();
+    assert_eq!(rig.frequency().unwrap(), 50_313_001.0);
+}
+
+#[test]
+fn test_sync() {
+    let (address, radio) = serve(RigState {
+        frequency: 14_080_000.0,
+        mode: "PKTUSB".to_string(),
+        passband: 3000.0,
+    });
+
+    let mut config = Configuration::default();
+    config.rig.enabled = true;
+    config.rig.address = address;
+    let mut rig = RigControl::default();
+
+    // The radio sets the dial.
+    run_until(&mut rig, &mut config, |config| {
+        config.dial_frequency == 14_080_000.0
+    });
+    assert_eq!(config.sideband, Sideband::Usb);
+    assert_eq!(config.rig.passband, 3000.0);
+
+    // The dial sets the radio.
+    config.dial_frequency = 7_040_000.0;
+    config.sideband = Sideband::Lsb;
+    let deadline = Instant::now() + TIMEOUT;
+    while radio.lock().unwrap().mode != "PKTLSB" {
+        assert!(Instant::now() < deadline, "status {:?}", rig.status);
+        rig.run(&mut config);
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(radio.lock().unwrap().frequency, 7_040_000.0);
+
+    // Turning the radio's knob moves the dial, and nothing goes back.
+    radio.lock().unwrap().frequency = 7_041_000.0;
+    run_until(&mut rig, &mut config, |config| {
+        config.dial_frequency == 7_041_000.0
+    });
+    assert_eq!(config.sideband, Sideband::Lsb);
+
+    // And on 6 m, to the hertz both ways.
+    radio.lock().unwrap().frequency = 50_313_001.0;
+    run_until(&mut rig, &mut config, |config| {
+        config.dial_frequency == 50_313_001.0
+    });
+    config.dial_frequency = 50_313_002.0;
+    let deadline = Instant::now() + TIMEOUT;
+    while radio.lock().unwrap().frequency != 50_313_002.0 {
+        assert!(Instant::now() < deadline, "status {:?}", rig.status);
+        rig.run(&mut config);
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    config.rig.enabled = false;
+    rig.run(&mut config);
+    assert_eq!(rig.status, None);
+}
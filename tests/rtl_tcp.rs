@@ -0,0 +1,156 @@
+mod common;
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc::{self as std_mpsc, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc;
+
+use waterfall_rs::configuration::{Configuration, TunerSettings};
+use waterfall_rs::input::{Command, InputSource, RtlTcp, Source};
+use waterfall_rs::types::{ComplexType, Samples};
+
+use common::*;
+
+// What the client asks for at 44.1 kHz: six times the audio rate, tuned a
+// quarter of that above the center frequency.
+const DEVICE_RATE: u32 = 264_600;
+const TUNING_OFFSET: u32 = DEVICE_RATE / 4;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+fn to_cu8(samples: &[ComplexType]) -> Vec<u8> {
+    let quantize = |v: f32| (v * 127.5 + 127.5).round().clamp(0.0, 255.0) as u8;
+    samples
+        .iter()
+        .flat_map(|s| [quantize(s.re), quantize(s.im)])
+        .collect()
+}
+
+/// Stands in for rtl_tcp: serves `recording` to the first client, then
+/// hangs up. Commands from the client are passed back.
+fn serve(recording: Vec<u8>) -> (String, std_mpsc::Receiver<Command>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let (command_tx, command_rx) = std_mpsc::channel();
+
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+
+        let mut reader = stream.try_clone().unwrap();
+        thread::spawn(move || {
+            let mut bytes = [0; 5];
+            while reader.read_exact(&mut bytes).is_ok() {
+                if let Some(command) = Command::from_bytes(bytes) {
+                    let _ = command_tx.send(command);
+                }
+            }
+        });
+
+        // R820T, 29 gain steps
+        stream.write_all(b"RTL0\0\0\0\x05\0\0\0\x1d").unwrap();
+        let _ = stream.write_all(&recording);
+    });
+
+    (address, command_rx)
+}
+
+fn rtl_tcp_config(address: String) -> Configuration {
+    let mut config = Configuration {
+        input_source: InputSource::RtlTcp,
+        center_frequency: 14_080_000.0,
+        ..Default::default()
+    };
+    config.rtl_tcp.address = address;
+    config
+}
+
+// The client connects on a thread of its own, and says so when it's run.
+fn wait_until_connected(source: &mut RtlTcp, config: &Configuration) {
+    let deadline = Instant::now() + TIMEOUT;
+    loop {
+        source.run(config);
+        let status = source.status().unwrap_or_default();
+        if status.starts_with("Connected") {
+            return;
+        }
+        assert!(Instant::now() < deadline, "{}", status);
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn test_commands() {
+    let (address, commands) = serve(Vec::new());
+    let mut config = rtl_tcp_config(address);
+
+    let (sample_tx, _sample_rx) = mpsc::channel::<Samples>(1024);
+    let mut source = RtlTcp::new(sample_tx, &config);
+    wait_until_connected(&mut source, &config);
+
+    let expect = |expected: Command| {
+        assert_eq!(commands.recv_timeout(TIMEOUT).unwrap(), expected);
+    };
+    expect(Command::SetSampleRate(DEVICE_RATE));
+    expect(Command::SetFrequency(14_080_000 + TUNING_OFFSET));
+    expect(Command::SetFrequencyCorrection(0));
+    expect(Command::SetGainMode(false));
+
+    // Finer than an f32 can hold up here.
+    config.center_frequency = 433_920_001.0;
+    config.rtl_tcp.agc = false;
+    config.rtl_tcp.gain = 20.7;
+    source.run(&config);
+
+    expect(Command::SetFrequency(433_920_001 + TUNING_OFFSET));
+    expect(Command::SetFrequencyCorrection(0));
+    expect(Command::SetGainMode(true));
+    expect(Command::SetGain(207));
+
+    drop(source);
+    assert_eq!(
+        commands.recv_timeout(TIMEOUT),
+        Err(RecvTimeoutError::Disconnected)
+    );
+}
+
+#[test]
+fn test_decode() {
+    // 2 kHz above the center frequency.
+    let space_hz = 2000.0;
+    let text = "CQ CQ DE W1AW K";
+    let recording = iq_afsk(
+        &format!("{}\r\n", text),
+        DEVICE_RATE as f32,
+        space_hz - TUNING_OFFSET as f32,
+    );
+    let expected_len = recording.len() / (DEVICE_RATE / 44100) as usize;
+
+    let (address, _commands) = serve(to_cu8(&recording));
+    let mut config = rtl_tcp_config(address);
+    config.tuner = TunerSettings {
+        carrier: space_hz - BAUD,
+        ..Default::default()
+    };
+
+    let (sample_tx, mut sample_rx) = mpsc::channel::<Samples>(1024);
+    let _source = RtlTcp::new(sample_tx, &config);
+
+    // The last partial read is lost when the server hangs up.
+    let deadline = Instant::now() + TIMEOUT;
+    let mut samples = Vec::new();
+    while samples.len() + 8192 < expected_len {
+        assert!(Instant::now() < deadline, "only {} samples", samples.len());
+        match sample_rx.try_recv() {
+            Ok(Samples::Complex(new_samples)) => samples.extend(new_samples),
+            Ok(Samples::Real(_)) => panic!("expected complex samples"),
+            Err(_) => thread::sleep(Duration::from_millis(10)),
+        }
+    }
+
+    let payloads = decode_iq(&samples, &config);
+
+    assert_eq!(payloads, vec![text.to_string()]);
+}
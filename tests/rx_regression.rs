@@ -0,0 +1,492 @@
+mod common;
+
+use std::f32::consts::PI;
+
+use tokio::sync::mpsc;
+
+use waterfall_rs::configuration::{
+    AudioSampleRate, Configuration, GeneratorSettings, MonitorSettings, NoiseSettings, Probe,
+    ScopeSettings, ScopeView, TunerSettings, Waveform,
+};
+use waterfall_rs::dsp::generator::SignalGenerator;
+use waterfall_rs::dsp::rx::Rx;
+use waterfall_rs::input::InputSource;
+use waterfall_rs::scope::Scope;
+use waterfall_rs::statistics::Statistics;
+
+use common::*;
+
+const PANGRAM: &str = "THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG 0123456789";
+
+#[test]
+fn test_decode_example_wav() {
+    let config = Configuration::default();
+    let (samples, sample_rate) = read_wav("example_signals/RTTY_170Hz_45.45Bd.wav");
+    assert_eq!(sample_rate, config.audio_sample_rate.as_frequency().value());
+
+    let payloads = decode(&samples, &config);
+
+    assert!(payloads.iter().any(|p| p == PANGRAM), "{:?}", payloads);
+}
+
+#[test]
+fn test_decode_synthetic() {
+    let config = Configuration::default();
+    let sample_rate = config.audio_sample_rate.as_frequency().value();
+    let text = "CQ CQ DE W1AW/4 K, RST 599?";
+    let samples = rtty_afsk(&format!("{}\r\n", text), sample_rate);
+
+    let payloads = decode(&samples, &config);
+
+    assert_eq!(payloads, vec![text.to_string()]);
+}
+
+#[test]
+fn test_decode_retuned() {
+    let carrier = 2125.0;
+    let config = Configuration {
+        tuner: TunerSettings {
+            carrier,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let sample_rate = config.audio_sample_rate.as_frequency().value();
+    let text = "CQ CQ DE W1AW/4 K";
+    let space_hz = carrier + BAUD;
+    let mut samples = rtty_afsk_at(&format!("{}\r\n", text), sample_rate, space_hz);
+
+    // Something at the decoder's own tones must be left out.
+    let interferer = rtty_afsk("RYRYRYRYRYRYRY\r\n", sample_rate);
+    samples
+        .iter_mut()
+        .zip(interferer)
+        .for_each(|(sample, interferer)| *sample += interferer);
+
+    let messages = decode_messages(&samples, &config);
+
+    let payloads: Vec<String> = messages.iter().map(|message| message.payload()).collect();
+    assert_eq!(payloads, vec![text.to_string()]);
+    assert_eq!(messages[0].frequency(), Some(carrier as f64));
+}
+
+#[test]
+fn test_decode_iq() {
+    let space_hz = -3000.0;
+    let config = Configuration {
+        input_source: InputSource::IqFile,
+        tuner: TunerSettings {
+            carrier: space_hz - BAUD,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let sample_rate = config.audio_sample_rate.as_frequency().value();
+    let text = "CQ CQ DE W1AW/4 K";
+    let mut samples = iq_afsk(&format!("{}\r\n", text), sample_rate, space_hz);
+
+    // An interferer at the image frequency must not get in the way.
+    let image = iq_afsk(
+        "RYRYRYRYRYRYRY\r\n",
+        sample_rate,
+        -space_hz - (MARK_HZ - SPACE_HZ),
+    );
+    samples
+        .iter_mut()
+        .zip(image)
+        .for_each(|(sample, image)| *sample += image);
+
+    let payloads = decode_iq(&samples, &config);
+
+    assert_eq!(payloads, vec![text.to_string()]);
+}
+
+/// RMS of what `Rx` passes on to the scope for a tone at `hz`, once the
+/// filter has settled.
+fn scope_rms(hz: f32, config: &Configuration) -> f32 {
+    let (plot_tx, mut plot_rx) = mpsc::channel(1024);
+    let mut rx = Rx::new(config).with_plot_sender(plot_tx);
+    let mut stats = Statistics::default();
+
+    let sample_rate = config.audio_sample_rate.as_frequency().value();
+    let samples: Vec<f32> = (0..2 * sample_rate as usize)
+        .map(|i| (2. * PI * hz * i as f32 / sample_rate).sin())
+        .collect();
+    for chunk in samples.chunks(1024) {
+        rx.run(chunk.to_vec(), config, &mut stats);
+    }
+
+    let mut output = Vec::new();
+    while let Ok(traces) = plot_rx.try_recv() {
+        for trace in traces {
+            if trace.probe == Probe::Channel {
+                output.extend(trace.samples);
+            }
+        }
+    }
+    let settled = &output[output.len() / 2..];
+    (settled.iter().map(|x| x * x).sum::<f32>() / settled.len() as f32).sqrt()
+}
+
+#[test]
+fn test_prefilter() {
+    let config = Configuration {
+        tuner: TunerSettings {
+            carrier: 1000.0,
+            lower: -200.0,
+            upper: 400.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let in_band = scope_rms(1100.0, &config);
+    assert!(in_band > 0.6, "in band {}", in_band);
+
+    for hz in [300.0, 2500.0] {
+        let out_of_band = scope_rms(hz, &config);
+        assert!(out_of_band < 0.01, "{} Hz: {}", hz, out_of_band);
+    }
+}
+
+#[test]
+fn test_scope_probes() {
+    let config = Configuration {
+        scope: ScopeSettings {
+            probes: vec![Probe::Channel, Probe::Discriminator],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (plot_tx, mut plot_rx) = mpsc::channel(1024);
+    let mut rx = Rx::new(&config).with_plot_sender(plot_tx);
+    let mut stats = Statistics::default();
+
+    // Steady mark.
+    let sample_rate = config.audio_sample_rate.as_frequency().value();
+    let samples: Vec<f32> = (0..2 * sample_rate as usize)
+        .map(|i| (2. * PI * MARK_HZ * i as f32 / sample_rate).sin())
+        .collect();
+    for chunk in samples.chunks(1024) {
+        rx.run(chunk.to_vec(), &config, &mut stats);
+    }
+
+    let mut discriminator = Vec::new();
+    while let Ok(traces) = plot_rx.try_recv() {
+        let probes: Vec<Probe> = traces.iter().map(|trace| trace.probe).collect();
+        assert_eq!(probes, config.scope.probes);
+        // Each covers the same stretch of time, at its own rate.
+        let seconds: Vec<f32> = traces
+            .iter()
+            .map(|trace| trace.samples.len() as f32 / trace.sample_rate.value())
+            .collect();
+        assert!((seconds[0] - seconds[1]).abs() < 0.01, "{:?}", seconds);
+        discriminator.extend(traces[1].samples.iter().copied());
+    }
+
+    let settled = &discriminator[discriminator.len() / 2..];
+    assert!(settled.iter().all(|x| *x > 0.0), "space on a mark tone");
+}
+
+#[test]
+fn test_eye_diagram() {
+    let mut config = Configuration {
+        scope: ScopeSettings {
+            view: ScopeView::Eye,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (plot_tx, plot_rx) = mpsc::channel(1024);
+    let mut rx = Rx::new(&config).with_plot_sender(plot_tx);
+    let mut scope = Scope::new(plot_rx);
+    let mut stats = Statistics::default();
+
+    let sample_rate = config.audio_sample_rate.as_frequency().value();
+    let mut samples = rtty_afsk(&format!("{}\r\n", PANGRAM), sample_rate);
+    add_awgn(&mut samples, 20.0, sample_rate, 7);
+    for chunk in samples.chunks(1024) {
+        rx.run(chunk.to_vec(), &config, &mut stats);
+        scope.run(&mut config);
+    }
+
+    // Every bit the decoder sampled lands in the open part of the eye.
+    let eye = scope.eye().unwrap();
+    assert!(eye.segments().count() > 100);
+    let opening = eye.opening().unwrap();
+    assert!(opening > 0.5, "{}", opening);
+}
+
+#[test]
+fn test_constellation() {
+    let mut config = Configuration {
+        tuner: TunerSettings {
+            carrier: 1500.0,
+            lower: -100.0,
+            upper: 100.0,
+            ..Default::default()
+        },
+        scope: ScopeSettings {
+            view: ScopeView::Constellation,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (plot_tx, plot_rx) = mpsc::channel(1024);
+    let mut rx = Rx::new(&config).with_plot_sender(plot_tx);
+    let mut scope = Scope::new(plot_rx);
+    let mut stats = Statistics::default();
+
+    let settings = GeneratorSettings {
+        waveform: Waveform::Psk,
+        frequency: 1500.0,
+        baud: 31.25,
+        noise: true,
+        snr_db: 20.0,
+        ..Default::default()
+    };
+    let mut generator =
+        SignalGenerator::new(&settings, config.audio_sample_rate.as_frequency()).with_seed(1);
+    // Ten seconds, measured once the carrier and timing have locked.
+    for block in 0..40 {
+        rx.run(generator.generate(11025), &config, &mut stats);
+        scope.run(&mut config);
+        if block == 8 {
+            scope.clear_constellation();
+        }
+    }
+
+    let constellation = scope.constellation();
+    assert!(constellation.points().count() > 200);
+    let stats = constellation.stats().unwrap();
+    assert!(stats.mer > 15.0, "{:?}", stats);
+    assert!(stats.phase_error < 10.0, "{:?}", stats);
+}
+
+#[test]
+fn test_monitor() {
+    let config = Configuration {
+        tuner: TunerSettings {
+            carrier: 1000.0,
+            lower: -300.0,
+            upper: 500.0,
+            ..Default::default()
+        },
+        monitor: MonitorSettings {
+            enabled: true,
+            shift: true,
+            pitch: 600.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (monitor_tx, mut monitor_rx) = mpsc::channel(1024);
+    let mut rx = Rx::new(&config).with_monitor_sender(monitor_tx);
+    let mut stats = Statistics::default();
+
+    let sample_rate = config.audio_sample_rate.as_frequency().value();
+    let samples: Vec<f32> = (0..2 * sample_rate as usize)
+        .map(|i| {
+            let t = i as f32 / sample_rate;
+            // One in the channel and one outside it.
+            (2. * PI * 1100. * t).sin() + (2. * PI * 2800. * t).sin()
+        })
+        .collect();
+    for chunk in samples.chunks(1024) {
+        rx.run(chunk.to_vec(), &config, &mut stats);
+    }
+
+    let mut output = Vec::new();
+    while let Ok(samples) = monitor_rx.try_recv() {
+        output.extend(samples);
+    }
+    let settled = &output[output.len() / 2..];
+    let amplitude = |hz: f32| {
+        let (i, q) = settled.iter().enumerate().fold((0., 0.), |(i, q), (n, x)| {
+            let phase = 2. * PI * hz * n as f32 / sample_rate;
+            (i + x * phase.cos(), q + x * phase.sin())
+        });
+        2. * (i * i + q * q).sqrt() / settled.len() as f32
+    };
+
+    // The channel's tone moves with the carrier, and nothing else is left.
+    assert!(
+        (amplitude(700.0) - 1.0).abs() < 0.05,
+        "{}",
+        amplitude(700.0)
+    );
+    for hz in [1100.0, 2800.0, 2400.0] {
+        assert!(amplitude(hz) < 0.01, "{} Hz: {}", hz, amplitude(hz));
+    }
+}
+
+/// The noise stages must not get in the way of a signal they have adapted to.
+#[test]
+fn test_decode_through_noise_stages() {
+    let config = Configuration {
+        noise: NoiseSettings {
+            noise_reduction: true,
+            blanker: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let sample_rate = config.audio_sample_rate.as_frequency().value();
+    let text = "CQ CQ DE W1AW/4 K";
+    let mut samples = rtty_afsk(&format!("{}\r\n", text), sample_rate);
+    add_awgn(&mut samples, 10.0, sample_rate, 3);
+    // Ignition noise.
+    for start in (5000..samples.len() - 1).step_by(2000) {
+        samples[start] += 30.0;
+        samples[start + 1] -= 30.0;
+    }
+
+    let payloads = decode(&samples, &config);
+
+    assert_eq!(payloads, vec![text.to_string()]);
+}
+
+/// The notch takes out a steady carrier, but the RTTY tones keep moving, so
+/// it must leave them alone.
+#[test]
+fn test_decode_through_notch() {
+    let config = Configuration {
+        noise: NoiseSettings {
+            notch: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let sample_rate = config.audio_sample_rate.as_frequency().value();
+    let text = "CQ CQ DE W1AW/4 K";
+    let mut samples = rtty_afsk(&format!("{}\r\n", text), sample_rate);
+    // A carrier between the tones, loud enough to ruin the copy without the
+    // notch.
+    let carrier = (MARK_HZ + SPACE_HZ) / 2.0;
+    for (i, sample) in samples.iter_mut().enumerate() {
+        *sample += 10.0 * (2.0 * PI * carrier * i as f32 / sample_rate).sin();
+    }
+
+    let payloads = decode(&samples, &config);
+
+    // The carrier stopping can decode as a stray character after the message.
+    assert_eq!(payloads.first(), Some(&text.to_string()));
+}
+
+#[test]
+fn test_decode_generator() {
+    let config = Configuration::default();
+    let settings = GeneratorSettings {
+        noise: true,
+        snr_db: 10.0,
+        ..Default::default()
+    };
+    let mut generator =
+        SignalGenerator::new(&settings, config.audio_sample_rate.as_frequency()).with_seed(1);
+    let samples = generator.generate(12 * 44100);
+
+    let payloads = decode(&samples, &config);
+
+    assert!(payloads.iter().any(|p| p == PANGRAM), "{:?}", payloads);
+}
+
+#[test]
+fn test_character_error_rate_vs_snr() {
+    let config = Configuration::default();
+    let sample_rate = config.audio_sample_rate.as_frequency().value();
+    let clean = rtty_afsk(&format!("{}\r\n", PANGRAM), sample_rate);
+
+    let mut results = Vec::new();
+    for snr_db in [30.0, 20.0, 10.0, 5.0, 0.0, -5.0] {
+        let mut samples = clean.clone();
+        add_awgn(&mut samples, snr_db, sample_rate, 0x5eed);
+        let decoded = decode(&samples, &config).join("");
+        results.push((snr_db, character_error_rate(PANGRAM, &decoded)));
+    }
+
+    // The whole sweep goes in the message, to show where decoding falls off.
+    for (snr_db, cer) in &results {
+        if *snr_db >= 10.0 {
+            assert_eq!(
+                *cer, 0.0,
+                "errors at {} dB SNR; (SNR, CER): {:?}",
+                snr_db, results
+            );
+        }
+    }
+}
+
+#[test]
+fn test_streaming_text() {
+    for audio_sample_rate in [AudioSampleRate::F8000, AudioSampleRate::F44100] {
+        let config = Configuration {
+            audio_sample_rate,
+            ..Default::default()
+        };
+        let sample_rate = audio_sample_rate.as_frequency().value();
+        let samples = rtty_afsk(&format!("CQ CQ\r\n{}\r\n", PANGRAM), sample_rate);
+
+        let events = decode_text_events(&samples, &config);
+        let text = events.concat();
+
+        assert!(
+            text.ends_with(&format!("CQ CQ\n{}\n", PANGRAM)),
+            "{:?}",
+            text
+        );
+        // Characters take 165 ms at 45.45 Bd, so text that goes out a slice
+        // at a time comes a character or two at once, at any input rate.
+        assert!(
+            events.iter().all(|text| text.chars().count() <= 2),
+            "{:?} at {}",
+            events,
+            audio_sample_rate
+        );
+    }
+}
+
+#[test]
+fn test_snr() {
+    // The offset to the quoted bandwidth comes from the decoder's own
+    // filters, after resampling, so it holds whatever the input rate.
+    for audio_sample_rate in [AudioSampleRate::F8000, AudioSampleRate::F44100] {
+        let config = Configuration {
+            audio_sample_rate,
+            ..Default::default()
+        };
+        let sample_rate = audio_sample_rate.as_frequency().value();
+        let clean = rtty_afsk(&format!("{}\r\n", PANGRAM), sample_rate);
+
+        for snr_db in [30.0, 20.0, 10.0, 0.0] {
+            // Averaged over a few noise seeds, as one message only has so
+            // many bits to measure.
+            let mut measured = Vec::new();
+            for seed in 0..4 {
+                let mut samples = clean.clone();
+                add_awgn(&mut samples, snr_db, sample_rate, seed);
+                // Noise can decode as a stray character or two on its own.
+                let message = decode_messages(&samples, &config)
+                    .into_iter()
+                    .find(|message| message.payload() == PANGRAM)
+                    .expect("the pangram is decoded");
+                measured.push(message.snr().unwrap());
+            }
+            let mean = measured.iter().sum::<f32>() / measured.len() as f32;
+            assert!(
+                (mean - snr_db).abs() < 1.0,
+                "{} dB at {} measured as {:?}",
+                snr_db,
+                audio_sample_rate,
+                measured
+            );
+        }
+    }
+}
+
+#[test]
+fn test_character_error_rate() {
+    assert_eq!(character_error_rate("RTTY", "RTTY"), 0.0);
+    assert_eq!(character_error_rate("RTTY", "RTTX"), 0.25);
+    assert_eq!(character_error_rate("RTTY", "RTY"), 0.25);
+    assert_eq!(character_error_rate("RTTY", ""), 1.0);
+}